@@ -78,6 +78,30 @@ fn main() {
     code.push_str("        }\n");
     code.push_str("    }\n\n");
 
+    code.push_str("    pub fn author(&self) -> Option<&'static str> {\n");
+    code.push_str("        match self {\n");
+    for (filename, metadata) in &map_entries {
+        let variant_name = filename_to_variant_name(filename);
+        match &metadata.author {
+            Some(author) => code.push_str(&format!("            MapType::{} => Some(\"{}\"),\n", variant_name, author)),
+            None => code.push_str(&format!("            MapType::{} => None,\n", variant_name)),
+        }
+    }
+    code.push_str("        }\n");
+    code.push_str("    }\n\n");
+
+    code.push_str("    pub fn difficulty(&self) -> Option<u8> {\n");
+    code.push_str("        match self {\n");
+    for (filename, metadata) in &map_entries {
+        let variant_name = filename_to_variant_name(filename);
+        match metadata.difficulty {
+            Some(difficulty) => code.push_str(&format!("            MapType::{} => Some({}),\n", variant_name, difficulty)),
+            None => code.push_str(&format!("            MapType::{} => None,\n", variant_name)),
+        }
+    }
+    code.push_str("        }\n");
+    code.push_str("    }\n\n");
+
     code.push_str("    pub fn create_map(&self, _width: usize, _height: usize) -> Result<crate::map::GridMap, Box<dyn std::error::Error>> {\n");
     code.push_str("        let json_content = match self {\n");
     for (filename, _) in &map_entries {
@@ -98,6 +122,8 @@ fn main() {
 struct MapMetadata {
     name: String,
     description: String,
+    author: Option<String>,
+    difficulty: Option<u8>,
 }
 
 fn extract_map_metadata(json_content: &str) -> Result<MapMetadata, Box<dyn std::error::Error>> {
@@ -110,8 +136,15 @@ fn extract_map_metadata(json_content: &str) -> Result<MapMetadata, Box<dyn std::
         .and_then(|v| v.as_str())
         .unwrap_or("")
         .to_string();
+    // Author and difficulty are optional - older maps don't have them
+    let author = value.get("author")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+    let difficulty = value.get("difficulty")
+        .and_then(|v| v.as_u64())
+        .map(|d| d.min(u8::MAX as u64) as u8);
 
-    Ok(MapMetadata { name, description })
+    Ok(MapMetadata { name, description, author, difficulty })
 }
 
 fn filename_to_variant_name(filename: &str) -> String {
@@ -1,8 +1,19 @@
-use crate::map::{GridMap, TileKind};
-use crate::openrouter::{Function, Message, OpenRouterEvent, Tool, open_router_event_stream};
+use crate::clock::{RealClock, SharedClock};
+use crate::map::{GridMap, TileKind, TileCosts, Topology, TraversabilityRules};
+use crate::model_presets::ModelPreset;
+use crate::pathfinding::{
+    cheapest_path, move_distance, shortest_path, trace_search, SearchAlgorithm,
+};
+use crate::openrouter::{
+    Function, Message, OpenRouterEvent, Tool, build_chat_request, open_router_completion,
+    open_router_event_stream,
+};
+use crate::route::{explain_route, measure_distances};
+use eframe::egui;
 use futures::stream::StreamExt;
 use serde_json::{Value, json};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use web_time::{Duration, Instant};
 
@@ -31,22 +42,117 @@ pub enum LogEntry {
         direction: String,
         position: (usize, usize),
     },
-    /// Error message
-    Error(String),
+    /// Agent was moved by a portal
+    Teleport {
+        portal: u8,
+        from: (usize, usize),
+        to: (usize, usize),
+    },
+    /// Error message, tagged with what went wrong
+    Error(ErrorKind, String),
     /// General info message
     Info(String),
+    /// Tokens (and cost, when reported) used by one LLM turn
+    Usage {
+        prompt_tokens: u64,
+        completion_tokens: u64,
+        cost: Option<f64>,
+    },
+}
+
+/// Category of a logged error, so the UI and tool results can tell them apart
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// A move was blocked by terrain or the map edge
+    Movement,
+    /// The LLM request or its stream failed
+    Network,
+    /// User input or loaded data was rejected
+    Validation,
+    /// A tool call failed or reported a problem
+    Tool,
+    /// Something that should not happen (e.g. a bundled map failed to build)
+    Internal,
+}
+
+impl ErrorKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Movement => "Movement",
+            Self::Network => "Network",
+            Self::Validation => "Validation",
+            Self::Tool => "Tool",
+            Self::Internal => "Internal",
+        }
+    }
 }
 
 /// Direction for agent movement
+///
+/// The diagonal variants are hex moves on hex maps (see `Topology::Hex`) and true
+/// diagonals on square maps, where agents only take them with diagonal movement on.
+/// Which set a map allows is given by `Direction::for_topology`.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Direction {
     Up,
     Down,
     Left,
     Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
 }
 
 impl Direction {
+    /// Moves available on a map with the given topology, in search order
+    pub fn for_topology(topology: Topology) -> &'static [Direction] {
+        match topology {
+            Topology::Square => &[Self::Up, Self::Down, Self::Left, Self::Right],
+            Topology::Hex => &[
+                Self::UpLeft,
+                Self::UpRight,
+                Self::DownLeft,
+                Self::DownRight,
+                Self::Left,
+                Self::Right,
+            ],
+        }
+    }
+
+    /// Moves an agent may take: `for_topology`, plus the four diagonals on square maps
+    /// when `diagonals` is on
+    pub fn available(topology: Topology, diagonals: bool) -> &'static [Direction] {
+        match topology {
+            Topology::Square if diagonals => &[
+                Self::Up,
+                Self::Down,
+                Self::Left,
+                Self::Right,
+                Self::UpLeft,
+                Self::UpRight,
+                Self::DownLeft,
+                Self::DownRight,
+            ],
+            _ => Self::for_topology(topology),
+        }
+    }
+
+    /// The two orthogonal moves a square-grid diagonal passes between, or None for other
+    /// moves (hex diagonals are plain neighbors, with no corner to cut)
+    pub fn corner_steps(&self, topology: Topology) -> Option<[Direction; 2]> {
+        if topology != Topology::Square {
+            return None;
+        }
+        match self {
+            Direction::UpLeft => Some([Direction::Up, Direction::Left]),
+            Direction::UpRight => Some([Direction::Up, Direction::Right]),
+            Direction::DownLeft => Some([Direction::Down, Direction::Left]),
+            Direction::DownRight => Some([Direction::Down, Direction::Right]),
+            _ => None,
+        }
+    }
+
     /// Parse direction from string
     pub fn from_str(s: &str) -> Result<Self, String> {
         match s.to_lowercase().as_str() {
@@ -54,6 +160,10 @@ impl Direction {
             "down" => Ok(Direction::Down),
             "left" => Ok(Direction::Left),
             "right" => Ok(Direction::Right),
+            "up_left" => Ok(Direction::UpLeft),
+            "up_right" => Ok(Direction::UpRight),
+            "down_left" => Ok(Direction::DownLeft),
+            "down_right" => Ok(Direction::DownRight),
             _ => Err(format!("Invalid direction: {}", s)),
         }
     }
@@ -65,38 +175,360 @@ impl Direction {
             Direction::Down => "down",
             Direction::Left => "left",
             Direction::Right => "right",
+            Direction::UpLeft => "up_left",
+            Direction::UpRight => "up_right",
+            Direction::DownLeft => "down_left",
+            Direction::DownRight => "down_right",
+        }
+    }
+
+    /// Compass name, with the top of the board as north
+    pub fn compass(&self) -> &'static str {
+        match self {
+            Direction::Up => "north",
+            Direction::Down => "south",
+            Direction::Left => "west",
+            Direction::Right => "east",
+            Direction::UpLeft => "north-west",
+            Direction::UpRight => "north-east",
+            Direction::DownLeft => "south-west",
+            Direction::DownRight => "south-east",
+        }
+    }
+
+    /// Cell offset of one step from row `y`
+    ///
+    /// Hex moves depend on the row: odd rows are shifted right, so their diagonal
+    /// neighbors sit one column further right than an even row's. On square maps the
+    /// diagonals are plain (±1, ±1) steps.
+    pub fn offset(&self, topology: Topology, y: i64) -> (i64, i64) {
+        let shift = y.rem_euclid(2);
+        match (self, topology) {
+            (Direction::Up, _) => (0, -1),
+            (Direction::Down, _) => (0, 1),
+            (Direction::Left, _) => (-1, 0),
+            (Direction::Right, _) => (1, 0),
+            (Direction::UpLeft, Topology::Square) => (-1, -1),
+            (Direction::UpRight, Topology::Square) => (1, -1),
+            (Direction::DownLeft, Topology::Square) => (-1, 1),
+            (Direction::DownRight, Topology::Square) => (1, 1),
+            (Direction::UpLeft, Topology::Hex) => (shift - 1, -1),
+            (Direction::UpRight, Topology::Hex) => (shift, -1),
+            (Direction::DownLeft, Topology::Hex) => (shift - 1, 1),
+            (Direction::DownRight, Topology::Hex) => (shift, 1),
         }
     }
 
     /// Apply direction to position, returning new position
-    pub fn apply(&self, x: i32, y: i32, map_width: i32, map_height: i32) -> (i32, i32) {
+    ///
+    /// The result may lie off the map; callers check bounds before using it.
+    pub fn apply(&self, topology: Topology, x: i32, y: i32) -> (i32, i32) {
+        let (dx, dy) = self.offset(topology, y as i64);
+        (x + dx as i32, y + dy as i32)
+    }
+}
+
+/// How the agent is drawn on the board
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AgentSprite {
+    #[default]
+    Circle,
+    /// Triangle pointing the way the agent last moved
+    Triangle,
+    /// Generated robot bitmap, tinted with the agent color
+    Robot,
+}
+
+impl AgentSprite {
+    pub const ALL: [AgentSprite; 3] = [Self::Circle, Self::Triangle, Self::Robot];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Circle => "Circle",
+            Self::Triangle => "Triangle",
+            Self::Robot => "Robot",
+        }
+    }
+}
+
+/// LLM tokens and cost added up over the turns logged since the last reset
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TokenUsage {
+    pub turns: usize,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    /// Credits (USD) reported by OpenRouter; turns without a reported cost add nothing
+    pub cost: f64,
+}
+
+impl TokenUsage {
+    pub fn total_tokens(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+
+    /// One-line summary, e.g. "12345 tokens (11000 prompt + 1345 completion) in 4 turns"
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "{} tokens ({} prompt + {} completion) in {} turns",
+            self.total_tokens(),
+            self.prompt_tokens,
+            self.completion_tokens,
+            self.turns
+        );
+        if self.cost > 0.0 {
+            summary.push_str(&format!(", ${:.4}", self.cost));
+        }
+        summary
+    }
+}
+
+/// Statistics for a single run (one user instruction and its continuations)
+#[derive(Clone, Debug, Default)]
+pub struct RunStats {
+    /// The user instruction that started the run
+    pub instruction: String,
+    pub started_at: Option<Instant>,
+    pub ended_at: Option<Instant>,
+    pub moves: usize,
+    pub tool_calls: usize,
+    pub llm_turns: usize,
+    /// Error log entries recorded while the run was active
+    pub errors: usize,
+    /// Positions the agent occupied during the run, starting with the start position
+    pub path: Vec<(usize, usize)>,
+    /// Each move as (cell left, cell entered), portal landings excluded, used to price
+    /// the route
+    pub entered: Vec<((usize, usize), (usize, usize))>,
+    /// Comparison with the shortest route, filled in when the run finishes
+    pub route: Option<RouteComparison>,
+}
+
+/// How the walked route compares to the shortest route between the same endpoints
+#[derive(Clone, Copy, Debug)]
+pub struct RouteComparison {
+    pub actual_moves: usize,
+    /// None if the pathfinder couldn't connect start and end (e.g. the map changed mid-run)
+    pub optimal_moves: Option<usize>,
+    /// Total movement cost of the walked route under the current tile costs
+    pub actual_cost: u32,
+    /// Cost of the cheapest route, None if unreachable
+    pub optimal_cost: Option<u32>,
+}
+
+impl RouteComparison {
+    /// Optimal / actual moves (1.0 = perfect); None if unknown or the agent didn't move
+    pub fn efficiency(&self) -> Option<f32> {
+        let optimal = self.optimal_moves?;
+        if self.actual_moves == 0 {
+            return None;
+        }
+        Some(optimal as f32 / self.actual_moves as f32)
+    }
+
+    /// Short description, e.g. "route efficiency 80% (8 optimal vs 10 moves)"
+    pub fn summary(&self) -> String {
+        match (self.efficiency(), self.optimal_moves) {
+            (Some(efficiency), Some(optimal)) => format!(
+                "route efficiency {:.0}% ({} optimal vs {} moves)",
+                efficiency * 100.0,
+                optimal,
+                self.actual_moves
+            ),
+            (None, Some(_)) => "no moves made".to_string(),
+            (_, None) => "optimal route unknown (no path between start and end)".to_string(),
+        }
+    }
+
+    /// Cost comparison, e.g. "cost 14 vs 11 cheapest"
+    pub fn cost_summary(&self) -> String {
+        match self.optimal_cost {
+            Some(optimal) => format!("cost {} vs {} cheapest", self.actual_cost, optimal),
+            None => format!("cost {}", self.actual_cost),
+        }
+    }
+}
+
+impl RunStats {
+    /// Time since the run started (frozen once the run has ended)
+    pub fn elapsed(&self) -> Duration {
+        match (self.started_at, self.ended_at) {
+            (Some(start), Some(end)) => end.duration_since(start),
+            (Some(start), None) => Instant::now().duration_since(start),
+            _ => Duration::from_secs(0),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.started_at.is_some() && self.ended_at.is_none()
+    }
+
+    /// One-line summary for logs and the agent panel
+    pub fn summary(&self) -> String {
+        format!(
+            "{:.1}s, {} moves, {} tool calls, {} LLM turns",
+            self.elapsed().as_secs_f32(),
+            self.moves,
+            self.tool_calls,
+            self.llm_turns
+        )
+    }
+}
+
+/// A finished run as kept in the run history, so attempts at the same task can be compared
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct RunRecord {
+    pub instruction: String,
+    pub duration_secs: f32,
+    pub moves: usize,
+    pub tool_calls: usize,
+    pub llm_turns: usize,
+    pub errors: usize,
+    /// Positions occupied during the run, from the start position to the final one
+    pub path: Vec<(usize, usize)>,
+    pub final_position: (usize, usize),
+    /// Ended on the goal (or the last move target if no goal is set); without either,
+    /// a run succeeds if it logged no errors
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub optimal_moves: Option<usize>,
+}
+
+/// Full input and output of one LLM turn, kept when decision logging is on
+///
+/// Deeper than the activity log: the exact request, the raw streamed text and tool call,
+/// and what the arguments parsed to, for working out why the model chose what it did.
+#[derive(Clone, Debug, Default, serde::Serialize)]
+pub struct DecisionRecord {
+    pub turn: usize,
+    /// Endpoint, headers and body as sent, with the API key redacted
+    pub request: Value,
+    /// Text content streamed back, verbatim
+    pub raw_output: String,
+    pub tool_name: Option<String>,
+    /// Tool call arguments exactly as streamed, before parsing
+    pub raw_arguments: String,
+    /// Arguments after parsing and agent_id injection; null if they didn't parse
+    pub parsed_arguments: Option<Value>,
+    pub errors: Vec<String>,
+    /// False while the response is still streaming
+    pub finished: bool,
+}
+
+/// Replace the API key wherever it appears in the strings of a logged value
+fn redact_secret(value: &mut Value, secret: &str) {
+    match value {
+        Value::String(text) if !secret.is_empty() && text.contains(secret) => {
+            *text = text.replace(secret, "[redacted]");
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| redact_secret(item, secret)),
+        Value::Object(fields) => fields.values_mut().for_each(|field| redact_secret(field, secret)),
+        _ => {}
+    }
+}
+
+/// Shape of a `get_map_state` result
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum MapStateFormat {
+    /// Every tile in view as rows of names, plus an ASCII picture
+    #[default]
+    Ascii,
+    /// Only the cells in view the agent can't walk on, which is far smaller on open maps
+    Sparse,
+}
+
+impl MapStateFormat {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ascii" => Some(Self::Ascii),
+            "sparse" => Some(Self::Sparse),
+            _ => None,
+        }
+    }
+}
+
+/// What happens to the rest of a movement sequence when one step is blocked
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MovementErrorPolicy {
+    /// Cancel all remaining queued moves
+    #[default]
+    AbortRemaining,
+    /// Drop the blocked step and carry on with the next one
+    SkipAndContinue,
+}
+
+impl MovementErrorPolicy {
+    pub const ALL: [MovementErrorPolicy; 2] = [Self::AbortRemaining, Self::SkipAndContinue];
+
+    pub fn label(&self) -> &'static str {
         match self {
-            Direction::Up => (x, y.saturating_sub(1)),
-            Direction::Down => (x, (y + 1).min(map_height - 1)),
-            Direction::Left => (x.saturating_sub(1), y),
-            Direction::Right => ((x + 1).min(map_width - 1), y),
+            Self::AbortRemaining => "Abort remaining moves",
+            Self::SkipAndContinue => "Skip blocked move and continue",
         }
     }
 }
 
-/// Result of a movement step
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum MovementStatus {
-    Completed,        // All moves done
-    StepSuccess,      // One step succeeded, more to go
-    BlockedByTerrain, // Hit obstacle
-    OutOfBounds,      // Hit map edge
+/// How much detail observation tools like get_bearings put in their results
+///
+/// Terse keeps only the numbers and directions needed to move, saving tokens; Verbose
+/// adds per-cell detail on top of the normal result.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ToolVerbosity {
+    Terse,
+    #[default]
+    Normal,
+    Verbose,
+}
+
+impl ToolVerbosity {
+    pub const ALL: [ToolVerbosity; 3] = [Self::Terse, Self::Normal, Self::Verbose];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Terse => "Terse",
+            Self::Normal => "Normal",
+            Self::Verbose => "Verbose",
+        }
+    }
 }
 
+/// Positions kept in movement history by default; enough for a long run's trail
+pub const DEFAULT_MOVEMENT_HISTORY_LIMIT: usize = 1000;
+
+/// Upper bound for the configurable `get_map_state` visibility
+pub const MAX_VISIBILITY_LIMIT: usize = 30;
+
+/// Energy capacity offered when the budget is first turned on
+pub const DEFAULT_ENERGY_CAPACITY: u32 = 50;
+
+/// Replans allowed per movement sequence before a blocked step falls back to the error policy
+pub const MAX_REPLANS_PER_SEQUENCE: usize = 3;
+
+/// Start of the error for a blocked step the tool execution manager may replan around
+pub const REPLAN_PREFIX: &str = "REPLAN: ";
+
+/// Largest (odd) side length `probe_neighborhood` accepts
+pub const MAX_PROBE_SIZE: usize = 9;
+
+/// Decision records kept before the oldest are dropped; each holds a whole request
+pub const MAX_DECISION_RECORDS: usize = 100;
+
 #[derive(Clone, Debug)]
 pub struct Agent {
     // Identity
     pub id: u32,
     pub name: String,
 
-    // Position
+    // Position (top-left cell of the footprint)
     pub x: usize,
     pub y: usize,
+    size: (usize, usize), // Footprint as (width, height) in cells
+    spawn: (usize, usize), // Where "reset to spawn" returns the agent
+
+    // Appearance
+    pub color: egui::Color32,
+    pub label_background: bool, // Draw the name on a background in the agent color
+    pub sprite: AgentSprite,
+    facing: Direction, // Direction of the last move, shown by directional sprites
 
     // LLM interaction
     chat_history: Vec<Message>,
@@ -106,16 +538,68 @@ pub struct Agent {
     pending_moves: Vec<Direction>,
     movement_active: bool,
     next_step_at: Option<Instant>,
+    clock: SharedClock, // Time source for movement step timing
     current_target: Option<(usize, usize)>, // Optional target for hint generation
-    movement_step_index: usize, // Current step number (0-based) in the movement sequence
-    total_movement_steps: usize, // Total steps in current movement sequence
+    goal: Option<(usize, usize)>, // Navigation goal set by the user
+    replans_this_sequence: usize, // Times the current sequence was replanned around a blocked step
+    last_path_explanation: Option<String>, // Why the last planned route beat equally short ones
 
-    // Movement history - tracks all positions visited by this agent
+    // Movement history - tracks the most recent positions visited by this agent
     movement_history: Vec<(usize, usize)>,
+    movement_history_limit: usize, // Oldest positions are dropped beyond this many
+
+    // Optional trail tiles written into the map, with the terrain they replaced
+    leave_trail_tiles: bool,
+    trail_tiles: HashMap<(usize, usize), TileKind>,
+
+    // Cells the agent has bumped into, remembered so the LLM stops retrying them
+    known_obstacles: HashSet<(usize, usize)>,
+
+    // Cells currently occupied by moving obstacles (patrollers); blocking until they move
+    dynamic_obstacles: HashSet<(usize, usize)>,
+
+    // Whether a blocked step cancels the rest of the sequence
+    movement_error_policy: MovementErrorPolicy,
+
+    // Whether square-grid moves include the four diagonals
+    allow_diagonals: bool,
+
+    // Fog of war: get_map_state hides cells the agent hasn't seen yet
+    fog_of_war: bool,
+    explored: HashSet<(usize, usize)>,
+
+    // Detail level of observation tool results
+    tool_verbosity: ToolVerbosity,
+
+    // Optional energy budget: each move spends the tile's cost, fuel tiles refill it
+    energy_capacity: Option<u32>, // None means unlimited energy
+    energy: u32,
+    out_of_energy: bool, // A move failed for lack of energy since the app last checked
+
+    // Stats for the current (or last) run
+    run_stats: RunStats,
+    run_history: Vec<RunRecord>, // Finished runs, oldest first
+    token_usage: TokenUsage,     // Totals from the logged usage entries
+
+    // Content of the LLM response currently streaming in, shared with the stream task
+    streaming_thinking: Arc<Mutex<String>>,
+    // Abort flag of the current stream task, and when it last received an event
+    stream_abort: Arc<AtomicBool>,
+    last_stream_activity: Arc<Mutex<Instant>>,
+    // Full I/O trace of recent LLM turns, filled in by the stream task while logging is on
+    decision_log: Arc<Mutex<Vec<DecisionRecord>>>,
+    decision_logging: bool,
+    decision_turns: usize, // Turns recorded so far, numbering the records
 
     // Configuration
     max_history_messages: usize, // Maximum number of chat history messages to send to LLM
+    default_visibility: usize, // get_map_state view distance when the model doesn't pass one
+    max_visibility: usize,     // Largest view distance get_map_state accepts
+    temperature: Option<f32>, // Sampling temperature (None = provider default)
     enabled_tools: HashSet<String>, // Set of enabled tool names
+    placeable_tiles: HashSet<TileKind>, // Tiles the agent may place via set_tile
+    traversability: TraversabilityRules, // Which tile kinds the agent can walk on
+    tile_costs: TileCosts,               // Movement cost per tile kind
 
     // Tools
     tool_registry: Vec<Tool>,
@@ -128,17 +612,55 @@ impl Agent {
             name: name.into(),
             x,
             y,
+            size: (1, 1),
+            spawn: (x, y),
+            color: egui::Color32::from_rgb(230, 70, 50),
+            label_background: false,
+            sprite: AgentSprite::default(),
+            facing: Direction::Down,
             chat_history: Vec::new(),
             logs: Vec::new(),
             pending_moves: Vec::new(),
             movement_active: false,
             next_step_at: None,
+            clock: RealClock::shared(),
             current_target: None,
-            movement_step_index: 0,
-            total_movement_steps: 0,
+            goal: None,
+            replans_this_sequence: 0,
+            last_path_explanation: None,
             movement_history: Vec::new(),
+            movement_history_limit: DEFAULT_MOVEMENT_HISTORY_LIMIT,
+            leave_trail_tiles: false,
+            trail_tiles: HashMap::new(),
+            known_obstacles: HashSet::new(),
+            dynamic_obstacles: HashSet::new(),
+            movement_error_policy: MovementErrorPolicy::default(),
+            allow_diagonals: false,
+            fog_of_war: false,
+            explored: HashSet::new(),
+            tool_verbosity: ToolVerbosity::default(),
+            energy_capacity: None,
+            energy: 0,
+            out_of_energy: false,
+            run_stats: RunStats::default(),
+            run_history: Vec::new(),
+            token_usage: TokenUsage::default(),
+            streaming_thinking: Arc::new(Mutex::new(String::new())),
+            stream_abort: Arc::new(AtomicBool::new(false)),
+            last_stream_activity: Arc::new(Mutex::new(Instant::now())),
+            decision_log: Arc::new(Mutex::new(Vec::new())),
+            decision_logging: false,
+            decision_turns: 0,
             max_history_messages: 50, // Default to last 50 messages
+            default_visibility: 5,
+            max_visibility: 10,
+            temperature: None,
             enabled_tools: HashSet::new(),
+            placeable_tiles: [TileKind::Grass, TileKind::Sand, TileKind::Wall]
+                .into_iter()
+                .collect(),
+            traversability: TraversabilityRules::default(),
+            tile_costs: TileCosts::default(),
             tool_registry: Vec::new(),
         };
 
@@ -160,22 +682,310 @@ impl Agent {
         self.y = y;
     }
 
+    /// Recorded spawn position
+    pub fn spawn(&self) -> (usize, usize) {
+        self.spawn
+    }
+
+    pub fn set_spawn(&mut self, x: usize, y: usize) {
+        self.spawn = (x, y);
+    }
+
+    /// Start on a freshly loaded map: at its designated spawn if it has one the agent fits
+    /// on, else the current position (moved to an open cell if blocked). The chosen cell
+    /// becomes the agent's spawn.
+    pub fn place_at_map_spawn(&mut self, map: &GridMap) {
+        if let Some((x, y)) = map.spawn_point()
+            && self.can_enter(map, x, y)
+        {
+            self.set_pos(x, y);
+        } else {
+            self.relocate_if_blocked(map);
+        }
+        self.spawn = self.pos();
+    }
+
+    /// Return to the spawn point with a clean trail so the same scenario can be re-run
+    pub fn reset_to_spawn(&mut self, map: &mut GridMap) {
+        let restored = self.restore_trail_tiles(map);
+        self.pending_moves.clear();
+        self.movement_active = false;
+        self.next_step_at = None;
+        // Keep the spawn on the map if it has been resized since
+        self.spawn = (
+            self.spawn.0.min(map.width().saturating_sub(1)),
+            self.spawn.1.min(map.height().saturating_sub(1)),
+        );
+        self.set_pos(self.spawn.0, self.spawn.1);
+        self.clear_movement_history();
+        self.clear_explored();
+
+        let mut message = format!("Agent reset to spawn ({}, {})", self.spawn.0, self.spawn.1);
+        if restored > 0 {
+            message.push_str(&format!(", restored {} trail tiles", restored));
+        }
+        self.log_info(message);
+    }
+
+    /// Use `clock` for movement timing (share the event queue's clock)
+    pub fn set_clock(&mut self, clock: SharedClock) {
+        self.clock = clock;
+    }
+
+    /// Navigation goal set by the user, if any
+    pub fn goal(&self) -> Option<(usize, usize)> {
+        self.goal
+    }
+
+    pub fn set_goal(&mut self, goal: Option<(usize, usize)>) {
+        self.goal = goal;
+    }
+
+    /// Active traversability rules
+    pub fn traversability(&self) -> &TraversabilityRules {
+        &self.traversability
+    }
+
+    pub fn traversability_mut(&mut self) -> &mut TraversabilityRules {
+        &mut self.traversability
+    }
+
+    pub fn tile_costs(&self) -> &TileCosts {
+        &self.tile_costs
+    }
+
+    pub fn tile_costs_mut(&mut self) -> &mut TileCosts {
+        &mut self.tile_costs
+    }
+
+    /// Cost of moving the footprint onto (x, y): the most expensive cell it covers
+    pub fn movement_cost(&self, map: &GridMap, x: usize, y: usize) -> u32 {
+        let (w, h) = self.size;
+        (y..y + h)
+            .flat_map(|cy| (x..x + w).map(move |cx| (cx, cy)))
+            .map(|(cx, cy)| self.tile_costs.cell_cost(map, cx, cy))
+            .max()
+            .unwrap_or(TileCosts::DEFAULT_COST)
+    }
+
+    /// Cost of a single move from `from` onto `to`: the footprint's tile cost plus the
+    /// height climbed, as the weighted pathfinders price it
+    pub fn step_cost(&self, map: &GridMap, from: (usize, usize), to: (usize, usize)) -> u32 {
+        self.movement_cost(map, to.0, to.1) + map.climb_cost(from, to)
+    }
+
+    /// "Traversable tiles: ...\nBlocking tiles: ..." for movement error details
+    fn traversability_details(&self) -> String {
+        let mut passable = self.traversability.tile_names(true);
+        passable.push("trail");
+        format!(
+            "Traversable tiles: {}\nBlocking tiles: {}",
+            passable.join(", "),
+            self.traversability.tile_names(false).join(", ")
+        )
+    }
+
+    /// Footprint as (width, height) in cells
+    pub fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    /// Set the footprint size (each side is at least 1 cell)
+    pub fn set_size(&mut self, width: usize, height: usize) {
+        self.size = (width.max(1), height.max(1));
+    }
+
+    /// Whether the agent has seen every cell of the footprint anchored at (x, y)
+    ///
+    /// Always true without fog of war.
+    fn footprint_explored(&self, x: usize, y: usize) -> bool {
+        let (w, h) = self.size;
+        !self.fog_of_war
+            || (y..y + h).all(|cy| (x..x + w).all(|cx| self.explored.contains(&(cx, cy))))
+    }
+
+    /// Check whether the agent's current footprint covers (x, y)
+    pub fn covers(&self, x: usize, y: usize) -> bool {
+        let (w, h) = self.size;
+        (self.x..self.x + w).contains(&x) && (self.y..self.y + h).contains(&y)
+    }
+
+    /// Find the first cell of the footprint anchored at (x, y) that is off the map or blocked
+    fn blocked_footprint_cell(&self, map: &GridMap, x: usize, y: usize) -> Option<(usize, usize)> {
+        let (w, h) = self.size;
+        (y..y + h)
+            .flat_map(|cy| (x..x + w).map(move |cx| (cx, cy)))
+            .find(|&(cx, cy)| {
+                !self.traversability.is_cell_passable(map, cx, cy)
+                    || self.dynamic_obstacles.contains(&(cx, cy))
+            })
+    }
+
+    /// `blocked_footprint_cell` for a step in `direction` from `from` to (x, y)
+    ///
+    /// A square-grid diagonal is also blocked when the cell on either side of it is, so
+    /// the agent never cuts a corner.
+    fn blocked_step_cell(
+        &self,
+        map: &GridMap,
+        from: (usize, usize),
+        direction: &Direction,
+        x: usize,
+        y: usize,
+    ) -> Option<(usize, usize)> {
+        self.blocked_footprint_cell(map, x, y).or_else(|| {
+            direction.corner_steps(map.topology())?.iter().find_map(|side| {
+                let (sx, sy) = side.apply(map.topology(), from.0 as i32, from.1 as i32);
+                if sx < 0 || sy < 0 {
+                    return None;
+                }
+                self.blocked_footprint_cell(map, sx as usize, sy as usize)
+            })
+        })
+    }
+
+    /// Moves the agent may take on `map` (see `Direction::available`)
+    pub fn directions(&self, map: &GridMap) -> &'static [Direction] {
+        Direction::available(map.topology(), self.allow_diagonals)
+    }
+
+    /// Whether the agent may also move diagonally on square maps
+    pub fn allow_diagonals(&self) -> bool {
+        self.allow_diagonals
+    }
+
+    pub fn set_allow_diagonals(&mut self, allow: bool) {
+        self.allow_diagonals = allow;
+    }
+
+    /// Direction of the agent's last move
+    pub fn facing(&self) -> &Direction {
+        &self.facing
+    }
+
+    /// What blocks (x, y): a patroller standing there, or else the tile's name
+    fn blocker_name(&self, map: &GridMap, x: usize, y: usize) -> &'static str {
+        if self.dynamic_obstacles.contains(&(x, y)) {
+            return "patroller";
+        }
+        self.terrain_at(map, x, y).map_or("unknown", |t| t.name())
+    }
+
+    /// Cells currently occupied by moving obstacles
+    pub fn dynamic_obstacles(&self) -> &HashSet<(usize, usize)> {
+        &self.dynamic_obstacles
+    }
+
+    /// Replace the cells occupied by moving obstacles (called whenever they move)
+    pub fn set_dynamic_obstacles(&mut self, cells: impl IntoIterator<Item = (usize, usize)>) {
+        self.dynamic_obstacles = cells.into_iter().collect();
+    }
+
+    /// Check whether the whole footprint fits on traversable tiles when anchored at (x, y)
+    pub fn can_enter(&self, map: &GridMap, x: usize, y: usize) -> bool {
+        self.blocked_footprint_cell(map, x, y).is_none()
+    }
+
+    /// Move the agent to the map's spawn cell, or else the nearest cell it fits on, if its
+    /// current footprint is blocked
+    ///
+    /// Returns true if the agent was relocated. Leaves it in place (and logs an error)
+    /// when there's nowhere to go.
+    pub fn relocate_if_blocked(&mut self, map: &GridMap) -> bool {
+        if self.can_enter(map, self.x, self.y) {
+            return false;
+        }
+        let from = self.pos();
+        // Prefer the map's designated spawn cell when it has one the agent fits on
+        if let Some((x, y)) = map.spawn_point()
+            && self.can_enter(map, x, y)
+        {
+            self.set_pos(x, y);
+            self.log_info(format!(
+                "Agent was on a blocked cell at ({}, {}); moved to the map's spawn ({}, {})",
+                from.0, from.1, x, y
+            ));
+            return true;
+        }
+        match map.nearest_cell_where(from, |x, y| self.can_enter(map, x, y)) {
+            Some((x, y)) => {
+                self.set_pos(x, y);
+                self.log_info(format!(
+                    "Agent was on a blocked cell at ({}, {}); moved to nearest open cell ({}, {})",
+                    from.0, from.1, x, y
+                ));
+                true
+            }
+            None => {
+                self.log_error(ErrorKind::Validation, format!(
+                    "Agent at ({}, {}) is blocked and no open cell fits it",
+                    from.0, from.1
+                ));
+                false
+            }
+        }
+    }
+
     /// Get agent logs
     pub fn get_logs(&self) -> &[LogEntry] {
         &self.logs
     }
 
+    /// Remove every log entry
+    pub fn clear_logs(&mut self) {
+        self.logs.clear();
+    }
+
     /// Add a log entry
     pub fn log(&mut self, entry: LogEntry) {
+        // The console only exists in the browser; host builds (tests) just keep the entry
+        #[cfg(target_arch = "wasm32")]
         web_sys::console::log_1(&format!("{:?}", entry).into());
+        // The finished thinking entry replaces the live preview
+        if matches!(entry, LogEntry::AgentThinking(_)) {
+            self.streaming_thinking.lock().unwrap().clear();
+        }
+        if matches!(entry, LogEntry::Error(..)) && self.run_stats.is_active() {
+            self.run_stats.errors += 1;
+        }
+        if let LogEntry::Usage { prompt_tokens, completion_tokens, cost } = entry {
+            self.token_usage.turns += 1;
+            self.token_usage.prompt_tokens += prompt_tokens;
+            self.token_usage.completion_tokens += completion_tokens;
+            self.token_usage.cost += cost.unwrap_or(0.0);
+        }
         self.logs.push(entry);
     }
 
+    /// Agent thinking received so far from the response that is still streaming
+    pub fn streaming_thinking(&self) -> String {
+        self.streaming_thinking.lock().unwrap().clone()
+    }
+
+    /// Time since the current LLM stream started or last received an event
+    pub fn stream_idle_for(&self) -> Duration {
+        let last = *self.last_stream_activity.lock().unwrap();
+        self.clock.now().saturating_duration_since(last)
+    }
+
+    /// Tell the current stream task to stop; anything it receives afterwards is dropped
+    ///
+    /// A hung connection never wakes the task, so the caller also clears the LLM status.
+    pub fn abort_stream(&mut self) {
+        self.stream_abort.store(true, Ordering::Relaxed);
+        self.streaming_thinking.lock().unwrap().clear();
+    }
+
     /// Add a simple info log
     pub fn log_info(&mut self, message: impl Into<String>) {
         self.log(LogEntry::Info(message.into()));
     }
 
+    /// Add an error log of the given kind
+    pub fn log_error(&mut self, kind: ErrorKind, message: impl Into<String>) {
+        self.log(LogEntry::Error(kind, message.into()));
+    }
+
     /// Get chat history
     pub fn get_chat_history(&self) -> &[Message] {
         &self.chat_history
@@ -229,19 +1039,28 @@ impl Agent {
             Decide on ONE tool call to best accomplish the user's instruction. \
             Return only a tool call with complete JSON arguments.\n\n\
             Current position: ({}, {})\n\
+            {}\
+            {}\
+            {}\
             Map dimensions: {}x{} (width x height)\n\
             Coordinate system: (0,0) is top-left corner\n\
+            {}\
             \n\
             TILE TRAVERSABILITY:\n\
-            - TRAVERSABLE (you can move through): empty, grass, sand\n\
-            - BLOCKING (you cannot move through): wall, water, tree\n\
+            - TRAVERSABLE (you can move through): {}, portal\n\
+            - BLOCKING (you cannot move through): {}\n\
+            {}\
+            - PORTAL: stepping onto a portal instantly moves you to the other portal with the \
+            same id (see 'portals' in get_map_state); unpaired portals do nothing\n\
             \n\
-            Movement will fail if you try to move onto a blocking tile or outside the map boundaries.\n\n\
+            Movement will fail if you try to move onto a blocking tile or outside the map boundaries.\n\
+            {}\n\
             IMPORTANT: Use 'get_map_state' tool to see the current map before planning movement.\n\
             For efficient path finding, use the 'area' parameter to focus on specific regions:\n\
             - Use 'area': {{\"x\": X, \"y\": Y}} to view a 7x7 area around coordinate (X,Y)\n\
-            - Use 'visibility': N (1-10) to limit view distance from your position\n\
+            - Use 'visibility': N (1-{}) to limit view distance from your position\n\
             - Smaller visibility values reduce complexity and speed up planning\n\
+            - Use 'format': \"sparse\" to get only the cells you can't walk on (much shorter on open maps)\n\
             - Focus on areas near your target or along your planned route\n\
             \n\
             Use 'think' tool to plan your path, especially when:\n\
@@ -251,25 +1070,157 @@ impl Agent {
             The think tool helps you reason through complex navigation problems.\n\n\
             Use 'get_bearings' tool when you're stuck or need navigation help:\n\
             - Get information about obstacles and open directions\n\
-            - Set a target coordinate for better navigation hints\n\
-            - If you're stuck, try picking a different target coordinate!",
+            - Set a target coordinate for better navigation hints and a recommended next move\n\
+            - If you're stuck, try picking a different target coordinate!\n\
+            \n\
+            Use 'probe_neighborhood' for a small grid of the tiles right around you before deciding your next few moves.\n\
+            \n\
+            Use 'explain_route' with a target to get the best (cheapest) route as turn-by-turn directions plus the moves to pass to move_agent.\n\
+            \n\
+            Use 'measure' to compare the distances between any two points, e.g. candidate targets, before committing to one.\n\
+            \n\
+            Use 'get_current_plan' while moves are still queued to see the remaining steps and where they end, so you can decide whether to keep going or change course.",
             self.name,
             self.id,
             self.x,
             self.y,
+            self.size_prompt_line(),
+            self.goal_prompt_line(),
+            self.energy_prompt_line(),
             map.width(),
-            map.height()
+            map.height(),
+            self.topology_prompt_line(map),
+            self.traversability.tile_names(true).join(", "),
+            self.traversability.tile_names(false).join(", "),
+            self.tile_costs_prompt_line(),
+            self.known_obstacles_prompt_line() + self.fog_of_war_prompt_line(),
+            self.max_visibility
+        )
+    }
+
+    /// System prompt line explaining hex or diagonal movement; plain square maps need no
+    /// explanation
+    fn topology_prompt_line(&self, map: &GridMap) -> &'static str {
+        match map.topology() {
+            Topology::Square if self.allow_diagonals => {
+                "DIAGONAL MOVES: besides up, down, left and right you may move up_left (x-1, \
+                y-1), up_right (x+1, y-1), down_left (x-1, y+1) and down_right (x+1, y+1). A \
+                diagonal is blocked if either cell beside it is blocked (no cutting corners).\n"
+            }
+            Topology::Square => "",
+            Topology::Hex => {
+                "HEX GRID: odd rows are shifted half a cell right, so each cell has six \
+                neighbors. Move with left, right, up_left, up_right, down_left and \
+                down_right; up and down are not moves here. From an even row, up_left goes \
+                to (x-1, y-1) and up_right to (x, y-1); from an odd row, up_left goes to \
+                (x, y-1) and up_right to (x+1, y-1) (down_* likewise with y+1).\n"
+            }
+        }
+    }
+
+    /// System prompt line warning that unseen cells are hidden
+    fn fog_of_war_prompt_line(&self) -> &'static str {
+        if !self.fog_of_war {
+            return "";
+        }
+        "FOG OF WAR: you only know tiles you have seen. get_map_state reveals everything \
+        within your visibility of your position; cells beyond it that you haven't seen yet \
+        show as \"unknown\", so explore to find your way. explain_route only plans through \
+        tiles you have seen.\n"
+    }
+
+    /// System prompt line listing the tiles that are slower than a plain step
+    fn tile_costs_prompt_line(&self) -> String {
+        let slow: Vec<String> = self
+            .tile_costs
+            .non_default()
+            .filter(|(tile, _)| self.traversability.is_passable(tile))
+            .map(|(tile, cost)| format!("{} {}", tile.name(), cost))
+            .collect();
+        if slow.is_empty() {
+            return String::new();
+        }
+        format!(
+            "- MOVEMENT COST (time per step, and energy when budgeted; other tiles cost {}): \
+            {}. The cheapest route may take more steps than the shortest; explain_route \
+            follows the cheapest.\n",
+            TileCosts::DEFAULT_COST,
+            slow.join(", ")
+        )
+    }
+
+    /// System prompt line listing cells already found to be blocked
+    fn known_obstacles_prompt_line(&self) -> String {
+        const MAX_LISTED: usize = 40;
+
+        if self.known_obstacles.is_empty() {
+            return String::new();
+        }
+        let mut known: Vec<_> = self.known_obstacles.iter().copied().collect();
+        known.sort_unstable_by_key(|&(x, y)| (y, x));
+        let listed: Vec<String> = known
+            .iter()
+            .take(MAX_LISTED)
+            .map(|(x, y)| format!("({},{})", x, y))
+            .collect();
+        let more = known.len().saturating_sub(MAX_LISTED);
+        format!(
+            "KNOWN OBSTACLES (moves here already failed, don't retry): {}{}\n",
+            listed.join(" "),
+            if more > 0 { format!(" and {} more", more) } else { String::new() }
+        )
+    }
+
+    /// System prompt line naming the goal cell, if one is set
+    fn goal_prompt_line(&self) -> String {
+        match self.goal {
+            Some((x, y)) => format!(
+                "Goal: reach ({}, {}). get_position reports on_goal once you're there.\n",
+                x, y
+            ),
+            None => String::new(),
+        }
+    }
+
+    fn energy_prompt_line(&self) -> String {
+        match self.energy() {
+            Some((energy, capacity)) => format!(
+                "Energy: {} of {}. Each move spends the cost of the tile you enter; at 0 you \
+                can't move. Stepping on a fuel tile refills it. get_position reports energy.\n",
+                energy, capacity
+            ),
+            None => String::new(),
+        }
+    }
+
+    /// Extra system prompt line describing a footprint larger than one cell
+    fn size_prompt_line(&self) -> String {
+        let (w, h) = self.size;
+        if (w, h) == (1, 1) {
+            return String::new();
+        }
+        format!(
+            "Agent size: {}x{} cells. Your position is your top-left cell, and every cell \
+            you cover must be traversable, so you need a {}-wide, {}-tall gap to pass.\n",
+            w, h, w, h
         )
     }
 
     /// Generate JSON representation of map state with agent position marked
     fn map_state_json(&self, map: &GridMap) -> String {
-        self.map_state_json_with_params(map, None, None)
+        self.map_state_json_with_params(map, None, None, MapStateFormat::Ascii)
     }
 
     /// Generate JSON representation of map state with optional area and visibility parameters
-    fn map_state_json_with_params(&self, map: &GridMap, area: Option<(usize, usize)>, visibility: Option<usize>) -> String {
-        let visibility = visibility.unwrap_or(5).clamp(1, 10);
+    fn map_state_json_with_params(
+        &self,
+        map: &GridMap,
+        area: Option<(usize, usize)>,
+        visibility: Option<usize>,
+        format: MapStateFormat,
+    ) -> String {
+        let visibility = self.clamped_visibility(visibility);
+        let known = |x: usize, y: usize| !self.fog_of_war || self.explored.contains(&(x, y));
 
         // Determine the view bounds
         let (view_x, view_y, view_width, view_height) = if let Some((center_x, center_y)) = area {
@@ -289,31 +1240,104 @@ impl Agent {
             (start_x, start_y, end_x - start_x, end_y - start_y)
         };
 
-        let mut rows: Vec<Vec<String>> = Vec::with_capacity(view_height);
-        for y in view_y..(view_y + view_height) {
-            let mut row: Vec<String> = Vec::with_capacity(view_width);
-            for x in view_x..(view_x + view_width) {
-                // Mark agent's position with "@" prefix
-                if x == self.x && y == self.y {
-                    let tile_name = map.get(x, y).map(|tile| tile.name()).unwrap_or("empty");
-                    row.push(format!("@{}", tile_name));
-                } else {
-                    let tile_name = map.get(x, y).map(|tile| tile.name()).unwrap_or("empty");
-                    row.push(tile_name.to_string());
-                }
-            }
-            rows.push(row);
-        }
-
-        // Create ASCII minimap for better readability
-        let mut minimap = String::new();
-        minimap.push_str(&format!("Map View ({}x{} area at ({}, {}))\n",
-            view_width, view_height, view_x, view_y));
-        minimap.push_str(&format!("Agent at ({}, {}) in full map ({}x{})\n",
-            self.x, self.y, map.width(), map.height()));
-
-        if let Some((area_x, area_y)) = area {
-            minimap.push_str(&format!("Area center: ({}, {})\n", area_x, area_y));
+        let mut result = json!({
+            "view_bounds": {
+                "x": view_x,
+                "y": view_y,
+                "width": view_width,
+                "height": view_height
+            },
+            "full_map_size": {
+                "width": map.width(),
+                "height": map.height()
+            },
+            "agent_position": {"x": self.x, "y": self.y}
+        });
+
+        // List visible portals with their exits so routes through them can be planned
+        let in_view = |x: usize, y: usize| {
+            (view_x..view_x + view_width).contains(&x) && (view_y..view_y + view_height).contains(&y)
+        };
+        let portals: Vec<Value> = map
+            .portals()
+            .filter(|&(_, x, y)| in_view(x, y) && known(x, y))
+            .map(|(id, x, y)| {
+                let exit = map.portal_exit(x, y).map(|(ex, ey)| json!({"x": ex, "y": ey}));
+                json!({"id": id, "x": x, "y": y, "exit": exit})
+            })
+            .collect();
+        if !portals.is_empty() {
+            result["portals"] = json!(portals);
+        }
+
+        // Add area info if specified
+        if let Some((area_x, area_y)) = area {
+            result["area_center"] = json!({"x": area_x, "y": area_y});
+        }
+
+        // Add visibility info
+        result["visibility_used"] = json!(visibility);
+        if map.topology() == Topology::Hex {
+            result["topology"] = json!("hex (odd rows shifted half a cell right)");
+        }
+
+        if format == MapStateFormat::Sparse {
+            // Checked per cell like the ASCII view; a larger footprint still needs a gap
+            // wide enough for all of it
+            let blocked: Vec<Value> = (view_y..view_y + view_height)
+                .flat_map(|y| (view_x..view_x + view_width).map(move |x| (x, y)))
+                .filter(|&(x, y)| known(x, y) && !self.traversability.is_cell_passable(map, x, y))
+                .map(|(x, y)| {
+                    let tile = self.terrain_at(map, x, y).map_or("empty", |tile| tile.name());
+                    json!({"x": x, "y": y, "tile": tile})
+                })
+                .collect();
+            let unknown: Vec<Value> = (view_y..view_y + view_height)
+                .flat_map(|y| (view_x..view_x + view_width).map(move |x| (x, y)))
+                .filter(|&(x, y)| !known(x, y))
+                .map(|(x, y)| json!({"x": x, "y": y}))
+                .collect();
+            result["format"] = json!("sparse");
+            result["blocked"] = json!(blocked);
+            if unknown.is_empty() {
+                result["note"] =
+                    json!("Every cell in view_bounds not listed in 'blocked' is walkable");
+            } else {
+                result["unknown"] = json!(unknown);
+                result["note"] = json!(
+                    "Every cell in view_bounds not listed in 'blocked' or 'unknown' is walkable"
+                );
+            }
+            return serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string());
+        }
+
+        let mut rows: Vec<Vec<String>> = Vec::with_capacity(view_height);
+        for y in view_y..(view_y + view_height) {
+            let mut row: Vec<String> = Vec::with_capacity(view_width);
+            for x in view_x..(view_x + view_width) {
+                // Mark agent's position with "@" prefix
+                if !known(x, y) {
+                    row.push("unknown".to_string());
+                } else if x == self.x && y == self.y {
+                    let tile_name = self.terrain_at(map, x, y).map_or("empty", |tile| tile.name());
+                    row.push(format!("@{}", tile_name));
+                } else {
+                    let tile_name = self.terrain_at(map, x, y).map_or("empty", |tile| tile.name());
+                    row.push(tile_name.to_string());
+                }
+            }
+            rows.push(row);
+        }
+
+        // Create ASCII minimap for better readability
+        let mut minimap = String::new();
+        minimap.push_str(&format!("Map View ({}x{} area at ({}, {}))\n",
+            view_width, view_height, view_x, view_y));
+        minimap.push_str(&format!("Agent at ({}, {}) in full map ({}x{})\n",
+            self.x, self.y, map.width(), map.height()));
+
+        if let Some((area_x, area_y)) = area {
+            minimap.push_str(&format!("Area center: ({}, {})\n", area_x, area_y));
         }
         minimap.push_str(&format!("Visibility used: {}\n\n", visibility));
 
@@ -322,6 +1346,8 @@ impl Agent {
             for x in view_x..(view_x + view_width) {
                 if x == self.x && y == self.y {
                     minimap.push('@');
+                } else if !known(x, y) {
+                    minimap.push('-');
                 } else {
                     let tile_char = match map.get(x, y) {
                         Some(TileKind::Empty) => '.',
@@ -331,6 +1357,8 @@ impl Agent {
                         Some(TileKind::Wall) => '#',
                         Some(TileKind::Trail) => '*',
                         Some(TileKind::Tree) => 'T',
+                        Some(TileKind::Portal(_)) => 'O',
+                        Some(TileKind::Fuel) => 'F',
                         Some(TileKind::Custom(_)) => '?',
                         None => ' ',
                     };
@@ -341,30 +1369,12 @@ impl Agent {
         }
 
         // Add legend
-        minimap.push_str("\nLegend: @=agent, .=empty, ,=grass, ~=water, #=wall, T=tree, s=sand, *=trail\n");
-
-        let mut result = json!({
-            "view_bounds": {
-                "x": view_x,
-                "y": view_y,
-                "width": view_width,
-                "height": view_height
-            },
-            "full_map_size": {
-                "width": map.width(),
-                "height": map.height()
-            },
-            "tiles": rows,
-            "agent_position": {"x": self.x, "y": self.y}
-        });
-
-        // Add area info if specified
-        if let Some((area_x, area_y)) = area {
-            result["area_center"] = json!({"x": area_x, "y": area_y});
+        minimap.push_str("\nLegend: @=agent, .=empty, ,=grass, ~=water, #=wall, T=tree, s=sand, *=trail, O=portal, F=fuel\n");
+        if self.fog_of_war {
+            minimap.push_str("-=unknown (not explored yet)\n");
         }
 
-        // Add visibility info
-        result["visibility_used"] = json!(visibility);
+        result["tiles"] = json!(rows);
 
         // Store minimap separately for UI display (not sent to LLM)
         // The UI will reconstruct this from the JSON data if needed
@@ -373,10 +1383,9 @@ impl Agent {
         serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
     }
 
-    /// Register default tools for this agent
-    fn register_default_tools(&mut self) {
-        // Get map state tool
-        self.tool_registry.push(Tool {
+    /// `get_map_state` tool definition; its visibility bounds follow the agent's settings
+    fn map_state_tool(&self) -> Tool {
+        Tool {
             type_: "function".into(),
             function: Function {
                 name: "get_map_state".into(),
@@ -394,16 +1403,28 @@ impl Agent {
                         },
                         "visibility": {
                             "type": "integer",
-                            "description": "How far you can see from your current position (1-10). Smaller values show less area but are faster for planning.",
+                            "description": format!("How far you can see from your current position (1-{}). Smaller values show less area but are faster for planning.", self.max_visibility),
                             "minimum": 1,
-                            "maximum": 10,
-                            "default": 5
+                            "maximum": self.max_visibility,
+                            "default": self.default_visibility
+                        },
+                        "format": {
+                            "type": "string",
+                            "enum": ["ascii", "sparse"],
+                            "description": "'ascii' (default) lists every tile in view; 'sparse' lists only the cells you can't walk on, which is much shorter on open maps",
+                            "default": "ascii"
                         }
                     },
                     "required": []
                 }),
             }
-        });
+        }
+    }
+
+    /// Register default tools for this agent
+    fn register_default_tools(&mut self) {
+        // Get map state tool
+        self.tool_registry.push(self.map_state_tool());
         self.enabled_tools.insert("get_map_state".to_string());
 
         // Thinking tool - no-op, used for planning
@@ -431,14 +1452,17 @@ impl Agent {
             type_: "function".into(),
             function: Function {
                 name: "move_agent".into(),
-                description: "Move the agent by executing up to 5 steps from ['up','down','left','right']. Optionally specify a target coordinate to receive helpful hints if blocked.".to_string(),
+                description: "Move the agent by executing up to 5 steps from ['up','down','left','right'] (on hex grids: ['up_left','up_right','down_left','down_right','left','right']; with diagonal movement on, square grids also allow the four diagonals, which can't cut past a blocked corner). Optionally specify a target coordinate to receive helpful hints if blocked.".to_string(),
                 parameters: json!({
                     "type": "object",
                     "properties": {
                         "agent_id": {"type":"integer"},
                         "steps": {
                             "type": "array",
-                            "items": {"type": "string", "enum": ["up","down","left","right"]},
+                            "items": {
+                                "type": "string",
+                                "enum": ["up","down","left","right","up_left","up_right","down_left","down_right"]
+                            },
                             "minItems": 1,
                             "maxItems": 5
                         },
@@ -462,7 +1486,7 @@ impl Agent {
             type_: "function".into(),
             function: Function {
                 name: "get_position".into(),
-                description: "Get the agent's current position coordinates (x, y).".to_string(),
+                description: "Get the agent's current position coordinates (x, y), the tile you're standing on, and whether you're on the goal (on_goal) or your move target (on_target) when one is set.".to_string(),
                 parameters: json!({
                     "type": "object",
                     "properties": {},
@@ -488,12 +1512,32 @@ impl Agent {
         self.enabled_tools
             .insert("get_available_directions".to_string());
 
+        // Probe neighborhood tool - the tiles immediately around the agent as a matrix
+        self.tool_registry.push(Tool {
+            type_: "function".into(),
+            function: Function {
+                name: "probe_neighborhood".into(),
+                description: format!("Get the tiles immediately around you as a small grid of tile names, rows from north to south and columns from west to east. Cells you occupy are \"agent\" and cells past the map edge are \"edge\". Cheaper and more reliable than get_map_state for deciding the next few moves. Size is an odd number from 3 to {} (default 3).", MAX_PROBE_SIZE),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "size": {
+                            "type": "integer",
+                            "description": format!("Side length of the grid, odd, 3-{} (default 3)", MAX_PROBE_SIZE)
+                        }
+                    },
+                    "required": []
+                }),
+            }
+        });
+        self.enabled_tools.insert("probe_neighborhood".to_string());
+
         // Get bearings tool - provides navigation hints when blocked
         self.tool_registry.push(Tool {
             type_: "function".into(),
             function: Function {
                 name: "get_bearings".into(),
-                description: "Get navigation bearings and hints when you're blocked or need to navigate to a target. Provides information about obstacles, open directions, and distance to target.".to_string(),
+                description: "Get navigation bearings and hints when you're blocked or need to navigate to a target. Provides information about obstacles, open directions, and distance to target. With a target, also recommends the open direction with the lowest estimated cost to reach it.".to_string(),
                 parameters: json!({
                     "type": "object",
                     "properties": {
@@ -511,6 +1555,118 @@ impl Agent {
             }
         });
         self.enabled_tools.insert("get_bearings".to_string());
+
+        // Explain route tool - turn-by-turn directions to a coordinate
+        self.tool_registry.push(Tool {
+            type_: "function".into(),
+            function: Function {
+                name: "explain_route".into(),
+                description: "Plan the best route from your position to a target (the cheapest one when tiles have different movement costs, else the shortest) and get it as turn-by-turn directions, e.g. \"go east 4 until the wall at (7, 2), then north 3\". Also returns the individual moves, ready for move_agent.".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "target": {
+                            "type": "object",
+                            "description": "Destination coordinate (x, y)",
+                            "properties": {
+                                "x": {"type": "integer"},
+                                "y": {"type": "integer"}
+                            },
+                            "required": ["x", "y"]
+                        }
+                    },
+                    "required": ["target"]
+                }),
+            }
+        });
+        self.enabled_tools.insert("explain_route".to_string());
+
+        // Measure tool - distances between any two cells, independent of the agent
+        self.tool_registry.push(Tool {
+            type_: "function".into(),
+            function: Function {
+                name: "measure".into(),
+                description: "Measure the distance between two map coordinates: Manhattan and Chebyshev (on hex maps, the hex grid distance instead) and Euclidean distances, plus the number of moves on the shortest walkable route (null with a reason if there is none). Useful for comparing candidate targets; doesn't depend on your position.".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "from": {
+                            "type": "object",
+                            "description": "First coordinate (x, y)",
+                            "properties": {
+                                "x": {"type": "integer"},
+                                "y": {"type": "integer"}
+                            },
+                            "required": ["x", "y"]
+                        },
+                        "to": {
+                            "type": "object",
+                            "description": "Second coordinate (x, y)",
+                            "properties": {
+                                "x": {"type": "integer"},
+                                "y": {"type": "integer"}
+                            },
+                            "required": ["x", "y"]
+                        }
+                    },
+                    "required": ["from", "to"]
+                }),
+            }
+        });
+        self.enabled_tools.insert("measure".to_string());
+
+        // Explain last path tool - how pathfinding ties were broken for the last route
+        self.tool_registry.push(Tool {
+            type_: "function".into(),
+            function: Function {
+                name: "explain_last_path".into(),
+                description: "Explain why the last route planned by explain_route (or by replanning after a blocked move) was chosen over other equally short routes, e.g. \"preferred heading east over heading north\" because of the search's fixed neighbor order.".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {},
+                    "required": []
+                }),
+            }
+        });
+        self.enabled_tools.insert("explain_last_path".to_string());
+
+        // Current plan tool - the queued moves that haven't been taken yet
+        self.tool_registry.push(Tool {
+            type_: "function".into(),
+            function: Function {
+                name: "get_current_plan".into(),
+                description: "Get the moves still queued from your last move_agent call, in order, with the position they will end at (projected, ignoring obstacles) and the first step that would currently be blocked. Reports an empty plan when nothing is queued.".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {},
+                    "required": []
+                }),
+            }
+        });
+        self.enabled_tools.insert("get_current_plan".to_string());
+
+        // Set tile tool - lets the agent modify the map (disabled by default)
+        self.tool_registry.push(Tool {
+            type_: "function".into(),
+            function: Function {
+                name: "set_tile".into(),
+                description: "Change the tile at a map coordinate, e.g. to build a bridge over water or place a wall. Only some tile types may be placed.".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "agent_id": {"type": "integer"},
+                        "x": {"type": "integer", "description": "X coordinate of the tile to change"},
+                        "y": {"type": "integer", "description": "Y coordinate of the tile to change"},
+                        "tile": {
+                            "type": "string",
+                            "description": "Tile type to place",
+                            "enum": ["empty", "grass", "sand", "water", "wall", "tree"]
+                        }
+                    },
+                    "required": ["agent_id", "x", "y", "tile"]
+                }),
+            }
+        });
     }
 
     /// Register a custom tool
@@ -557,6 +1713,61 @@ impl Agent {
         self.enabled_tools.iter().cloned().collect()
     }
 
+    /// Check if the agent may place a tile type via set_tile
+    pub fn can_place_tile(&self, tile: TileKind) -> bool {
+        self.placeable_tiles.contains(&tile)
+    }
+
+    /// Allow or forbid placing a tile type via set_tile
+    pub fn set_tile_placeable(&mut self, tile: TileKind, allowed: bool) {
+        if allowed {
+            self.placeable_tiles.insert(tile);
+        } else {
+            self.placeable_tiles.remove(&tile);
+        }
+    }
+
+    /// Build the messages sent to the LLM: system prompt plus the last N history messages
+    /// `pending_instruction` is appended as a user message without touching the history
+    fn request_messages(&self, map: &GridMap, pending_instruction: Option<&str>) -> Vec<Message> {
+        let mut history: Vec<Message> = self.chat_history.clone();
+        if let Some(instruction) = pending_instruction.filter(|i| !i.is_empty()) {
+            history.push(Message {
+                role: "user".into(),
+                content: Some(instruction.to_string()),
+                tool_calls: None,
+                tool_call_id: None,
+                name: None,
+            });
+        }
+
+        let mut messages = vec![Message {
+            role: "system".into(),
+            content: Some(self.generate_system_prompt(map)),
+            tool_calls: None,
+            tool_call_id: None,
+            name: None,
+        }];
+
+        // Add last N chat history messages (includes tool results from previous turns)
+        let start_idx = history.len().saturating_sub(self.max_history_messages);
+        messages.extend(history.drain(start_idx..));
+        messages
+    }
+
+    /// Build the exact request body `execute_instruction` would send, without sending it
+    pub fn build_request_body(
+        &self,
+        map: &GridMap,
+        model: &str,
+        pending_instruction: Option<&str>,
+        streaming: bool,
+    ) -> Value {
+        let messages = self.request_messages(map, pending_instruction);
+        let tools = self.get_tools();
+        build_chat_request(model, &messages, Some(&tools), self.temperature, streaming)
+    }
+
     /// Execute an instruction via LLM
     pub fn execute_instruction(
         &mut self,
@@ -567,13 +1778,21 @@ impl Agent {
         tool_callback: Arc<Mutex<Vec<(u32, String, Value)>>>,
         log_callback: Arc<Mutex<Vec<(u32, LogEntry)>>>,
         llm_status_callback: Arc<Mutex<bool>>,
+        streaming: bool,
+        retries: u32,
     ) {
-        let system_prompt = self.generate_system_prompt(map);
-
         // Check if this is a continuation (empty instruction) or new instruction
         let is_continuation = instruction.is_empty();
 
         if !is_continuation {
+            // A new instruction starts a new run
+            self.run_stats = RunStats {
+                instruction: instruction.clone(),
+                started_at: Some(Instant::now()),
+                path: vec![self.pos()],
+                ..RunStats::default()
+            };
+
             // Log the user instruction
             self.log(LogEntry::UserInstruction(instruction.clone()));
 
@@ -587,44 +1806,74 @@ impl Agent {
             });
         }
 
-        let agent_id = self.id;
-        let tools = self.get_tools();
-
-        // Build messages with history
-        let mut messages = vec![Message {
-            role: "system".into(),
-            content: Some(system_prompt),
-            tool_calls: None,
-            tool_call_id: None,
-            name: None,
-        }];
+        self.run_stats.llm_turns += 1;
 
-        // Add last N chat history messages (includes tool results from previous turns)
-        let start_idx = self
-            .chat_history
-            .len()
-            .saturating_sub(self.max_history_messages);
-        for msg in &self.chat_history[start_idx..] {
-            messages.push(msg.clone());
+        let agent_id = self.id;
+        let request_body = self.build_request_body(map, &model, None, streaming);
+        let decision = self
+            .decision_logging
+            .then(|| self.begin_decision_record(&request_body, &api_key));
+        let streaming_thinking = self.streaming_thinking.clone();
+        streaming_thinking.lock().unwrap().clear();
+
+        // Each stream gets its own abort flag, so aborting a stale one can't stop this one
+        let abort = Arc::new(AtomicBool::new(false));
+        self.stream_abort = abort.clone();
+        let activity = self.last_stream_activity.clone();
+        let clock = self.clock.clone();
+        *activity.lock().unwrap() = clock.now();
+
+        // Set LLM active flag before spawning so the app never sees an idle gap
+        if let Ok(mut status) = llm_status_callback.lock() {
+            *status = true;
         }
 
         // Spawn async task for streaming
         wasm_bindgen_futures::spawn_local(async move {
-            // Set LLM active flag
-            if let Ok(mut status) = llm_status_callback.lock() {
-                *status = true;
-            }
 
             let mut name_buf: Option<String> = None;
             let mut args_buf = String::new();
             let mut content_buf = String::new();
-            let mut stream = open_router_event_stream(api_key, model, messages, None, Some(tools));
+            let mut stream_errors = Vec::new();
+            let mut usage = None;
+            // Without streaming the whole reply arrives at once, replayed as the same events
+            let mut stream = if streaming {
+                open_router_event_stream(api_key, request_body, retries)
+            } else {
+                futures::stream::once(open_router_completion(api_key, request_body, retries))
+                    .flat_map(|result| {
+                        let events = match result {
+                            Ok(events) => events.into_iter().map(Ok).collect(),
+                            Err(e) => vec![Err(e)],
+                        };
+                        futures::stream::iter(events)
+                    })
+                    .boxed_local()
+            };
+            let finish_record = |update: &dyn Fn(&mut DecisionRecord)| {
+                if let Some((log, turn)) = &decision
+                    && let Some(record) =
+                        log.lock().unwrap().iter_mut().find(|record| record.turn == *turn)
+                {
+                    update(record);
+                    record.finished = true;
+                }
+            };
 
             while let Some(evt) = stream.next().await {
+                if abort.load(Ordering::Relaxed) {
+                    web_sys::console::log_1(&"Stream aborted; dropping response".into());
+                    finish_record(&|record| record.errors.push("Stream aborted".to_string()));
+                    return;
+                }
+                *activity.lock().unwrap() = clock.now();
                 match evt {
                     Ok(OpenRouterEvent::Content(c)) => {
                         web_sys::console::log_1(&format!("Content: {}", c).into());
                         content_buf.push_str(&c);
+                        if let Ok(mut live) = streaming_thinking.lock() {
+                            live.push_str(&c);
+                        }
                     }
                     Ok(OpenRouterEvent::ToolCallDelta {
                         name,
@@ -644,20 +1893,41 @@ impl Agent {
                             args_buf.push_str(&a);
                         }
                     }
+                    Ok(OpenRouterEvent::Usage { prompt_tokens, completion_tokens, cost }) => {
+                        // Logged after the turn's output so it reads as the turn's footer
+                        usage = Some(LogEntry::Usage { prompt_tokens, completion_tokens, cost });
+                    }
                     Err(e) => {
                         web_sys::console::log_1(&format!("Stream error: {}", e).into());
+                        stream_errors.push(e.to_string());
                         if let Ok(mut g) = log_callback.lock() {
-                            g.push((agent_id, LogEntry::Error(format!("Stream error: {}", e))));
+                            g.push((
+                                agent_id,
+                                LogEntry::Error(ErrorKind::Network, format!("Stream error: {}", e)),
+                            ));
                         }
                     }
                 }
             }
 
-            // Log accumulated agent thinking content if any
+            if abort.load(Ordering::Relaxed) {
+                finish_record(&|record| record.errors.push("Stream aborted".to_string()));
+                return;
+            }
+            finish_record(&|record| {
+                record.raw_output = content_buf.clone();
+                record.tool_name = name_buf.clone();
+                record.raw_arguments = args_buf.clone();
+                record.errors = stream_errors.clone();
+            });
+
+            // Log accumulated agent thinking content if any; logging it clears the live preview
             if !content_buf.is_empty() {
                 if let Ok(mut g) = log_callback.lock() {
                     g.push((agent_id, LogEntry::AgentThinking(content_buf)));
                 }
+            } else if let Ok(mut live) = streaming_thinking.lock() {
+                live.clear();
             }
 
             web_sys::console::log_1(
@@ -677,6 +1947,7 @@ impl Agent {
                 }
 
                 web_sys::console::log_1(&format!("Final args with agent_id: {:?}", parsed).into());
+                finish_record(&|record| record.parsed_arguments = Some(parsed.clone()));
 
                 // Log the tool call - use rich proposal for tools that have custom UI
                 if let Ok(mut g) = log_callback.lock() {
@@ -701,6 +1972,12 @@ impl Agent {
                 }
             }
 
+            if let Some(usage) = usage
+                && let Ok(mut g) = log_callback.lock()
+            {
+                g.push((agent_id, usage));
+            }
+
             // Clear LLM active flag when streaming completes
             if let Ok(mut status) = llm_status_callback.lock() {
                 *status = false;
@@ -709,12 +1986,18 @@ impl Agent {
     }
 
     /// Handle a tool call for this agent
+    ///
+    /// `queued` is this agent's move events still waiting in the event queue, which is
+    /// where moves live once the tool execution manager has submitted them.
     pub fn handle_tool_call(
         &mut self,
         name: &str,
         args: Value,
         map: &mut GridMap,
+        queued: &[Direction],
     ) -> Result<String, String> {
+        self.run_stats.tool_calls += 1;
+
         // Handle get_map_state tool (no agent_id required)
         if name == "get_map_state" {
             // Parse optional area and visibility parameters
@@ -728,7 +2011,16 @@ impl Agent {
                 .and_then(|v| v.as_u64())
                 .map(|v| v as usize);
 
-            let map_state = self.map_state_json_with_params(map, area, visibility);
+            let format = match args.get("format").and_then(|v| v.as_str()) {
+                None => MapStateFormat::default(),
+                Some(name) => MapStateFormat::from_name(name)
+                    .ok_or_else(|| format!("unknown format: {} (use ascii or sparse)", name))?,
+            };
+
+            if self.fog_of_war {
+                self.explore_around(map, self.clamped_visibility(visibility));
+            }
+            let map_state = self.map_state_json_with_params(map, area, visibility, format);
             // Don't log here - the ToolProposal log entry will display the map fetch
             return Ok(map_state);
         }
@@ -745,6 +2037,18 @@ impl Agent {
             return Ok(format!("Recorded thoughts: {}", thoughts));
         }
 
+        // Handle measure tool (no agent_id required; only reads the map)
+        if name == "measure" {
+            let point = |key: &str| {
+                let point = args.get(key)?;
+                Some((point.get("x")?.as_u64()? as usize, point.get("y")?.as_u64()? as usize))
+            };
+            let (Some(from), Some(to)) = (point("from"), point("to")) else {
+                return Err("measure needs from and to coordinates with x and y".to_string());
+            };
+            return measure_distances(map, from, to).map(|result| result.to_string());
+        }
+
         // Verify agent_id matches for other tools
         let agent_id_val = args
             .get("agent_id")
@@ -766,9 +2070,19 @@ impl Agent {
 
         let result = match name {
             "move_agent" => self.handle_move_agent_tool(args, map),
-            "get_position" => self.handle_get_position_tool(),
+            "get_position" => self.handle_get_position_tool(map),
             "get_available_directions" => self.handle_get_available_directions_tool(map),
+            "probe_neighborhood" => self.handle_probe_neighborhood_tool(args, map),
             "get_bearings" => self.handle_get_bearings_tool(args, map),
+            "explain_route" => self.handle_explain_route_tool(args, map),
+            "explain_last_path" => Ok(json!({
+                "explanation": self
+                    .last_path_explanation()
+                    .unwrap_or("No route has been planned yet; use explain_route first")
+            })
+            .to_string()),
+            "get_current_plan" => Ok(self.handle_get_current_plan_tool(map, queued)),
+            "set_tile" => self.handle_set_tile_tool(args, map),
             _ => Err(format!("unknown tool: {}", name)),
         };
 
@@ -794,7 +2108,9 @@ impl Agent {
     }
 
     /// Execute a single move step (used by event system)
-    /// Returns Err with "ABORT" prefix if movement should cancel remaining events
+    /// Returns Err with "ABORT" prefix if movement should cancel remaining events (see `MovementErrorPolicy`)
+    /// With a `current_target`, a tile-blocked step returns `REPLAN_PREFIX` instead and the
+    /// caller decides between a new route and the policy
     pub fn execute_move_step(
         &mut self,
         direction: Direction,
@@ -804,88 +2120,223 @@ impl Agent {
         let w = map.width() as i32;
         let h = map.height() as i32;
 
-        let (nx, ny) = direction.apply(x, y, w, h);
+        let (nx, ny) = direction.apply(map.topology(), x, y);
 
         // Check bounds
         if nx < 0 || ny < 0 || nx >= w || ny >= h {
-            let err = "ABORT: Movement blocked - edge of map".to_string();
-            self.log(LogEntry::Error("Movement blocked: edge of map".to_string()));
-            return Err(err);
+            self.log_error(ErrorKind::Movement, "Movement blocked: edge of map");
+            return Err(self.blocked_step_error("Movement blocked - edge of map".to_string()));
         }
 
-        // Check traversability
-        if !map.is_traversable(nx as usize, ny as usize) {
-            let tile_type = map
-                .get(nx as usize, ny as usize)
-                .map(|t| t.name())
-                .unwrap_or("unknown");
-            let err = format!(
-                "ABORT: Movement blocked by {} tile at ({}, {})",
-                tile_type, nx, ny
-            );
-            self.log(LogEntry::Error(format!(
+        // Check traversability of every cell the agent would cover (and any cut corner)
+        let from = self.pos();
+        if let Some((bx, by)) =
+            self.blocked_step_cell(map, from, &direction, nx as usize, ny as usize)
+        {
+            if !map.in_bounds(bx, by) {
+                self.log_error(ErrorKind::Movement, "Movement blocked: edge of map");
+                return Err(self.blocked_step_error("Movement blocked - edge of map".to_string()));
+            }
+            if !self.dynamic_obstacles.contains(&(bx, by)) {
+                self.known_obstacles.insert((bx, by));
+            }
+            let tile_type = self.blocker_name(map, bx, by);
+            let reason = format!("Movement blocked by {} tile at ({}, {})", tile_type, bx, by);
+            // With a target the rest of the sequence may be replanned around the block
+            if self.current_target.is_some() {
+                self.log_info(format!(
+                    "{} moving {}; replanning towards the target",
+                    reason,
+                    direction.as_str()
+                ));
+                return Err(format!("{}{}", REPLAN_PREFIX, reason));
+            }
+            self.log_error(ErrorKind::Movement, format!(
                 "Movement blocked by {} tile at ({}, {})\n\
                 Agent position: ({}, {})\n\
                 Attempted move: {} to ({}, {})\n\
-                Traversable tiles: empty, grass, sand, trail\n\
-                Blocking tiles: wall, water, tree",
+                {}",
                 tile_type,
-                nx,
-                ny,
+                bx,
+                by,
                 self.x,
                 self.y,
                 direction.as_str(),
                 nx,
-                ny
-            )));
-            return Err(err);
+                ny,
+                self.traversability_details()
+            ));
+            return Err(self.blocked_step_error(reason));
+        }
+
+        // Running dry ends the sequence whatever the error policy
+        if !self.spend_energy(map, nx as usize, ny as usize) {
+            return Err("ABORT: Movement failed - out of energy".to_string());
         }
 
         // Record current position in movement history
-        self.movement_history.push((self.x, self.y));
+        self.push_movement_history((self.x, self.y));
+        self.mark_trail(map, self.x, self.y);
 
         // Move agent
         self.set_pos(nx as usize, ny as usize);
+        self.facing = direction.clone();
+        self.record_run_move(from);
         self.log(LogEntry::Movement {
             direction: direction.as_str().to_string(),
             position: (self.x, self.y),
         });
+        self.apply_portal(map);
+        self.refuel_if_on_fuel(map);
 
         Ok(())
     }
 
-    /// Handle the get_position tool
-    fn handle_get_position_tool(&self) -> Result<String, String> {
-        Ok(serde_json::to_string(&json!({
-            "x": self.x,
-            "y": self.y
-        }))
-        .unwrap_or_else(|_| format!("{{\"x\": {}, \"y\": {}}}", self.x, self.y)))
+    /// Teleport the agent if it just stepped onto a paired portal
+    ///
+    /// Only called after a regular step, never after a teleport, so two portals pointing
+    /// at each other can't bounce the agent back and forth.
+    fn apply_portal(&mut self, map: &GridMap) {
+        let Some(&TileKind::Portal(portal)) = map.get(self.x, self.y) else {
+            return;
+        };
+        let Some((ex, ey)) = map.portal_exit(self.x, self.y) else {
+            return; // Unpaired portal behaves like an ordinary tile
+        };
+        if !self.can_enter(map, ex, ey) {
+            self.log_info(format!(
+                "Portal {} exit at ({}, {}) is blocked; staying put",
+                portal, ex, ey
+            ));
+            return;
+        }
+
+        let from = self.pos();
+        self.push_movement_history(from);
+        self.set_pos(ex, ey);
+        if self.run_stats.is_active() {
+            self.run_stats.path.push((ex, ey));
+        }
+        self.log(LogEntry::Teleport {
+            portal,
+            from,
+            to: (ex, ey),
+        });
     }
 
-    /// Handle the get_available_directions tool
-    fn handle_get_available_directions_tool(&self, map: &GridMap) -> Result<String, String> {
+    /// Handle the get_position tool
+    fn handle_get_position_tool(&self, map: &GridMap) -> Result<String, String> {
+        let verbosity = self.tool_verbosity;
+        let mut result = json!({"x": self.x, "y": self.y});
+        if verbosity != ToolVerbosity::Terse {
+            result["tile"] =
+                json!(self.terrain_at(map, self.x, self.y).map_or("unknown", |t| t.name()));
+        }
+        // Only report goal/target status when one is set
+        if let Some((gx, gy)) = self.goal {
+            result["on_goal"] = json!(self.covers(gx, gy));
+            if verbosity == ToolVerbosity::Verbose {
+                result["goal"] = json!({"x": gx, "y": gy});
+            }
+        }
+        if let Some((tx, ty)) = self.current_target {
+            result["on_target"] = json!(self.covers(tx, ty));
+            if verbosity == ToolVerbosity::Verbose {
+                result["target"] = json!({"x": tx, "y": ty});
+            }
+        }
+        if let Some((energy, capacity)) = self.energy() {
+            result["energy"] = json!({"remaining": energy, "capacity": capacity});
+        }
+        Ok(serde_json::to_string(&result)
+            .unwrap_or_else(|_| format!("{{\"x\": {}, \"y\": {}}}", self.x, self.y)))
+    }
+
+    /// Handle the get_current_plan tool: the remaining queued moves and where they lead
+    fn handle_get_current_plan_tool(&self, map: &GridMap, queued: &[Direction]) -> String {
+        let (x, y) = self.pos();
+        let remaining: Vec<Direction> = self.pending_moves.iter().chain(queued).cloned().collect();
+        if remaining.is_empty() {
+            return json!({
+                "queued": 0,
+                "moves": [],
+                "endpoint": {"x": x, "y": y},
+                "message": "No moves are queued; you are standing still",
+            })
+            .to_string();
+        }
+        let (end_x, end_y) = self.planned_path(map, queued).last().copied().unwrap_or((x, y));
+        let moves: Vec<&str> = remaining.iter().map(|d| d.as_str()).collect();
+        let mut result = json!({
+            "queued": moves.len(),
+            "moves": moves,
+            "from": {"x": x, "y": y},
+            "endpoint": {"x": end_x, "y": end_y},
+        });
+        if let Some((index, reason)) = self.first_blocked_step(map, &remaining) {
+            result["blocked"] = json!({"step": index + 1, "by": reason});
+        }
+        result.to_string()
+    }
+
+    /// Handle the probe_neighborhood tool
+    fn handle_probe_neighborhood_tool(&self, args: Value, map: &GridMap) -> Result<String, String> {
+        let size = args.get("size").and_then(|v| v.as_u64()).unwrap_or(3) as usize;
+        if !(3..=MAX_PROBE_SIZE).contains(&size) || size.is_multiple_of(2) {
+            return Err(format!(
+                "size must be an odd number from 3 to {}, got {}",
+                MAX_PROBE_SIZE, size
+            ));
+        }
+
+        let radius = (size / 2) as i64;
+        let grid: Vec<Vec<&str>> = (-radius..=radius)
+            .map(|dy| {
+                (-radius..=radius)
+                    .map(|dx| {
+                        let (x, y) = (self.x as i64 + dx, self.y as i64 + dy);
+                        if x < 0 || y < 0 {
+                            return "edge";
+                        }
+                        let (x, y) = (x as usize, y as usize);
+                        match map.get(x, y) {
+                            None => "edge",
+                            Some(_) if self.covers(x, y) => "agent",
+                            Some(_) => self.terrain_at(map, x, y).map_or("unknown", |t| t.name()),
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut result = json!({
+            "position": {"x": self.x, "y": self.y},
+            "top_left": {"x": self.x as i64 - radius, "y": self.y as i64 - radius},
+            "grid": grid
+        });
+        // The center and size follow from the grid itself, so terse results leave them out
+        if self.tool_verbosity != ToolVerbosity::Terse {
+            let standing_on = self.terrain_at(map, self.x, self.y);
+            result["standing_on"] = json!(standing_on.map_or("unknown", |t| t.name()));
+            result["size"] = json!(size);
+            result["center"] = json!({"row": radius, "col": radius});
+        }
+        Ok(result.to_string())
+    }
+
+    /// Handle the get_available_directions tool
+    fn handle_get_available_directions_tool(&self, map: &GridMap) -> Result<String, String> {
         let mut valid_directions = Vec::new();
 
-        for direction in [
-            Direction::Up,
-            Direction::Down,
-            Direction::Left,
-            Direction::Right,
-        ] {
-            let (nx, ny) = direction.apply(
-                self.x as i32,
-                self.y as i32,
-                map.width() as i32,
-                map.height() as i32,
-            );
+        for direction in self.directions(map) {
+            let (nx, ny) = direction.apply(map.topology(), self.x as i32, self.y as i32);
 
-            // Check bounds and traversability
+            // Check bounds and traversability, including corners cut by diagonals
             if nx >= 0
                 && ny >= 0
-                && nx < map.width() as i32
-                && ny < map.height() as i32
-                && map.is_traversable(nx as usize, ny as usize)
+                && self
+                    .blocked_step_cell(map, self.pos(), direction, nx as usize, ny as usize)
+                    .is_none()
             {
                 valid_directions.push(direction.as_str().to_string());
             }
@@ -921,56 +2372,90 @@ impl Agent {
 
         // Add target info if provided
         if let Some((tx, ty)) = target {
+            let moves = self.directions(map);
+            let distance = move_distance(map.topology(), moves, self.pos(), (tx, ty));
             let dx = tx as i32 - self.x as i32;
             let dy = ty as i32 - self.y as i32;
             result["target"] = json!({
                 "x": tx,
                 "y": ty,
-                "distance": dx.abs() + dy.abs(),
+                "distance": distance,
                 "delta_x": dx,
                 "delta_y": dy
             });
         }
 
         // Check what's blocking/open in each cardinal direction
+        let verbosity = self.tool_verbosity;
         let mut blocking_directions = Vec::new();
         let mut open_directions = Vec::new();
+        let mut open_cells = Vec::new();
 
-        for (dir, dir_name) in [
-            (Direction::Up, "north"),
-            (Direction::Down, "south"),
-            (Direction::Left, "west"),
-            (Direction::Right, "east"),
-        ] {
-            let (nx, ny) = dir.apply(
-                self.x as i32,
-                self.y as i32,
-                map.width() as i32,
-                map.height() as i32,
-            );
+        for dir in self.directions(map) {
+            let dir_name = dir.compass();
+            let (nx, ny) = dir.apply(map.topology(), self.x as i32, self.y as i32);
 
-            if nx < 0 || ny < 0 || nx >= map.width() as i32 || ny >= map.height() as i32 {
-                blocking_directions.push(json!({
+            // Off-map for the anchor or for any covered cell counts as the map edge
+            let blocked = if nx < 0 || ny < 0 {
+                Some(None)
+            } else {
+                self.blocked_step_cell(map, self.pos(), dir, nx as usize, ny as usize)
+                    .map(|(bx, by)| self.terrain_at(map, bx, by))
+            };
+            match blocked {
+                // Terse results just name the blocked directions
+                Some(_) if verbosity == ToolVerbosity::Terse => {
+                    blocking_directions.push(json!(dir_name))
+                }
+                Some(None) => blocking_directions.push(json!({
                     "direction": dir_name,
                     "reason": "map_edge"
-                }));
-            } else if !map.is_traversable(nx as usize, ny as usize) {
-                let tile = map
-                    .get(nx as usize, ny as usize)
-                    .map(|t| t.name())
-                    .unwrap_or("unknown");
-                blocking_directions.push(json!({
+                })),
+                Some(Some(tile)) => blocking_directions.push(json!({
                     "direction": dir_name,
                     "reason": "obstacle",
-                    "tile": tile
-                }));
-            } else {
-                open_directions.push(dir_name);
+                    "tile": tile.name()
+                })),
+                None => {
+                    open_directions.push(dir_name);
+                    open_cells.push((dir_name, nx as usize, ny as usize));
+                }
             }
         }
 
         result["blocking_directions"] = json!(blocking_directions);
         result["open_directions"] = json!(open_directions);
+        if verbosity == ToolVerbosity::Verbose {
+            result["open_cells"] = json!(
+                open_cells
+                    .iter()
+                    .map(|&(name, x, y)| json!({
+                        "direction": name,
+                        "x": x,
+                        "y": y,
+                        "tile": self.terrain_at(map, x, y).map_or("unknown", |t| t.name())
+                    }))
+                    .collect::<Vec<_>>()
+            );
+        }
+        if let Some(target) = target
+            && let Some(ranking) = self.rank_open_directions(map, &open_cells, target)
+        {
+            result["recommended"] = if verbosity == ToolVerbosity::Terse {
+                ranking["direction"].clone()
+            } else {
+                ranking
+            };
+        }
+        if verbosity == ToolVerbosity::Terse {
+            return Ok(result.to_string());
+        }
+        if !self.known_obstacles.is_empty() {
+            let mut known: Vec<_> = self.known_obstacles.iter().copied().collect();
+            known.sort_unstable_by_key(|&(x, y)| (y, x));
+            result["known_obstacles"] =
+                json!(known.iter().map(|&(x, y)| json!([x, y])).collect::<Vec<_>>());
+        }
 
         // Add navigation advice
         let mut advice = Vec::new();
@@ -993,8 +2478,179 @@ impl Agent {
             .unwrap_or_else(|_| format!("{{\"position\": {{\"x\": {}, \"y\": {}}}}}", self.x, self.y)))
     }
 
+    /// Handle the explain_route tool
+    fn handle_explain_route_tool(&mut self, args: Value, map: &GridMap) -> Result<String, String> {
+        let target = args
+            .get("target")
+            .and_then(|t| {
+                let x = t.get("x")?.as_u64()? as usize;
+                let y = t.get("y")?.as_u64()? as usize;
+                Some((x, y))
+            })
+            .ok_or("explain_route needs a target with x and y")?;
+        if !map.in_bounds(target.0, target.1) {
+            return Err(format!(
+                "Target ({}, {}) is outside the {}x{} map",
+                target.0,
+                target.1,
+                map.width(),
+                map.height()
+            ));
+        }
+
+        let path = self.plan_route(map, target).ok_or_else(|| {
+            let mut error = format!(
+                "No route from ({}, {}) to ({}, {})",
+                self.x, self.y, target.0, target.1
+            );
+            if self.fog_of_war {
+                error.push_str(
+                    " through explored tiles; explore further with get_map_state and moves",
+                );
+            }
+            error
+        })?;
+        let directions = Self::directions_along(map, &path)
+            .ok_or("Planned route contains a step that isn't a single move")?;
+        let summary = explain_route(map, self.pos(), &directions, |x, y| self.can_enter(map, x, y));
+
+        let mut result = json!({
+            "moves": directions.len(),
+            "directions": directions.iter().map(|d| d.as_str()).collect::<Vec<_>>(),
+            "summary": summary
+        });
+        if !self.tile_costs.is_uniform() || map.has_elevation() {
+            let cost: u32 = path.windows(2).map(|pair| self.step_cost(map, pair[0], pair[1])).sum();
+            result["cost"] = json!(cost);
+        }
+        // Terse results skip echoing back the endpoints the model already knows
+        if self.tool_verbosity != ToolVerbosity::Terse {
+            result["from"] = json!({"x": self.x, "y": self.y});
+            result["target"] = json!({"x": target.0, "y": target.1});
+        }
+        Ok(result.to_string())
+    }
+
+    /// Pick the open direction with the lowest estimated cost to `target` for get_bearings
+    ///
+    /// Each candidate scores the cost of the step (landing on a portal's exit when it
+    /// teleports) plus the grid distance left. With uniform tile costs on a flat map that
+    /// is just the distance, so the basis is reported as "distance" rather than
+    /// "terrain_cost".
+    fn rank_open_directions(
+        &self,
+        map: &GridMap,
+        open_cells: &[(&str, usize, usize)],
+        target: (usize, usize),
+    ) -> Option<Value> {
+        let uniform = self.tile_costs.is_uniform() && !map.has_elevation();
+        let moves = self.directions(map);
+        let mut ranked: Vec<(&str, u32)> = open_cells
+            .iter()
+            .map(|&(name, x, y)| {
+                let landing = match map.portal_exit(x, y) {
+                    Some((ex, ey))
+                        if self.can_enter(map, ex, ey) && self.footprint_explored(ex, ey) =>
+                    {
+                        (ex, ey)
+                    }
+                    _ => (x, y),
+                };
+                let remaining = move_distance(map.topology(), moves, landing, target);
+                let step = if uniform { 0 } else { self.step_cost(map, self.pos(), (x, y)) };
+                (name, step + remaining)
+            })
+            .collect();
+        // Stable sort keeps the north/south/west/east order among ties
+        ranked.sort_by_key(|&(_, estimate)| estimate);
+        let &(best, estimate) = ranked.first()?;
+
+        Some(json!({
+            "direction": best,
+            "basis": if uniform { "distance" } else { "terrain_cost" },
+            "estimate": estimate,
+            "ranking": ranked
+                .iter()
+                .map(|&(name, estimate)| json!({"direction": name, "estimate": estimate}))
+                .collect::<Vec<_>>()
+        }))
+    }
+
+    /// Handle the set_tile tool
+    fn handle_set_tile_tool(&mut self, args: Value, map: &mut GridMap) -> Result<String, String> {
+        let x = args.get("x").and_then(|v| v.as_u64()).ok_or("missing x")? as usize;
+        let y = args.get("y").and_then(|v| v.as_u64()).ok_or("missing y")? as usize;
+        let tile_name = args
+            .get("tile")
+            .and_then(|v| v.as_str())
+            .ok_or("missing tile")?;
+
+        let tile = TileKind::from_name(tile_name)
+            .ok_or_else(|| format!("unknown tile type: {}", tile_name))?;
+
+        if !self.can_place_tile(tile) {
+            return Err(format!("not allowed to place {} tiles", tile.name()));
+        }
+
+        if !map.in_bounds(x, y) {
+            return Err(format!(
+                "({}, {}) is outside the map ({}x{})",
+                x,
+                y,
+                map.width(),
+                map.height()
+            ));
+        }
+
+        let blocking = !self.traversability.is_passable(&tile);
+        if self.covers(x, y) && blocking {
+            return Err(format!("cannot place {} on your own position", tile.name()));
+        }
+
+        let previous = self.terrain_at(map, x, y).unwrap_or(TileKind::Empty);
+        map.set(x, y, tile);
+        if !blocking {
+            self.known_obstacles.remove(&(x, y));
+        }
+        self.log_info(format!(
+            "Agent changed tile at ({}, {}) from {} to {}",
+            x,
+            y,
+            previous.name(),
+            tile.name()
+        ));
+
+        let mut result = json!({
+            "x": x,
+            "y": y,
+            "previous_tile": previous.name(),
+            "tile": tile.name()
+        });
+
+        // Warn if that placement left the agent with no way out
+        if blocking && !self.has_open_neighbor(map) {
+            let warning = "You are now walled in: no adjacent tile is traversable.";
+            self.log_error(ErrorKind::Tool, format!("WARN: {}", warning));
+            result["warning"] = json!(warning);
+        }
+
+        Ok(serde_json::to_string(&result).unwrap_or_default())
+    }
+
+    /// Check whether any cardinal neighbor of the agent is traversable
+    fn has_open_neighbor(&self, map: &GridMap) -> bool {
+        self.directions(map).iter().any(|dir| {
+            let (nx, ny) = dir.apply(map.topology(), self.x as i32, self.y as i32);
+            nx >= 0
+                && ny >= 0
+                && self
+                    .blocked_step_cell(map, self.pos(), dir, nx as usize, ny as usize)
+                    .is_none()
+        })
+    }
+
     /// Handle the move_agent tool - returns directions for event submission
-    fn handle_move_agent_tool(&mut self, args: Value, _map: &GridMap) -> Result<String, String> {
+    fn handle_move_agent_tool(&mut self, args: Value, map: &GridMap) -> Result<String, String> {
         let steps = args
             .get("steps")
             .and_then(|v| v.as_array())
@@ -1011,19 +2667,29 @@ impl Agent {
             Some((x, y))
         });
 
-        // Parse directions
+        // Parse directions, keeping to the moves this map's topology has
+        let allowed = self.directions(map);
         let mut directions = Vec::new();
         for step in steps {
             let step_str = step.as_str().ok_or("step must be string")?;
-            directions.push(Direction::from_str(step_str)?);
+            let direction = Direction::from_str(step_str)?;
+            if !allowed.contains(&direction) {
+                let names: Vec<&str> = allowed.iter().map(Direction::as_str).collect();
+                return Err(format!(
+                    "'{}' is not a move on a {} grid; use {}",
+                    step_str,
+                    map.topology().label().to_lowercase(),
+                    names.join(", ")
+                ));
+            }
+            directions.push(direction);
         }
 
         // Store for event submission (handled by caller)
         self.pending_moves = directions;
         self.movement_active = true;
-        self.next_step_at = Some(Instant::now());
-        self.total_movement_steps = self.pending_moves.len();
-        self.movement_step_index = 0;
+        self.next_step_at = Some(self.clock.now());
+        self.replans_this_sequence = 0;
 
         let target_info = if let Some((tx, ty)) = self.current_target {
             format!(" towards target ({}, {})", tx, ty)
@@ -1039,23 +2705,338 @@ impl Agent {
             self.y
         ));
 
-        Ok(String::new())
+        // Initial result; the tool execution manager appends the outcome once the moves run
+        let step_names: Vec<&str> = self.pending_moves.iter().map(|d| d.as_str()).collect();
+        let mut result = format!(
+            "Queued {} step{}{} from ({}, {}): {}.",
+            step_names.len(),
+            if step_names.len() == 1 { "" } else { "s" },
+            target_info,
+            self.x,
+            self.y,
+            step_names.join(", ")
+        );
+
+        // Upfront warning, so the LLM learns about a bad plan before the steps play out
+        if let Some((index, reason)) = self.first_blocked_step(map, &self.pending_moves) {
+            let warning = format!(
+                "Look-ahead: step {} ({}) will be blocked by {}.",
+                index + 1,
+                step_names[index],
+                reason
+            );
+            self.log_info(warning.clone());
+            result.push(' ');
+            result.push_str(&warning);
+        }
+        Ok(result)
+    }
+
+    /// Simulate `directions` from the current position without moving the agent
+    ///
+    /// Mirrors `execute_move_step` (including portal jumps) and returns the index of
+    /// the first step that would be blocked, with what blocks it. Under fog of war the
+    /// check stops at the first step into cells the agent hasn't seen.
+    pub fn first_blocked_step(
+        &self,
+        map: &GridMap,
+        directions: &[Direction],
+    ) -> Option<(usize, String)> {
+        let (w, h) = (map.width() as i32, map.height() as i32);
+        let (mut x, mut y) = self.pos();
+        for (index, direction) in directions.iter().enumerate() {
+            let (nx, ny) = direction.apply(map.topology(), x as i32, y as i32);
+            if nx < 0 || ny < 0 || nx >= w || ny >= h {
+                return Some((index, "the edge of the map".to_string()));
+            }
+            let (nx, ny) = (nx as usize, ny as usize);
+            if !self.footprint_explored(nx, ny) {
+                return None;
+            }
+            if let Some((bx, by)) = self.blocked_step_cell(map, (x, y), direction, nx, ny) {
+                let reason = if map.in_bounds(bx, by) {
+                    format!("a {} tile at ({}, {})", self.blocker_name(map, bx, by), bx, by)
+                } else {
+                    "the edge of the map".to_string()
+                };
+                return Some((index, reason));
+            }
+            (x, y) = (nx, ny);
+            if let Some(TileKind::Portal(_)) = map.get(x, y)
+                && let Some((ex, ey)) = map.portal_exit(x, y)
+                && self.can_enter(map, ex, ey)
+            {
+                (x, y) = (ex, ey);
+            }
+        }
+        None
+    }
+
+    /// Cost of each step of `directions` from the current position, as `step_cost`
+    ///
+    /// Follows the moves like `first_blocked_step`, including portal jumps; a blocked
+    /// step costs the default, since the agent stays put.
+    pub fn step_costs(&self, map: &GridMap, directions: &[Direction]) -> Vec<u32> {
+        let (w, h) = (map.width() as i32, map.height() as i32);
+        let mut pos = self.pos();
+        directions
+            .iter()
+            .map(|direction| {
+                let (nx, ny) = direction.apply(map.topology(), pos.0 as i32, pos.1 as i32);
+                if nx < 0 || ny < 0 || nx >= w || ny >= h {
+                    return TileCosts::DEFAULT_COST;
+                }
+                let next = (nx as usize, ny as usize);
+                if self.blocked_step_cell(map, pos, direction, next.0, next.1).is_some() {
+                    return TileCosts::DEFAULT_COST;
+                }
+                let cost = self.step_cost(map, pos, next);
+                pos = match map.portal_exit(next.0, next.1) {
+                    Some(exit) if self.can_enter(map, exit.0, exit.1) => exit,
+                    _ => next,
+                };
+                cost
+            })
+            .collect()
     }
 
     /// Get and clear pending moves (for event submission)
     pub fn take_pending_moves(&mut self) -> Vec<Direction> {
         self.movement_active = false;
         self.next_step_at = None;
-        self.total_movement_steps = 0;
-        self.movement_step_index = 0;
         std::mem::take(&mut self.pending_moves)
     }
 
+    /// Cells the remaining plan passes through, starting where the agent stands
+    ///
+    /// Follows the agent's own queued moves, then `queued` (moves already handed to the
+    /// event queue), jumping through paired portals the way a real step would. Steps off
+    /// the map are skipped, since the agent stays put.
+    pub fn planned_path(&self, map: &GridMap, queued: &[Direction]) -> Vec<(usize, usize)> {
+        let mut path = vec![self.pos()];
+        for dir in self.pending_moves.iter().chain(queued) {
+            let (x, y) = path[path.len() - 1];
+            let (nx, ny) = dir.apply(map.topology(), x as i32, y as i32);
+            if nx < 0 || ny < 0 || !map.in_bounds(nx as usize, ny as usize) {
+                continue;
+            }
+            let next = (nx as usize, ny as usize);
+            path.push(next);
+            // Like `execute_move_step`, a portal with a blocked exit leaves the agent on it
+            if let Some((ex, ey)) = map.portal_exit(next.0, next.1)
+                && self.can_enter(map, ex, ey)
+            {
+                path.push((ex, ey));
+            }
+        }
+        path
+    }
+
     /// Check if agent is currently moving
     pub fn is_moving(&self) -> bool {
         self.movement_active
     }
 
+    /// Get stats for the current (or most recent) run
+    pub fn run_stats(&self) -> &RunStats {
+        &self.run_stats
+    }
+
+    /// Count a move from `from` toward the active run (manual moves between runs don't count)
+    fn record_run_move(&mut self, from: (usize, usize)) {
+        if self.run_stats.is_active() {
+            self.run_stats.moves += 1;
+            self.run_stats.path.push(self.pos());
+            self.run_stats.entered.push((from, self.pos()));
+        }
+    }
+
+    /// Compare the walked route with the shortest and cheapest routes between the same endpoints
+    fn compare_route(&self, map: &GridMap) -> Option<RouteComparison> {
+        let (&start, &end) = (self.run_stats.path.first()?, self.run_stats.path.last()?);
+        let passable = |x, y| self.can_enter(map, x, y);
+        let moves = self.directions(map);
+        let optimal = shortest_path(map, start, end, moves, passable);
+        let cheapest = cheapest_path(map, start, end, moves, passable, |x, y| {
+            self.movement_cost(map, x, y)
+        });
+        Some(RouteComparison {
+            actual_moves: self.run_stats.moves,
+            optimal_moves: optimal.map(|path| path.len() - 1),
+            actual_cost: self
+                .run_stats
+                .entered
+                .iter()
+                .map(|&(from, to)| self.step_cost(map, from, to))
+                .sum(),
+            optimal_cost: cheapest.map(|(_, cost)| cost),
+        })
+    }
+
+    /// Recompute the last run's route comparison, e.g. after tile costs changed
+    pub fn refresh_route_comparison(&mut self, map: &GridMap) {
+        if self.run_stats.route.is_some() {
+            self.run_stats.route = self.compare_route(map);
+        }
+    }
+
+    /// Mark the current run as finished and log its summary
+    pub fn finish_run(&mut self, map: &GridMap) {
+        if !self.run_stats.is_active() {
+            return;
+        }
+        self.run_stats.ended_at = Some(Instant::now());
+
+        self.run_stats.route = self.compare_route(map);
+
+        let mut summary = self.run_stats.summary();
+        if let Some(route) = &self.run_stats.route {
+            summary.push_str(&format!(", {}, {}", route.summary(), route.cost_summary()));
+        }
+        self.log_info(format!("Run finished: {}", summary));
+
+        let stats = &self.run_stats;
+        let success = match self.goal.or(self.current_target) {
+            Some((x, y)) => self.covers(x, y),
+            None => stats.errors == 0,
+        };
+        self.run_history.push(RunRecord {
+            instruction: stats.instruction.clone(),
+            duration_secs: stats.elapsed().as_secs_f32(),
+            moves: stats.moves,
+            tool_calls: stats.tool_calls,
+            llm_turns: stats.llm_turns,
+            errors: stats.errors,
+            path: stats.path.clone(),
+            final_position: self.pos(),
+            success,
+            optimal_moves: stats.route.and_then(|route| route.optimal_moves),
+        });
+    }
+
+    /// LLM tokens and cost used since the agent was created or the totals were reset
+    pub fn token_usage(&self) -> TokenUsage {
+        self.token_usage
+    }
+
+    pub fn reset_token_usage(&mut self) {
+        self.token_usage = TokenUsage::default();
+    }
+
+    /// Finished runs, oldest first
+    pub fn run_history(&self) -> &[RunRecord] {
+        &self.run_history
+    }
+
+    pub fn clear_run_history(&mut self) {
+        self.run_history.clear();
+    }
+
+    /// Start the decision record for a turn about to send `request_body`; returns the
+    /// log handle and turn number the stream task fills it in with
+    fn begin_decision_record(
+        &mut self,
+        request_body: &Value,
+        api_key: &str,
+    ) -> (Arc<Mutex<Vec<DecisionRecord>>>, usize) {
+        self.decision_turns += 1;
+        let mut request = json!({
+            "url": "https://openrouter.ai/api/v1/chat/completions",
+            "headers": {"Authorization": "Bearer [redacted]", "Content-Type": "application/json"},
+            "body": request_body,
+        });
+        // The key only goes in the header, but a pasted one could end up in a message
+        redact_secret(&mut request, api_key);
+        let mut log = self.decision_log.lock().unwrap();
+        if log.len() >= MAX_DECISION_RECORDS {
+            log.remove(0);
+        }
+        log.push(DecisionRecord {
+            turn: self.decision_turns,
+            request,
+            ..DecisionRecord::default()
+        });
+        (self.decision_log.clone(), self.decision_turns)
+    }
+
+    /// Record each LLM turn's full request and response from the next turn on
+    pub fn set_decision_logging(&mut self, enabled: bool) {
+        self.decision_logging = enabled;
+    }
+
+    /// Recorded LLM turns, oldest first
+    pub fn decision_log(&self) -> Vec<DecisionRecord> {
+        self.decision_log.lock().unwrap().clone()
+    }
+
+    /// The decision log as pretty-printed JSON
+    pub fn decision_log_json(&self) -> String {
+        serde_json::to_string_pretty(&*self.decision_log.lock().unwrap()).unwrap_or_default()
+    }
+
+    pub fn clear_decision_log(&mut self) {
+        self.decision_log.lock().unwrap().clear();
+    }
+
+    /// The whole run history as pretty-printed JSON
+    pub fn run_history_json(&self) -> String {
+        serde_json::to_string_pretty(&self.run_history).unwrap_or_default()
+    }
+
+    /// Remaining and total energy, or None when energy is unlimited
+    pub fn energy(&self) -> Option<(u32, u32)> {
+        self.energy_capacity.map(|capacity| (self.energy, capacity))
+    }
+
+    /// Turn the energy budget on with `capacity` (full tank) or off with None
+    pub fn set_energy_capacity(&mut self, capacity: Option<u32>) {
+        self.energy_capacity = capacity.map(|c| c.max(1));
+        self.refill_energy();
+    }
+
+    pub fn refill_energy(&mut self) {
+        self.energy = self.energy_capacity.unwrap_or(0);
+        self.out_of_energy = false;
+    }
+
+    /// Whether a move failed for lack of energy since the last call
+    pub fn take_out_of_energy(&mut self) -> bool {
+        std::mem::take(&mut self.out_of_energy)
+    }
+
+    /// Pay for a step from the current cell onto (x, y) at its `step_cost`; returns false
+    /// (and logs) if the budget can't cover it. Always succeeds with unlimited energy.
+    fn spend_energy(&mut self, map: &GridMap, x: usize, y: usize) -> bool {
+        if self.energy_capacity.is_none() {
+            return true;
+        }
+        let cost = self.step_cost(map, self.pos(), (x, y));
+        if self.energy < cost {
+            self.out_of_energy = true;
+            self.log_error(ErrorKind::Movement, format!(
+                "Out of energy: moving to ({}, {}) costs {} but only {} is left",
+                x, y, cost, self.energy
+            ));
+            return false;
+        }
+        self.energy -= cost;
+        true
+    }
+
+    /// Refill the budget if any cell under the agent is a fuel tile
+    fn refuel_if_on_fuel(&mut self, map: &GridMap) {
+        let Some(capacity) = self.energy_capacity else {
+            return;
+        };
+        let (w, h) = self.size;
+        let on_fuel = (self.y..self.y + h)
+            .any(|y| (self.x..self.x + w).any(|x| map.get(x, y) == Some(&TileKind::Fuel)));
+        if on_fuel && self.energy < capacity {
+            self.refill_energy();
+            self.log_info(format!("Refueled to {} energy at ({}, {})", capacity, self.x, self.y));
+        }
+    }
+
     /// Get maximum history messages sent to LLM
     pub fn max_history_messages(&self) -> usize {
         self.max_history_messages
@@ -1066,11 +3047,206 @@ impl Agent {
         self.max_history_messages = max.max(1); // Minimum of 1
     }
 
+    /// Default `get_map_state` view distance
+    pub fn default_visibility(&self) -> usize {
+        self.default_visibility
+    }
+
+    /// Largest `get_map_state` view distance the model may request
+    pub fn max_visibility(&self) -> usize {
+        self.max_visibility
+    }
+
+    /// Set the default and maximum view distance, and regenerate the tool schema to match
+    pub fn set_visibility_limits(&mut self, default: usize, max: usize) {
+        self.max_visibility = max.clamp(1, MAX_VISIBILITY_LIMIT);
+        self.default_visibility = default.clamp(1, self.max_visibility);
+        let tool = self.map_state_tool();
+        if let Some(existing) = self
+            .tool_registry
+            .iter_mut()
+            .find(|t| t.function.name == tool.function.name)
+        {
+            *existing = tool;
+        }
+    }
+
+    /// Get the sampling temperature sent to the LLM (None = provider default)
+    pub fn temperature(&self) -> Option<f32> {
+        self.temperature
+    }
+
+    /// Set the sampling temperature sent to the LLM
+    pub fn set_temperature(&mut self, temperature: Option<f32>) {
+        self.temperature = temperature.map(|t| t.clamp(0.0, 2.0));
+    }
+
+    /// Apply a model preset's defaults to this agent's configuration
+    pub fn apply_model_preset(&mut self, preset: &ModelPreset) {
+        self.set_max_history_messages(preset.max_history_messages);
+        self.set_temperature(preset.temperature);
+    }
+
     /// Get the agent's movement history (all positions visited)
     pub fn get_movement_history(&self) -> &[(usize, usize)] {
         &self.movement_history
     }
 
+    pub fn movement_error_policy(&self) -> MovementErrorPolicy {
+        self.movement_error_policy
+    }
+
+    pub fn set_movement_error_policy(&mut self, policy: MovementErrorPolicy) {
+        self.movement_error_policy = policy;
+    }
+
+    pub fn tool_verbosity(&self) -> ToolVerbosity {
+        self.tool_verbosity
+    }
+
+    pub fn set_tool_verbosity(&mut self, verbosity: ToolVerbosity) {
+        self.tool_verbosity = verbosity;
+    }
+
+    /// Error for a blocked event-driven step: "ABORT:"-prefixed (cancelling the remaining
+    /// events) unless the policy says to skip it and continue
+    pub fn blocked_step_error(&mut self, reason: String) -> String {
+        match self.movement_error_policy {
+            MovementErrorPolicy::AbortRemaining => format!("ABORT: {}", reason),
+            MovementErrorPolicy::SkipAndContinue => {
+                self.log_info("Skipped blocked move; continuing with remaining moves");
+                format!("Skipped: {}", reason)
+            }
+        }
+    }
+
+    pub fn fog_of_war(&self) -> bool {
+        self.fog_of_war
+    }
+
+    /// Turn fog of war on or off; either way the agent starts from an unexplored map
+    pub fn set_fog_of_war(&mut self, enabled: bool) {
+        self.fog_of_war = enabled;
+        self.explored.clear();
+    }
+
+    /// Cells the agent has seen through `get_map_state` since the last reset
+    pub fn explored(&self) -> &HashSet<(usize, usize)> {
+        &self.explored
+    }
+
+    /// Forget explored cells (e.g. when the map changes)
+    pub fn clear_explored(&mut self) {
+        self.explored.clear();
+    }
+
+    /// Mark every cell within `radius` of the agent's footprint (a square, like the map
+    /// view) as seen
+    fn explore_around(&mut self, map: &GridMap, radius: usize) {
+        let (w, h) = self.size;
+        let (x0, y0) = (self.x.saturating_sub(radius), self.y.saturating_sub(radius));
+        let x1 = (self.x + w + radius).min(map.width());
+        let y1 = (self.y + h + radius).min(map.height());
+        self.explored.extend((y0..y1).flat_map(|y| (x0..x1).map(move |x| (x, y))));
+    }
+
+    /// Requested view distance, or the default, kept within the allowed range
+    fn clamped_visibility(&self, visibility: Option<usize>) -> usize {
+        visibility
+            .unwrap_or(self.default_visibility)
+            .clamp(1, self.max_visibility)
+    }
+
+    /// Forget discovered obstacles (e.g. when the map changes)
+    pub fn clear_known_obstacles(&mut self) {
+        self.known_obstacles.clear();
+    }
+
+    /// Drop the navigation target used for hints and replanning
+    pub fn clear_target(&mut self) {
+        self.current_target = None;
+    }
+
+    /// Whether walking leaves trail tiles in the map (off = trail lives only in movement history)
+    pub fn leaves_trail_tiles(&self) -> bool {
+        self.leave_trail_tiles
+    }
+
+    /// Turn trail tiles on or off; turning them off restores the terrain they covered
+    pub fn set_leave_trail_tiles(&mut self, enabled: bool, map: &mut GridMap) {
+        self.leave_trail_tiles = enabled;
+        if !enabled {
+            self.restore_trail_tiles(map);
+        }
+    }
+
+    /// Replace the tile the agent is leaving with a trail tile, if trail tiles are enabled
+    fn mark_trail(&mut self, map: &mut GridMap, x: usize, y: usize) {
+        if !self.leave_trail_tiles {
+            return;
+        }
+        match map.get(x, y).copied() {
+            // Portals and fuel keep working, and existing trail already has its original recorded
+            None | Some(TileKind::Trail) | Some(TileKind::Portal(_)) | Some(TileKind::Fuel) => {}
+            Some(original) => {
+                self.trail_tiles.entry((x, y)).or_insert(original);
+                map.set(x, y, TileKind::Trail);
+            }
+        }
+    }
+
+    /// Terrain at (x, y), looking through trail tiles to what they replaced
+    pub fn terrain_at(&self, map: &GridMap, x: usize, y: usize) -> Option<TileKind> {
+        match map.get(x, y).copied() {
+            Some(TileKind::Trail) => {
+                Some(self.trail_tiles.get(&(x, y)).copied().unwrap_or(TileKind::Trail))
+            }
+            other => other,
+        }
+    }
+
+    /// Put back the original terrain under every trail tile; returns how many were restored
+    pub fn restore_trail_tiles(&mut self, map: &mut GridMap) -> usize {
+        let mut restored = 0;
+        for ((x, y), original) in self.trail_tiles.drain() {
+            if map.get(x, y) == Some(&TileKind::Trail) {
+                map.set(x, y, original);
+                restored += 1;
+            }
+        }
+        restored
+    }
+
+    /// Forget recorded trail tiles without touching the map (e.g. the map was replaced)
+    pub fn forget_trail_tiles(&mut self) {
+        self.trail_tiles.clear();
+    }
+
+    /// Record a visited position, dropping the oldest ones beyond the history limit
+    fn push_movement_history(&mut self, pos: (usize, usize)) {
+        self.movement_history.push(pos);
+        self.trim_movement_history();
+    }
+
+    fn trim_movement_history(&mut self) {
+        let excess = self
+            .movement_history
+            .len()
+            .saturating_sub(self.movement_history_limit);
+        self.movement_history.drain(..excess);
+    }
+
+    /// Maximum number of positions kept in movement history (and drawn as trail)
+    pub fn movement_history_limit(&self) -> usize {
+        self.movement_history_limit
+    }
+
+    /// Set the movement history limit, trimming the oldest positions if it shrank
+    pub fn set_movement_history_limit(&mut self, limit: usize) {
+        self.movement_history_limit = limit.max(1); // Minimum of 1
+        self.trim_movement_history();
+    }
+
     /// Clear the agent's movement history
     pub fn clear_movement_history(&mut self) {
         self.movement_history.clear();
@@ -1100,29 +3276,20 @@ impl Agent {
         let mut blocking_dirs = Vec::new();
         let mut open_dirs = Vec::new();
 
-        for (dir, dir_name) in [
-            (Direction::Up, "north"),
-            (Direction::Down, "south"),
-            (Direction::Left, "west"),
-            (Direction::Right, "east"),
-        ] {
-            let (nx, ny) = dir.apply(
-                self.x as i32,
-                self.y as i32,
-                map.width() as i32,
-                map.height() as i32,
-            );
+        for dir in self.directions(map) {
+            let dir_name = dir.compass();
+            let (nx, ny) = dir.apply(map.topology(), self.x as i32, self.y as i32);
 
-            if nx < 0 || ny < 0 || nx >= map.width() as i32 || ny >= map.height() as i32 {
-                blocking_dirs.push(format!("{} (map edge)", dir_name));
-            } else if !map.is_traversable(nx as usize, ny as usize) {
-                let tile = map
-                    .get(nx as usize, ny as usize)
-                    .map(|t| t.name())
-                    .unwrap_or("unknown");
-                blocking_dirs.push(format!("{} ({})", dir_name, tile));
+            let blocked = if nx < 0 || ny < 0 {
+                Some(None)
             } else {
-                open_dirs.push(dir_name);
+                self.blocked_step_cell(map, self.pos(), dir, nx as usize, ny as usize)
+                    .map(|(bx, by)| self.terrain_at(map, bx, by))
+            };
+            match blocked {
+                Some(None) => blocking_dirs.push(format!("{} (map edge)", dir_name)),
+                Some(Some(tile)) => blocking_dirs.push(format!("{} ({})", dir_name, tile.name())),
+                None => open_dirs.push(dir_name),
             }
         }
 
@@ -1147,98 +3314,301 @@ impl Agent {
         Some(hint)
     }
 
-    /// Process one movement step (call this from update loop)
-    pub fn process_movement_step(&mut self, map: &mut GridMap) -> MovementStatus {
-        if !self.movement_active {
-            return MovementStatus::Completed;
+    /// Moves that walk a pathfinder route, where a step onto a portal may land on its exit
+    pub fn directions_along(map: &GridMap, path: &[(usize, usize)]) -> Option<Vec<Direction>> {
+        path.windows(2)
+            .map(|pair| {
+                let (from, to) = (pair[0], pair[1]);
+                Direction::available(map.topology(), true)
+                    .iter()
+                    .find(|dir| {
+                        let (nx, ny) = dir.apply(map.topology(), from.0 as i32, from.1 as i32);
+                        if nx < 0 || ny < 0 {
+                            return false;
+                        }
+                        let entered = (nx as usize, ny as usize);
+                        entered == to || map.portal_exit(entered.0, entered.1) == Some(to)
+                    })
+                    .cloned()
+            })
+            .collect()
+    }
+
+    /// Best route from the agent to `target`, remembering how ties between equally
+    /// good routes were broken for `last_path_explanation`
+    ///
+    /// Fewest moves (BFS) while every step costs the same; otherwise the cheapest route
+    /// under the agent's tile costs and the map's climbs (Dijkstra).
+    fn plan_route(&mut self, map: &GridMap, target: (usize, usize)) -> Option<Vec<(usize, usize)>> {
+        let algorithm = if self.tile_costs.is_uniform() && !map.has_elevation() {
+            SearchAlgorithm::Bfs
+        } else {
+            SearchAlgorithm::Dijkstra
+        };
+        let trace = trace_search(
+            map,
+            self.pos(),
+            target,
+            algorithm,
+            self.directions(map),
+            |x, y| self.can_enter(map, x, y) && self.footprint_explored(x, y),
+            |x, y| self.movement_cost(map, x, y),
+        );
+        self.last_path_explanation = Some(trace.explanation());
+        trace.path
+    }
+
+    /// Why the last route the agent planned was picked over equally short ones
+    pub fn last_path_explanation(&self) -> Option<&str> {
+        self.last_path_explanation.as_deref()
+    }
+
+    /// Fresh route to `current_target` to replace a movement sequence after a blocked step
+    ///
+    /// `remaining` is how many moves the sequence had left, the blocked one included; the
+    /// new route is cut to that length so a sequence never grows. None when there's no
+    /// target, the replan budget is spent or no route exists.
+    pub fn replan_remaining_moves(
+        &mut self,
+        map: &GridMap,
+        remaining: usize,
+    ) -> Option<Vec<Direction>> {
+        let target = self.current_target?;
+        if self.replans_this_sequence >= MAX_REPLANS_PER_SEQUENCE {
+            self.log_info(format!(
+                "Not replanning: already replanned {} times this sequence",
+                MAX_REPLANS_PER_SEQUENCE
+            ));
+            return None;
         }
+        let Some(path) = self.plan_route(map, target) else {
+            self.log_info(format!(
+                "Not replanning: no route to target ({}, {})",
+                target.0, target.1
+            ));
+            return None;
+        };
 
-        let now = Instant::now();
-        if let Some(next_at) = self.next_step_at {
-            if now < next_at {
-                return MovementStatus::StepSuccess; // Still waiting
-            }
+        let mut directions = Self::directions_along(map, &path)?;
+        if directions.is_empty() {
+            return None;
         }
+        directions.truncate(remaining);
 
-        if let Some(dir) = self.pending_moves.first().cloned() {
-            let current_step = self.movement_step_index + 1;
-            let (x, y) = (self.x as i32, self.y as i32);
-            let w = map.width() as i32;
-            let h = map.height() as i32;
+        self.replans_this_sequence += 1;
+        let names: Vec<&str> = directions.iter().map(|d| d.as_str()).collect();
+        self.log_info(format!(
+            "Replanned towards target ({}, {}) ({}/{}): {}",
+            target.0,
+            target.1,
+            self.replans_this_sequence,
+            MAX_REPLANS_PER_SEQUENCE,
+            names.join(", ")
+        ));
+        Some(directions)
+    }
+}
 
-            let (nx, ny) = dir.apply(x, y, w, h);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            // Check bounds
-            if nx < 0 || ny < 0 || nx >= w || ny >= h {
-                self.log(LogEntry::Error(format!(
-                    "Movement aborted on step {} of {}: edge of map",
-                    current_step, self.total_movement_steps
-                )));
-                self.movement_active = false;
-                self.pending_moves.clear();
-                self.next_step_at = None;
-                return MovementStatus::OutOfBounds;
-            }
+    fn open_map(topology: Topology) -> GridMap {
+        let mut map = GridMap::new(4, 4, TileKind::Empty);
+        map.set_topology(topology);
+        map
+    }
 
-            // Check traversability
-            if !map.is_traversable(nx as usize, ny as usize) {
-                let tile_type = map
-                    .get(nx as usize, ny as usize)
-                    .map(|t| t.name())
-                    .unwrap_or("unknown");
-                self.log(LogEntry::Error(format!(
-                    "Movement aborted on step {} of {}: blocked by {} tile at ({}, {})\n\
-                    Agent position: ({}, {})\n\
-                    Attempted move: {} to ({}, {})\n\
-                    Traversable tiles: empty, grass, sand, trail\n\
-                    Blocking tiles: wall, water, tree",
-                    current_step,
-                    self.total_movement_steps,
-                    tile_type,
-                    nx,
-                    ny,
-                    self.x,
-                    self.y,
-                    dir.as_str(),
-                    nx,
-                    ny
-                )));
-                self.movement_active = false;
-                self.pending_moves.clear();
-                self.next_step_at = None;
-                return MovementStatus::BlockedByTerrain;
+    #[test]
+    fn steps_off_the_right_and_bottom_edges_leave_the_map() {
+        assert_eq!(Direction::Right.apply(Topology::Square, 3, 1), (4, 1));
+        assert_eq!(Direction::Down.apply(Topology::Square, 1, 3), (1, 4));
+        assert_eq!(Direction::DownRight.apply(Topology::Square, 3, 1), (4, 2));
+        assert_eq!(Direction::UpRight.apply(Topology::Hex, 3, 1), (4, 0));
+        assert_eq!(Direction::DownLeft.apply(Topology::Hex, 0, 3), (0, 4));
+
+        for topology in [Topology::Square, Topology::Hex] {
+            let map = open_map(topology);
+            let agent = Agent::new(1, "a", 3, 3);
+            for direction in [Direction::Right, Direction::Down] {
+                let blocked = agent.first_blocked_step(&map, &[direction]);
+                assert_eq!(blocked, Some((0, "the edge of the map".to_string())));
             }
+        }
+        let agent = Agent::new(1, "a", 3, 1);
+        let blocked = agent.first_blocked_step(&open_map(Topology::Hex), &[Direction::UpRight]);
+        assert_eq!(blocked, Some((0, "the edge of the map".to_string())));
+    }
 
-            // Record current position in movement history
-            self.movement_history.push((self.x, self.y));
+    #[test]
+    fn diagonals_off_the_right_and_bottom_edges_are_blocked() {
+        let mut map = open_map(Topology::Square);
+        map.set(3, 2, TileKind::Sand);
+        let mut agent = Agent::new(1, "a", 3, 1);
+        agent.set_allow_diagonals(true);
+
+        let result = agent.handle_get_available_directions_tool(&map).unwrap();
+        let available: Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(
+            available["available_directions"],
+            json!(["up", "down", "left", "up_left", "down_left"])
+        );
+        assert_eq!(
+            agent.step_costs(&map, &[Direction::DownRight, Direction::UpRight]),
+            vec![TileCosts::DEFAULT_COST; 2]
+        );
+
+        agent.set_pos(1, 3);
+        let bearings: Value =
+            serde_json::from_str(&agent.handle_get_bearings_tool(json!({}), &map).unwrap())
+                .unwrap();
+        let off_map: Vec<&str> = bearings["blocking_directions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|blocked| blocked["reason"] == "map_edge")
+            .filter_map(|blocked| blocked["direction"].as_str())
+            .collect();
+        assert_eq!(off_map, ["south", "south-west", "south-east"]);
+    }
 
-            // Move agent
-            self.set_pos(nx as usize, ny as usize);
-            self.log(LogEntry::Movement {
-                direction: dir.as_str().to_string(),
-                position: (self.x, self.y),
-            });
+    #[test]
+    fn diagonal_routes_are_planned_and_measured_with_diagonals() {
+        let mut map = open_map(Topology::Square);
+        let mut agent = Agent::new(1, "a", 0, 0);
+        agent.set_allow_diagonals(true);
+        agent.run_stats = RunStats {
+            started_at: Some(Instant::now()),
+            path: vec![agent.pos()],
+            ..RunStats::default()
+        };
+        for _ in 0..3 {
+            agent.execute_move_step(Direction::DownRight, &mut map).unwrap();
+        }
+        let route = agent.compare_route(&map).unwrap();
+        assert_eq!(route.summary(), "route efficiency 100% (3 optimal vs 3 moves)");
 
-            // Remove completed step
-            self.pending_moves.remove(0);
-            self.movement_step_index += 1;
-
-            // Check if done
-            if self.pending_moves.is_empty() {
-                self.movement_active = false;
-                self.next_step_at = None;
-                self.total_movement_steps = 0;
-                self.movement_step_index = 0;
-                self.log_info("Movement completed");
-                return MovementStatus::Completed;
-            } else {
-                self.next_step_at = Some(Instant::now() + Duration::from_secs(1));
-                return MovementStatus::StepSuccess;
-            }
-        } else {
-            self.movement_active = false;
-            self.next_step_at = None;
-            return MovementStatus::Completed;
+        agent.set_pos(0, 0);
+        let result = agent.handle_explain_route_tool(json!({"target": {"x": 3, "y": 3}}), &map);
+        let planned: Value = serde_json::from_str(&result.unwrap()).unwrap();
+        assert_eq!(planned["directions"], json!(["down_right", "down_right", "down_right"]));
+    }
+
+    #[test]
+    fn planned_routes_never_cut_a_corner() {
+        let mut map = open_map(Topology::Square);
+        map.set(1, 0, TileKind::Wall);
+        let mut agent = Agent::new(1, "a", 0, 0);
+        agent.set_allow_diagonals(true);
+
+        let (moves, passable) = (agent.directions(&map), |x, y| agent.can_enter(&map, x, y));
+        let path = shortest_path(&map, (0, 0), (1, 1), moves, passable);
+        assert_eq!(path, Some(vec![(0, 0), (0, 1), (1, 1)]));
+        let cheapest = cheapest_path(&map, (0, 0), (1, 1), moves, passable, |_, _| 1);
+        assert_eq!(cheapest.map(|(path, _)| path.len() - 1), Some(2));
+        assert!(agent.execute_move_step(Direction::DownRight, &mut map).is_err());
+        assert_eq!(agent.pos(), (0, 0));
+    }
+
+    #[test]
+    fn planned_path_stays_on_a_portal_with_a_blocked_exit() {
+        let mut map = GridMap::new(5, 3, TileKind::Empty);
+        map.set(1, 0, TileKind::Portal(1));
+        map.set(4, 2, TileKind::Portal(1));
+        let mut agent = Agent::new(1, "a", 0, 0);
+        assert_eq!(agent.planned_path(&map, &[Direction::Right]), [(0, 0), (1, 0), (4, 2)]);
+
+        agent.set_dynamic_obstacles([(4, 2)]);
+        assert_eq!(agent.planned_path(&map, &[Direction::Right]), [(0, 0), (1, 0)]);
+        agent.execute_move_step(Direction::Right, &mut map).unwrap();
+        assert_eq!(agent.pos(), (1, 0));
+    }
+
+    #[test]
+    fn trail_cells_report_the_terrain_underneath() {
+        let mut map = GridMap::new(3, 3, TileKind::Sand);
+        map.set(2, 1, TileKind::Trail);
+        let mut agent = Agent::new(1, "a", 0, 1);
+        agent.set_leave_trail_tiles(true, &mut map);
+        agent.execute_move_step(Direction::Right, &mut map).unwrap();
+        assert_eq!(map.get(0, 1), Some(&TileKind::Trail));
+
+        let probe = agent.handle_probe_neighborhood_tool(json!({"size": 3}), &map).unwrap();
+        let probe: Value = serde_json::from_str(&probe).unwrap();
+        // The walked cell shows what it was; a trail tile with no recorded terrain is "trail"
+        assert_eq!(probe["grid"][1], json!(["sand", "agent", "trail"]));
+        assert_eq!(agent.blocker_name(&map, 0, 1), "sand");
+    }
+
+    #[test]
+    fn fog_of_war_routes_only_through_explored_cells() {
+        let map = open_map(Topology::Square);
+        let mut agent = Agent::new(1, "a", 0, 0);
+        agent.set_fog_of_war(true);
+        agent.explore_around(&map, 1);
+
+        let target = json!({"target": {"x": 3, "y": 3}});
+        let error = agent.handle_explain_route_tool(target.clone(), &map).unwrap_err();
+        assert!(error.contains("through explored tiles"), "{}", error);
+        assert!(agent.handle_explain_route_tool(json!({"target": {"x": 1, "y": 1}}), &map).is_ok());
+        let into_the_fog = [Direction::Right, Direction::Right, Direction::Right];
+        assert_eq!(agent.first_blocked_step(&map, &into_the_fog), None);
+
+        agent.set_fog_of_war(false);
+        assert!(agent.handle_explain_route_tool(target, &map).is_ok());
+    }
+
+    #[test]
+    fn exploring_reveals_around_the_whole_footprint() {
+        let map = GridMap::new(6, 6, TileKind::Empty);
+        let mut agent = Agent::new(1, "a", 1, 1);
+        agent.set_size(2, 2);
+        agent.set_fog_of_war(true);
+        agent.explore_around(&map, 1);
+
+        assert!(agent.explored().contains(&(0, 0)));
+        assert!(agent.explored().contains(&(3, 3)));
+        assert!(!agent.explored().contains(&(4, 3)));
+        assert!(!agent.explored().contains(&(3, 4)));
+    }
+
+    #[test]
+    fn redact_secret_scrubs_every_string_that_holds_the_key() {
+        let mut value = json!({
+            "messages": [{"content": "my key is sk-123, keep it"}, {"content": "hello"}],
+            "nested": {"note": ["sk-123", 7]},
+        });
+        redact_secret(&mut value, "sk-123");
+        assert_eq!(
+            value,
+            json!({
+                "messages": [{"content": "my key is [redacted], keep it"}, {"content": "hello"}],
+                "nested": {"note": ["[redacted]", 7]},
+            })
+        );
+
+        // No key means nothing to hide, not every string matching ""
+        let mut value = json!({"content": "hello"});
+        redact_secret(&mut value, "");
+        assert_eq!(value, json!({"content": "hello"}));
+    }
+
+    #[test]
+    fn decision_records_redact_the_key_and_keep_the_newest_turns() {
+        let mut agent = Agent::new(1, "a", 0, 0);
+        let body = json!({"messages": [{"role": "user", "content": "use sk-123"}]});
+        agent.begin_decision_record(&body, "sk-123");
+        let record = &agent.decision_log()[0];
+        assert_eq!(record.turn, 1);
+        assert_eq!(record.request["body"]["messages"][0]["content"], "use [redacted]");
+        assert!(!agent.decision_log_json().contains("sk-123"));
+
+        for _ in 0..MAX_DECISION_RECORDS {
+            agent.begin_decision_record(&body, "sk-123");
         }
+        let log = agent.decision_log();
+        assert_eq!(log.len(), MAX_DECISION_RECORDS);
+        assert_eq!(log[0].turn, 2);
+        assert_eq!(log.last().unwrap().turn, MAX_DECISION_RECORDS + 1);
     }
 }
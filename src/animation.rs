@@ -1,9 +1,55 @@
+use serde::{Deserialize, Serialize};
 use web_time::{Duration, Instant};
 
+/// How often to repaint when the app is otherwise idle, so looping animations (water) move
+///
+/// egui only repaints on input or when asked via `request_repaint_after`. Activity
+/// (LLM streaming, queued moves, teach playback) already requests fast repaints; this
+/// setting only covers the idle case, and no request is made at all when nothing on
+/// screen animates, so an idle tab costs next to no CPU.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IdleRepaint {
+    /// Never repaint while idle; animations freeze until the next input
+    Off,
+    /// Twice a second
+    Low,
+    /// Every animation frame
+    #[default]
+    Smooth,
+}
+
+impl IdleRepaint {
+    pub const ALL: [IdleRepaint; 3] = [Self::Off, Self::Low, Self::Smooth];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Off => "Off",
+            Self::Low => "Low",
+            Self::Smooth => "Smooth",
+        }
+    }
+
+    /// Delay before the next idle repaint, None when idle repaints are off
+    pub fn interval(&self) -> Option<Duration> {
+        match self {
+            Self::Off => None,
+            Self::Low => Some(Duration::from_millis(500)),
+            Self::Smooth => Some(FRAME_INTERVAL),
+        }
+    }
+}
+
+/// Time between animation frames
+const FRAME_INTERVAL: Duration = Duration::from_millis(150);
+
+/// How long the "find agent" highlight pulses for
+const PULSE_DURATION: Duration = Duration::from_millis(1500);
+
 /// Manages animation state for UI elements
 pub struct AnimationController {
     animation_frame: u64,
     last_animation_update: Instant,
+    pulse_started: Option<Instant>,
 }
 
 impl AnimationController {
@@ -11,6 +57,7 @@ impl AnimationController {
         Self {
             animation_frame: 0,
             last_animation_update: Instant::now(),
+            pulse_started: None,
         }
     }
 
@@ -18,12 +65,28 @@ impl AnimationController {
     pub fn update(&mut self) {
         let now = Instant::now();
         // Update animation frame every 150ms
-        if now.duration_since(self.last_animation_update) >= Duration::from_millis(150) {
+        if now.duration_since(self.last_animation_update) >= FRAME_INTERVAL {
             self.animation_frame = self.animation_frame.wrapping_add(1);
             self.last_animation_update = now;
         }
     }
 
+    /// Current animation frame (advances every 150ms)
+    pub fn frame(&self) -> u64 {
+        self.animation_frame
+    }
+
+    /// Start (or restart) the time-bounded highlight pulse
+    pub fn start_pulse(&mut self) {
+        self.pulse_started = Some(Instant::now());
+    }
+
+    /// How far through the pulse we are, from 0 to 1; None when no pulse is running
+    pub fn pulse_progress(&self) -> Option<f32> {
+        let elapsed = self.pulse_started?.elapsed();
+        (elapsed < PULSE_DURATION).then(|| elapsed.as_secs_f32() / PULSE_DURATION.as_secs_f32())
+    }
+
     /// Generate animated "Thinking..." text with elaborate effects
     pub fn get_thinking_text(&self) -> String {
         let frame = self.animation_frame;
@@ -1,20 +1,48 @@
-use crate::agent::{Agent, LogEntry};
+use crate::agent::{
+    Agent, AgentSprite, ErrorKind, LogEntry, MovementErrorPolicy, DEFAULT_ENERGY_CAPACITY,
+    ToolVerbosity, MAX_VISIBILITY_LIMIT, REPLAN_PREFIX,
+};
 use crate::animation::AnimationController;
-use crate::board::{BoardInput, BoardRenderer};
-use crate::editor::{EditorState, EditorUI};
+use crate::clock::{RealClock, SharedClock};
+use crate::board::{
+    BoardImage, BoardInput, BoardRenderer, RenderOptions, SearchOverlay, Viewport,
+};
+use crate::editor::{EditorOperations, EditorState, EditorUI};
 use crate::events::EventQueue;
-use crate::map::{GridMap, TileKind};
-use crate::map_type::MapType;
+use crate::keyboard::{self, Shortcut};
+use crate::map::{GridMap, TileCosts, TileKind, Topology, TraversabilityRules};
+use crate::map_type::{MapTemplate, MapType, TemplateParams};
+use crate::model_presets::ModelPreset;
+use crate::pathfinding::check_solvable;
 use crate::rendering::*;
 use crate::tool_execution::ToolExecutionManager;
-use crate::ui::{AgentPanel, TileInfoPanel};
+use crate::patrol::Patroller;
+use crate::replay::Replay;
+use crate::settings::Settings;
+use crate::stress_test::StressTest;
+use crate::teach::TeachMode;
+use crate::ui::{
+    AgentPanel, Command, CommandPalette, Compass, DecisionLogPanel, EventInspector, Minimap,
+    ModelPicker, PatrolPanel, ReplayPanel, RunHistoryPanel, SettingsWindow, ShortcutHelp,
+    StressTestPanel, TeachPanel, TemplatePanel, TileInfoPanel, TileLegend, ToolApprovalCard,
+};
 use eframe::egui;
+use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
 use web_time::{Duration, Instant};
 
 // Game tick rate: 500ms per tick (2 ticks per second)
 const TICK_RATE: Duration = Duration::from_millis(500);
 
+// Stress-test trials run per frame while a stress test is in progress
+const STRESS_TRIALS_PER_FRAME: usize = 5;
+
+// Side length of the minimap next to the board
+const MINIMAP_SIDE: f32 = 140.0;
+
+// Cell size in pixels for board screenshots
+const SCREENSHOT_CELL_PX: f32 = 32.0;
+
 pub struct MyApp {
     // Map + rendering state
     board_dim: usize,
@@ -23,13 +51,45 @@ pub struct MyApp {
     map: GridMap,
     current_map_type: MapType,
     pending_map_change: Option<MapType>, // Defer map changes to avoid blocking UI
+    current_template: Option<MapTemplate>, // Generated template in use instead of a bundled map
+    template_params: TemplateParams,
     tree_tex: Option<egui::TextureHandle>,
+    agent_tex: Option<egui::TextureHandle>,
+    viewport: Viewport,
+    /// Keep the view centered on the agent; turned off by manual panning
+    follow_agent: bool,
+    /// Panning one-off to the agent after "Find agent", until the view arrives
+    finding_agent: bool,
 
     // Agent state (single agent)
     agent: Agent,
     agent_selected: bool,
     agent_instruction: String,
+    instruction_queue: VecDeque<String>, // Run one after another, each once the agent is idle
     selected_model: String,
+    model_picker: ModelPicker,
+    request_preview: Option<String>, // Pretty-printed request body shown by "Preview Request"
+    show_shortcut_help: bool,
+    command_palette: CommandPalette,
+
+    // App-wide preferences, saved to localStorage whenever the Settings window changes them
+    settings: Settings,
+    show_settings: bool,
+    settings_import_text: String, // Exported settings JSON pasted in for import
+    share_include_model: bool,
+    share_include_map: bool,
+
+    // Scripted moving obstacles, and the route being recorded for a new one
+    patrollers: Vec<Patroller>,
+    recording_patrol: Option<Vec<(usize, usize)>>,
+
+    // Session recording and the step being scrubbed to
+    replay: Replay,
+
+    // Automated navigation stress test; trial count and seed for the next run
+    stress_test: Option<StressTest>,
+    stress_trials: usize,
+    stress_seed: u64,
 
     // Log callback queue from async operations
     log_callbacks: Arc<Mutex<Vec<(u32, LogEntry)>>>,
@@ -50,6 +110,7 @@ pub struct MyApp {
 
     // Tick timing
     last_tick: Instant,
+    clock: SharedClock,
     accumulated_time: Duration,
 
     // OpenRouter API key
@@ -58,6 +119,9 @@ pub struct MyApp {
     // Animation controller
     animation_controller: AnimationController,
 
+    // Pathfinder search playback
+    teach_mode: TeachMode,
+
     // Map editor state
     editor_state: EditorState,
 }
@@ -91,15 +155,36 @@ impl MyApp {
             tree_image,
             egui::TextureOptions::LINEAR,
         ));
+        let agent_tex = Some(cc.egui_ctx.load_texture(
+            "agent_sprite",
+            generate_agent_sprite(48),
+            egui::TextureOptions::LINEAR,
+        ));
 
-        let initial_map = MapType::LakeTrees.create_map(24, 24).unwrap_or_else(|e| {
+        let settings = Settings::load().unwrap_or_default();
+        let mut initial_map = MapType::LakeTrees.create_map(24, 24).unwrap_or_else(|e| {
             eprintln!("Failed to load initial map: {}", e);
             GridMap::new(24, 24, TileKind::Grass)
         });
+        initial_map.set_topology(settings.topology);
 
         let mut editor_state = EditorState::new(24, 24);
         editor_state.initialize_from_map(&initial_map);
 
+        let selected_model = "x-ai/grok-4-fast".to_string();
+        // One clock drives the tick loop, the event queue and agent movement
+        let clock = RealClock::shared();
+        let mut agent = Agent::new(1, "Agent-1", 6, 10);
+        agent.set_clock(clock.clone());
+        agent.place_at_map_spawn(&initial_map);
+        agent.set_goal(initial_map.goal_point());
+        if let Err(e) = check_solvable(&initial_map, |x, y| agent.can_enter(&initial_map, x, y)) {
+            agent.log_info(format!("WARN: This map may be impossible: {}", e));
+        }
+        if let Some(preset) = ModelPreset::for_model(&selected_model) {
+            agent.apply_model_preset(preset);
+        }
+
         Self {
             board_dim: 24,
             selected_cell: None,
@@ -107,19 +192,43 @@ impl MyApp {
             map: initial_map,
             current_map_type: MapType::LakeTrees,
             pending_map_change: None,
+            current_template: None,
+            template_params: TemplateParams::default(),
             tree_tex,
-            agent: Agent::new(1, "Agent-1", 6, 10),
+            agent_tex,
+            viewport: Viewport::default(),
+            follow_agent: false,
+            finding_agent: false,
+            agent,
             agent_selected: false,
             agent_instruction: String::new(),
-            selected_model: "x-ai/grok-4-fast".to_string(),
+            instruction_queue: VecDeque::new(),
+            selected_model,
+            model_picker: ModelPicker::new(),
+            request_preview: None,
+            show_shortcut_help: false,
+            command_palette: CommandPalette::new(),
+            settings,
+            show_settings: false,
+            settings_import_text: String::new(),
+            share_include_model: true,
+            share_include_map: false,
+            patrollers: Vec::new(),
+            recording_patrol: None,
+            replay: Replay::default(),
+            stress_test: None,
+            stress_trials: 100,
+            stress_seed: 1,
             log_callbacks: Arc::new(Mutex::new(Vec::new())),
-            event_queue: EventQueue::new(),
+            event_queue: EventQueue::with_clock(clock.clone()),
             tool_execution_manager: ToolExecutionManager::new(TICK_RATE),
             agent_running: false,
             should_continue_execution: false,
             llm_active: false,
             llm_status_callback: Arc::new(Mutex::new(false)),
-            last_tick: Instant::now(),
+            last_tick: clock.now(),
+            teach_mode: TeachMode::new(clock.now()),
+            clock,
             accumulated_time: Duration::from_secs(0),
             openrouter_api_key: api_key,
             animation_controller: AnimationController::new(),
@@ -136,33 +245,35 @@ impl MyApp {
             // Extract agent_id before consuming event
             let agent_id_for_cancel = match &scheduled_event.event {
                 Event::AgentMove { agent_id, .. } => Some(*agent_id),
+                Event::Custom(custom) => custom.agent_id,
                 _ => None,
             };
 
-            let result = match scheduled_event.event {
-                Event::AgentMove {
-                    agent_id,
-                    direction,
-                } => {
-                    if agent_id == self.agent.id {
-                        let result = self.agent.execute_move_step(direction, &mut self.map);
-
-                        // Update selected tile to follow agent if movement succeeded
-                        if result.is_ok() {
-                            self.selected_tile = Some(self.agent.pos());
-                        }
-
-                        result
-                    } else {
-                        Err(format!("Unknown agent id: {}", agent_id))
-                    }
-                }
-                Event::Delay { .. } => {
-                    // Delay events just complete successfully
-                    Ok(())
+            let is_move = matches!(scheduled_event.event, Event::AgentMove { .. });
+            self.replay.record(
+                &scheduled_event.event,
+                &self.map,
+                self.agent.dynamic_obstacles(),
+            );
+            let result = match scheduled_event.event.apply(&mut self.agent, &mut self.map) {
+                // A blocked step with a target gets a new route, or else the error policy
+                Err(error) if error.starts_with(REPLAN_PREFIX) => {
+                    self.tool_execution_manager.replan_blocked_moves(
+                        &mut self.agent,
+                        &self.map,
+                        &self.event_queue,
+                        scheduled_event.id,
+                        &error,
+                    )
                 }
+                result => result,
             };
 
+            // Update selected tile to follow agent if movement succeeded
+            if is_move && result.is_ok() {
+                self.selected_tile = Some(self.agent.pos());
+            }
+
             // Check if we should abort and cancel remaining events
             if let Err(ref err_msg) = result {
                 if err_msg.starts_with("ABORT:") {
@@ -178,11 +289,233 @@ impl MyApp {
 
             self.event_queue.complete(scheduled_event.id, result);
         }
+
+        self.advance_patrollers();
+        self.replay.tick();
+    }
+
+    /// Abandon an LLM stream that has gone silent for longer than the LLM timeout and stop
+    /// the run, so a hung connection doesn't leave the agent waiting forever
+    fn check_llm_timeout(&mut self) {
+        if !*self.llm_status_callback.lock().unwrap() {
+            return;
+        }
+        let idle = self.agent.stream_idle_for();
+        if idle < self.settings.llm_timeout {
+            return;
+        }
+
+        self.agent.abort_stream();
+        if let Ok(mut status) = self.llm_status_callback.lock() {
+            *status = false;
+        }
+        self.agent.log_error(
+            ErrorKind::Network,
+            format!("LLM response timed out after {}s without new data", idle.as_secs()),
+        );
+        if self.agent_running {
+            self.agent_running = false;
+            self.should_continue_execution = false;
+            self.agent.finish_run(&self.map);
+        }
+    }
+
+    /// Step every patroller along its route; they wait rather than walk into the agent
+    /// or each other
+    fn advance_patrollers(&mut self) {
+        if self.patrollers.is_empty() {
+            return;
+        }
+        for i in 0..self.patrollers.len() {
+            let occupied: Vec<(usize, usize)> = self
+                .patrollers
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(_, p)| p.pos())
+                .collect();
+            let agent = &self.agent;
+            self.patrollers[i].advance(|x, y| agent.covers(x, y) || occupied.contains(&(x, y)));
+        }
+        self.sync_patrol_obstacles();
+    }
+
+    /// Tell the agent which cells patrollers currently block
+    fn sync_patrol_obstacles(&mut self) {
+        self.agent
+            .set_dynamic_obstacles(self.patrollers.iter().map(Patroller::pos));
+    }
+
+    /// Move the agent from the keyboard when it isn't being driven by the LLM
+    /// Callers must skip this while a text field has focus (see `keyboard::text_input_focused`)
+    fn handle_manual_movement(&mut self, ctx: &egui::Context) {
+        if self.editor_state.edit_mode || self.agent_running
+            || AgentPanel::is_processing(&self.event_queue, &self.tool_execution_manager)
+        {
+            return;
+        }
+
+        if let Some(direction) = ctx.input(|i| self.settings.movement_keys.pressed_direction(i))
+            && self.agent.directions(&self.map).contains(&direction)
+        {
+            self.event_queue.submit_immediate(crate::events::Event::AgentMove {
+                agent_id: self.agent.id,
+                direction,
+            });
+        }
+    }
+
+    /// Every action offered by the command palette; add new entries here
+    fn palette_commands(&self) -> Vec<Command<MyApp>> {
+        let is_busy = self.agent_running
+            || AgentPanel::is_processing(&self.event_queue, &self.tool_execution_manager);
+        let mut commands: Vec<Command<MyApp>> = MapType::all()
+            .into_iter()
+            .map(|map_type| {
+                let label = format!("Load map: {}", map_type.selector_label());
+                Command::new(label, move |app: &mut MyApp| {
+                    app.pending_map_change = Some(map_type);
+                })
+            })
+            .collect();
+        commands.extend(MapTemplate::ALL.into_iter().map(|template| {
+            Command::new(format!("Generate map: {}", template.label()), move |app: &mut MyApp| {
+                app.current_template = Some(template);
+                app.generate_template_map();
+            })
+        }));
+        commands.extend([
+            Command::new(
+                format!("Run pathfinder ({})", self.teach_mode.algorithm.name()),
+                |app: &mut MyApp| {
+                    if let Some(goal) = app.agent.goal() {
+                        app.teach_mode.start(&app.map, &app.agent, goal, app.clock.now());
+                    }
+                },
+            )
+            .enabled(self.agent.goal().is_some()),
+            Command::new("Toggle edit mode", |app: &mut MyApp| {
+                app.editor_state.edit_mode = !app.editor_state.edit_mode;
+            }),
+            Command::new("Clear log", |app: &mut MyApp| app.agent.clear_logs()),
+            Command::new("Reset agent to spawn", |app: &mut MyApp| {
+                app.agent.reset_to_spawn(&mut app.map);
+                app.selected_tile = Some(app.agent.pos());
+            })
+            .enabled(!is_busy),
+            Command::new("Find agent", |app: &mut MyApp| app.find_agent()),
+            Command::new("Open settings", |app: &mut MyApp| app.show_settings = true),
+            Command::new("Show keyboard shortcuts", |app: &mut MyApp| {
+                app.show_shortcut_help = true;
+            }),
+        ]);
+        commands
+    }
+
+    /// Show the command palette while it is open and run whatever gets picked
+    fn draw_command_palette(&mut self, ctx: &egui::Context) {
+        if !self.command_palette.is_open() {
+            return;
+        }
+        let mut commands = self.palette_commands();
+        if let Some(index) = self.command_palette.draw(ctx, &commands) {
+            let command = commands.swap_remove(index);
+            (command.run)(self);
+        }
+    }
+
+    /// Swap in a new map and reset everything tied to the old one
+    fn replace_map(&mut self, new_map: GridMap) {
+        self.map = new_map;
+        self.map.set_topology(self.settings.topology);
+        // Update board dimensions to match the loaded map
+        self.board_dim = self.map.width().max(self.map.height());
+        // Initialize editor state with new map metadata
+        self.editor_state.initialize_from_map(&self.map);
+        // Update editor state's target dimensions to match new map
+        self.editor_state.set_target_dimensions(self.map.width(), self.map.height());
+        // The remembered placement and edit history belong to the old map
+        self.editor_state.previous_agent_placement = None;
+        self.editor_state.clear_undo_history();
+        // Clear selection when changing maps to prevent hover issues
+        self.selected_cell = None;
+        self.selected_tile = None;
+        // The old goal may not exist (or make sense) on the new map; use the map's own
+        self.agent.set_goal(self.map.goal_point());
+        // Clear agent trail when changing maps
+        self.agent.clear_movement_history();
+        self.agent.forget_trail_tiles();
+        // Patrol routes belong to the old layout
+        self.patrollers.clear();
+        // A recording can't carry on across maps, though it can still be viewed
+        self.replay.stop();
+        self.recording_patrol = None;
+        self.sync_patrol_obstacles();
+        self.agent.place_at_map_spawn(&self.map);
+        if let Err(e) = check_solvable(&self.map, |x, y| self.agent.can_enter(&self.map, x, y)) {
+            self.agent.log_info(format!("WARN: This map may be impossible: {}", e));
+        }
+        self.agent.clear_known_obstacles();
+        self.agent.clear_explored();
+        self.teach_mode.clear();
+        self.agent.log(LogEntry::Info(
+            "Map changed - agent trail and known obstacles cleared".to_string(),
+        ));
+    }
+
+    /// Whether anything on screen keeps animating when the app is idle (currently water)
+    fn has_idle_animation(&self) -> bool {
+        self.map.tiles_flat().contains(&TileKind::Water)
+    }
+
+    /// Load `.json` maps dropped onto the window; the last valid file wins
+    fn handle_dropped_files(&mut self, ctx: &egui::Context) {
+        let dropped = ctx.input(|i| i.raw.dropped_files.clone());
+        for file in dropped {
+            let name = file
+                .path
+                .as_ref()
+                .and_then(|p| p.file_name())
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| file.name.clone());
+            if !name.to_lowercase().ends_with(".json") {
+                self.agent.log_error(ErrorKind::Validation, format!(
+                    "Ignored dropped file '{}': only .json maps can be loaded",
+                    name
+                ));
+                continue;
+            }
+
+            // Browsers hand over the bytes; native builds only give a path
+            let bytes = match (&file.bytes, &file.path) {
+                (Some(bytes), _) => Ok(bytes.to_vec()),
+                (None, Some(path)) => std::fs::read(path).map_err(|e| e.to_string()),
+                (None, None) => Err("no file contents available".to_string()),
+            };
+            let result = bytes
+                .and_then(|bytes| String::from_utf8(bytes).map_err(|e| e.to_string()))
+                .and_then(|text| GridMap::from_json(&text));
+            match result {
+                Ok(map) => {
+                    self.replace_map(map);
+                    self.agent.log_info(format!(
+                        "Loaded map '{}' ({}x{})",
+                        name,
+                        self.map.width(),
+                        self.map.height()
+                    ));
+                }
+                Err(e) => self.agent.log_error(
+                    ErrorKind::Validation,
+                    format!("Failed to load '{}': {}", name, e),
+                ),
+            }
+        }
     }
 
     /// Process accumulated time and run ticks
     fn process_ticks(&mut self) {
-        let now = Instant::now();
+        let now = self.clock.now();
         let delta = now.duration_since(self.last_tick);
         self.accumulated_time += delta;
         self.last_tick = now;
@@ -196,28 +529,12 @@ impl MyApp {
         // Process pending map change (deferred to avoid blocking UI)
         if let Some(new_map_type) = self.pending_map_change.take() {
             self.current_map_type = new_map_type;
+            self.current_template = None;
             match new_map_type.create_map(self.board_dim, self.board_dim) {
-                Ok(new_map) => {
-                    self.map = new_map;
-                    // Update board dimensions to match the loaded map
-                    self.board_dim = self.map.width().max(self.map.height());
-                    // Initialize editor state with new map metadata
-                    self.editor_state.initialize_from_map(&self.map);
-                    // Update editor state's target dimensions to match new map
-                    self.editor_state.set_target_dimensions(self.map.width(), self.map.height());
-                    // Clear selection when changing maps to prevent hover issues
-                    self.selected_cell = None;
-                    self.selected_tile = None;
-                    // Clear agent trail when changing maps
-                    self.agent.clear_movement_history();
-                    self.agent.log(LogEntry::Info(
-                        "Map changed - agent trail cleared".to_string(),
-                    ));
-                }
+                Ok(new_map) => self.replace_map(new_map),
                 Err(e) => {
-                    self.agent.log(LogEntry::Error(
-                        format!("Failed to load map: {}", e),
-                    ));
+                    self.agent
+                        .log_error(ErrorKind::Internal, format!("Failed to load map: {}", e));
                 }
             }
         }
@@ -233,6 +550,9 @@ impl eframe::App for MyApp {
         // Process fixed-rate ticks
         self.process_ticks();
 
+        // Load maps dropped onto the window
+        self.handle_dropped_files(ctx);
+
         // Drain log callbacks from async operations
         let pending_logs: Vec<(u32, LogEntry)> = {
             let mut g = self.log_callbacks.lock().unwrap();
@@ -262,19 +582,41 @@ impl eframe::App for MyApp {
             self.should_continue_execution = true;
         }
 
+        self.check_llm_timeout();
+
+        // Running out of energy ends the run; the tool result still reports the failed move
+        if self.agent.take_out_of_energy() && self.agent_running {
+            self.agent_running = false;
+            self.should_continue_execution = false;
+            self.agent.log_info("Run stopped: out of energy");
+            self.agent.finish_run(&self.map);
+        }
+
+        // Global shortcuts are suppressed while a text field has focus (ESC excepted)
+        let text_input_focused = keyboard::text_input_focused(ctx);
+
+        // Drawn before the ESC check below, which it takes Escape away from while open
+        if !text_input_focused && ctx.input(|i| Shortcut::CommandPalette.pressed(i)) {
+            self.command_palette.open();
+        }
+        self.draw_command_palette(ctx);
+
         // Check for ESC key to cancel execution
         ctx.input(|i| {
-            if i.key_pressed(egui::Key::Escape) && self.agent_running {
+            if Shortcut::CancelRun.pressed(i) && self.agent_running {
                 self.agent_running = false;
                 self.should_continue_execution = false;
 
-                // Clear LLM status indicator immediately
+                // Stop the stream and clear the LLM status indicator immediately
+                self.agent.abort_stream();
                 if let Ok(mut status) = self.llm_status_callback.lock() {
                     *status = false;
                 }
 
                 // Clear any pending tool callbacks that haven't been processed yet
                 self.tool_execution_manager.clear_callbacks();
+                self.tool_execution_manager
+                    .reject_all_approvals(&mut self.agent, "the run was cancelled");
 
                 // Clear any pending log callbacks
                 if let Ok(mut logs) = self.log_callbacks.lock() {
@@ -284,9 +626,25 @@ impl eframe::App for MyApp {
                 self.agent.log(LogEntry::Info(
                     "WARN: Execution cancelled by user (ESC)".to_string(),
                 ));
+                if !self.instruction_queue.is_empty() {
+                    self.agent.log_info(format!(
+                        "Dropped {} queued instructions",
+                        self.instruction_queue.len()
+                    ));
+                    self.instruction_queue.clear();
+                }
+                self.agent.finish_run(&self.map);
             }
         });
 
+        if !text_input_focused {
+            self.handle_manual_movement(ctx);
+            if ctx.input(|i| Shortcut::ToggleHelp.pressed(i)) {
+                self.show_shortcut_help = !self.show_shortcut_help;
+            }
+        }
+        ShortcutHelp::draw(ctx, &mut self.show_shortcut_help, self.settings.movement_keys);
+
         // Continue agent execution if tool result was just added
         if self.should_continue_execution
             && self.agent_running
@@ -300,6 +658,7 @@ impl eframe::App for MyApp {
             let tool_callbacks = self.tool_execution_manager.get_tool_callbacks();
             let log_callbacks = self.log_callbacks.clone();
 
+            self.agent.set_decision_logging(self.settings.decision_log);
             self.agent.execute_instruction(
                 String::new(), // Empty instruction - continue from chat history
                 api_key,
@@ -308,16 +667,65 @@ impl eframe::App for MyApp {
                 tool_callbacks,
                 log_callbacks,
                 self.llm_status_callback.clone(),
+                self.settings.streaming,
+                self.settings.llm_retries,
             );
         }
 
-        // Request repaint if there are pending events, tool executions, or LLM activity
-        if self.event_queue.pending_count() > 0
+        // The run is over once nothing is left to do: no LLM turn in flight, no queued
+        // tool calls, no moves still executing, and no continuation scheduled
+        if self.agent_running
+            && !self.should_continue_execution
+            && !*self.llm_status_callback.lock().unwrap()
+            && !self.tool_execution_manager.has_pending_callbacks()
+            && !self.tool_execution_manager.has_pending_executions()
+            && self.event_queue.pending_count() == 0
+        {
+            self.agent_running = false;
+            self.agent.finish_run(&self.map);
+        }
+
+        // Feed the next queued instruction once the agent has gone idle
+        if !self.agent_running
+            && !AgentPanel::is_processing(&self.event_queue, &self.tool_execution_manager)
+            && let Some(instruction) = self.instruction_queue.pop_front()
+        {
+            self.agent.log_info(format!(
+                "Starting queued instruction ({} left): {}",
+                self.instruction_queue.len(),
+                instruction
+            ));
+            self.start_instruction(instruction);
+        }
+
+        // Advance teach-mode playback
+        let teach_playing = self.teach_mode.update(self.clock.now());
+
+        // Run stress-test trials a batch per frame so the UI stays responsive
+        let stress_running = if let Some(test) = &mut self.stress_test
+            && !test.is_finished()
+        {
+            test.run_batch(STRESS_TRIALS_PER_FRAME);
+            true
+        } else {
+            false
+        };
+
+        // Request repaint if there are pending events, tool executions, LLM activity or playback
+        if teach_playing
+            || stress_running
+            || self.animation_controller.pulse_progress().is_some()
+            || self.event_queue.pending_count() > 0
             || self.tool_execution_manager.has_pending_executions()
             || self.agent_running
             || *self.llm_status_callback.lock().unwrap()
         {
             ctx.request_repaint_after(Duration::from_millis(50));
+        } else if let Some(interval) = self.settings.idle_repaint.interval()
+            && self.has_idle_animation()
+        {
+            // Keep animated tiles moving while idle
+            ctx.request_repaint_after(interval);
         }
 
         egui::CentralPanel::default()
@@ -338,6 +746,9 @@ impl eframe::App for MyApp {
                     });
                 });
             });
+
+        self.draw_request_preview(ctx);
+        self.draw_settings_window(ctx);
     }
 }
 
@@ -350,6 +761,17 @@ impl MyApp {
             .show(ui, |ui| {
                 ui.vertical(|ui| {
                     ui.heading("Agent Panel");
+                    if let Some(call) = self.tool_execution_manager.awaiting_approval()
+                        && let Some(approved) = ToolApprovalCard::draw(ui, call)
+                        && self.tool_execution_manager.resolve_approval(
+                            approved,
+                            &mut self.agent,
+                            &mut self.map,
+                            &self.event_queue,
+                        )
+                    {
+                        self.should_continue_execution = true;
+                    }
                     if self.agent_selected {
                         self.draw_agent_controls(ui);
                     } else {
@@ -361,6 +783,9 @@ impl MyApp {
                         self.draw_tile_info(ui);
                     }
 
+                    ui.separator();
+                    EventInspector::draw(ui, &self.event_queue);
+
                     ui.separator();
                     ui.heading("Activity Log");
                     self.draw_activity_log(ui);
@@ -377,60 +802,127 @@ impl MyApp {
         ui.add_enabled_ui(!is_processing, |ui| {
             ui.text_edit_singleline(&mut self.agent.name);
         });
-        ui.add_space(8.0);
+        ui.add_space(4.0);
 
-        // Model selection
-        ui.label("Model");
-        ui.add_enabled_ui(!is_processing, |ui| {
-            egui::ComboBox::from_id_source("model_selector")
-                .selected_text(&self.selected_model)
+        // Appearance
+        ui.horizontal(|ui| {
+            ui.label("Color");
+            ui.color_edit_button_srgba(&mut self.agent.color);
+            ui.checkbox(&mut self.agent.label_background, "Label background");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Sprite");
+            egui::ComboBox::from_id_source("agent_sprite")
+                .selected_text(self.agent.sprite.label())
                 .show_ui(ui, |ui| {
-                    ui.selectable_value(
-                        &mut self.selected_model,
-                        "anthropic/claude-haiku-4.5".to_string(),
-                        "Claude Haiku 4.5",
-                    );
-                    ui.selectable_value(
-                        &mut self.selected_model,
-                        "anthropic/claude-sonnet-4.5".to_string(),
-                        "Claude Sonnet 4.5",
-                    );
-                    ui.selectable_value(
-                        &mut self.selected_model,
-                        "x-ai/grok-4-fast".to_string(),
-                        "Grok 4 Fast",
-                    );
-                    ui.selectable_value(
-                        &mut self.selected_model,
-                        "x-ai/grok-code-fast-1".to_string(),
-                        "Grok Code Fast",
-                    );
+                    for sprite in AgentSprite::ALL {
+                        ui.selectable_value(&mut self.agent.sprite, sprite, sprite.label());
+                    }
                 });
         });
         ui.add_space(8.0);
 
-        // History messages limit
-        ui.label("Max History Messages");
+        // Footprint size
+        ui.label("Size");
         ui.add_enabled_ui(!is_processing, |ui| {
-            let mut max_history = self.agent.max_history_messages() as i32;
+            ui.horizontal(|ui| {
+                let (mut w, mut h) = self.agent.size();
+                let w_changed = ui
+                    .add(egui::DragValue::new(&mut w).range(1..=4).prefix("w: "))
+                    .changed();
+                let h_changed = ui
+                    .add(egui::DragValue::new(&mut h).range(1..=4).prefix("h: "))
+                    .changed();
+                if w_changed || h_changed {
+                    self.agent.set_size(w, h);
+                    if !self.agent.can_enter(&self.map, self.agent.x, self.agent.y) {
+                        self.agent.log_error(ErrorKind::Validation, format!(
+                            "A {}x{} agent does not fit at ({}, {})",
+                            w, h, self.agent.x, self.agent.y
+                        ));
+                    }
+                }
+            });
+        });
+        ui.add_space(8.0);
+
+        // Movement
+        ui.add_enabled_ui(!is_processing && self.map.topology() == Topology::Square, |ui| {
+            let mut diagonals = self.agent.allow_diagonals();
             if ui
-                .add(egui::Slider::new(&mut max_history, 1..=50).text("messages"))
+                .checkbox(&mut diagonals, "Diagonal moves")
+                .on_hover_text(
+                    "Also step diagonally on square grids; a diagonal can't cut past a \
+                    blocked corner",
+                )
                 .changed()
             {
-                self.agent.set_max_history_messages(max_history as usize);
+                self.agent.set_allow_diagonals(diagonals);
             }
-            ui.add_space(2.0);
-            ui.label(
-                egui::RichText::new("Controls how many recent messages are sent to the LLM")
-                    .small()
-                    .color(egui::Color32::from_gray(120)),
+        });
+        let mut fog = self.agent.fog_of_war();
+        if ui
+            .add_enabled(!is_processing, egui::Checkbox::new(&mut fog, "Fog of war"))
+            .on_hover_text(
+                "The agent only knows cells it has seen with get_map_state; the rest are \
+                dimmed and reported as unknown",
+            )
+            .changed()
+        {
+            self.agent.set_fog_of_war(fog);
+        }
+        ui.add_space(8.0);
+
+        // Model selection
+        ui.label("Model");
+        ui.add_enabled_ui(!is_processing, |ui| {
+            let model_changed = self.model_picker.draw(
+                ui,
+                &mut self.selected_model,
+                &self.openrouter_api_key,
             );
+
+            // Apply the new model's defaults; the user can still tweak them afterwards
+            if model_changed
+                && let Some(preset) = ModelPreset::for_model(&self.selected_model)
+            {
+                self.agent.apply_model_preset(preset);
+                self.agent.log_info(format!("Applied defaults for {}", preset.label));
+            }
         });
         ui.add_space(8.0);
 
         // Tool toggles
         self.draw_tool_toggles(ui, is_processing);
 
+        self.draw_energy_budget(ui, is_processing);
+        TeachPanel::draw(
+            ui,
+            &mut self.teach_mode,
+            &self.map,
+            &self.agent,
+            self.clock.now(),
+        );
+        if PatrolPanel::draw(
+            ui,
+            &mut self.patrollers,
+            &mut self.recording_patrol,
+            &self.map,
+            &mut self.agent,
+        ) {
+            self.sync_patrol_obstacles();
+        }
+        ReplayPanel::draw(ui, &mut self.replay, &self.map, &self.agent);
+        StressTestPanel::draw(
+            ui,
+            &mut self.stress_test,
+            (&mut self.stress_trials, &mut self.stress_seed),
+            &self.map,
+            &self.agent,
+            TICK_RATE,
+        );
+        ui.add_space(8.0);
+
         ui.label("Instruction");
 
         let mut should_submit = false;
@@ -441,10 +933,11 @@ impl MyApp {
                 .desired_width(ui.available_width());
             let response = ui.add(text_edit);
 
-            // Check for Shift+Enter shortcut
+            // Shift+Enter is the instruction box's own shortcut, so it only fires while
+            // that box has focus - the inverse of the global shortcut guard
             if response.has_focus() {
                 ui.input(|i| {
-                    if i.key_pressed(egui::Key::Enter) && i.modifiers.shift {
+                    if Shortcut::SubmitInstruction.pressed(i) {
                         should_submit = true;
                     }
                 });
@@ -460,35 +953,109 @@ impl MyApp {
             {
                 should_submit = true;
             }
+            if ui
+                .add_sized(
+                    [ui.available_width(), 0.0],
+                    egui::Button::new("Queue Lines"),
+                )
+                .on_hover_text("Run each line as its own instruction, one after another")
+                .clicked()
+            {
+                self.instruction_queue.extend(
+                    self.agent_instruction
+                        .lines()
+                        .map(str::trim)
+                        .filter(|line| !line.is_empty())
+                        .map(String::from),
+                );
+                self.agent_instruction.clear();
+            }
+            if ui
+                .add_sized(
+                    [ui.available_width(), 0.0],
+                    egui::Button::new("Preview Request"),
+                )
+                .on_hover_text("Show the exact payload that would be sent, without calling the API")
+                .clicked()
+            {
+                let body = self.agent.build_request_body(
+                    &self.map,
+                    &self.selected_model,
+                    Some(&self.agent_instruction),
+                    self.settings.streaming,
+                );
+                self.request_preview =
+                    Some(serde_json::to_string_pretty(&body).unwrap_or_default());
+            }
         });
 
         // Show status indicators under the button
         if is_llm_active {
-            AgentPanel::draw_thinking_status(ui, &self.animation_controller);
+            AgentPanel::draw_thinking_status(
+                ui,
+                &self.animation_controller,
+                &self.agent.streaming_thinking(),
+            );
         }
 
         if is_processing {
             AgentPanel::draw_processing_status(ui, &self.event_queue, &self.animation_controller);
         }
 
+        self.draw_instruction_queue(ui);
+
+        AgentPanel::draw_run_stats(ui, self.agent.run_stats());
+        AgentPanel::draw_token_usage(ui, &mut self.agent);
+        RunHistoryPanel::draw(ui, &mut self.agent);
+        if self.settings.decision_log {
+            DecisionLogPanel::draw(ui, &mut self.agent);
+        }
+
         if should_submit && !is_processing {
-            let api_key = self.openrouter_api_key.clone();
-            let instruction = self.agent_instruction.clone();
-            let tool_callbacks = self.tool_execution_manager.get_tool_callbacks();
-            let log_callbacks = self.log_callbacks.clone();
+            self.start_instruction(self.agent_instruction.clone());
+        }
+    }
 
-            // Start agent execution loop
-            self.agent_running = true;
+    /// Start the agent execution loop on a new instruction
+    fn start_instruction(&mut self, instruction: String) {
+        let api_key = self.openrouter_api_key.clone();
+        let tool_callbacks = self.tool_execution_manager.get_tool_callbacks();
+        let log_callbacks = self.log_callbacks.clone();
+
+        self.agent_running = true;
+
+        // Agent executes instruction internally
+        self.agent.set_decision_logging(self.settings.decision_log);
+        self.agent.execute_instruction(
+            instruction,
+            api_key,
+            self.selected_model.clone(),
+            &self.map,
+            tool_callbacks,
+            log_callbacks,
+            self.llm_status_callback.clone(),
+            self.settings.streaming,
+            self.settings.llm_retries,
+        );
+    }
 
-            // Agent executes instruction internally
-            self.agent.execute_instruction(
-                instruction,
-                api_key,
-                self.selected_model.clone(),
-                &self.map,
-                tool_callbacks,
-                log_callbacks,
-                self.llm_status_callback.clone(),
+    /// Instructions still waiting to run, with a button to drop them
+    fn draw_instruction_queue(&mut self, ui: &mut egui::Ui) {
+        if self.instruction_queue.is_empty() {
+            return;
+        }
+        ui.add_space(4.0);
+        ui.horizontal(|ui| {
+            ui.label(format!("Queued ({})", self.instruction_queue.len()));
+            if ui.small_button("Clear").clicked() {
+                self.instruction_queue.clear();
+            }
+        });
+        for (index, instruction) in self.instruction_queue.iter().enumerate() {
+            ui.label(
+                egui::RichText::new(format!("{}. {}", index + 1, instruction))
+                    .small()
+                    .color(egui::Color32::from_gray(90)),
             );
         }
     }
@@ -526,12 +1093,478 @@ impl MyApp {
                             .color(egui::Color32::from_gray(120)),
                     );
                 }
+                if tool_name == "set_tile" && self.agent.is_tool_enabled("set_tile") {
+                    self.draw_placeable_tiles(ui);
+                    ui.checkbox(
+                        &mut self.tool_execution_manager.require_approval,
+                        "Require approval for map edits",
+                    )
+                    .on_hover_text("Pause each map-changing tool call until you approve or reject it");
+                }
                 ui.add_space(4.0);
             }
         });
         ui.add_space(8.0);
     }
 
+    fn draw_traversability_rules(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Traversability")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.label(
+                    egui::RichText::new("Tiles the agent can walk on (trail and portals always can)")
+                        .small()
+                        .color(egui::Color32::from_gray(120)),
+                );
+                ui.horizontal_wrapped(|ui| {
+                    for tile in TraversabilityRules::CONFIGURABLE {
+                        let mut passable = self.agent.traversability().is_passable(&tile);
+                        if ui.checkbox(&mut passable, tile.name()).changed() {
+                            self.agent.traversability_mut().set_passable(tile, passable);
+                            // Remembered obstacles were found under the old rules
+                            self.agent.clear_known_obstacles();
+                        }
+                    }
+                });
+                if *self.agent.traversability() != TraversabilityRules::default()
+                    && ui.small_button("Reset to defaults").clicked()
+                {
+                    *self.agent.traversability_mut() = TraversabilityRules::default();
+                    self.agent.clear_known_obstacles();
+                }
+            });
+    }
+
+    fn draw_movement_error_policy(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("On blocked move:").small());
+            let mut policy = self.agent.movement_error_policy();
+            egui::ComboBox::from_id_source("movement_error_policy")
+                .selected_text(policy.label())
+                .show_ui(ui, |ui| {
+                    for option in MovementErrorPolicy::ALL {
+                        ui.selectable_value(&mut policy, option, option.label());
+                    }
+                });
+            if policy != self.agent.movement_error_policy() {
+                self.agent.set_movement_error_policy(policy);
+            }
+        });
+    }
+
+    /// How much detail tool results give the model
+    fn draw_tool_verbosity(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("Tool results:").small());
+            let mut verbosity = self.agent.tool_verbosity();
+            egui::ComboBox::from_id_source("tool_verbosity")
+                .selected_text(verbosity.label())
+                .show_ui(ui, |ui| {
+                    for option in ToolVerbosity::ALL {
+                        ui.selectable_value(&mut verbosity, option, option.label());
+                    }
+                });
+            if verbosity != self.agent.tool_verbosity() {
+                self.agent.set_tool_verbosity(verbosity);
+            }
+        });
+        ui.label(
+            egui::RichText::new("Terse results save tokens; verbose ones add per-cell detail")
+                .small()
+                .color(egui::Color32::from_gray(120)),
+        );
+    }
+
+    /// Energy budget toggle and capacity, with the remaining energy as a bar
+    fn draw_energy_budget(&mut self, ui: &mut egui::Ui, is_processing: bool) {
+        ui.add_enabled_ui(!is_processing, |ui| {
+            ui.horizontal(|ui| {
+                let mut enabled = self.agent.energy().is_some();
+                if ui
+                    .checkbox(&mut enabled, "Energy budget")
+                    .on_hover_text("Moves spend the entered tile's cost; fuel tiles refill it")
+                    .changed()
+                {
+                    self.agent
+                        .set_energy_capacity(enabled.then_some(DEFAULT_ENERGY_CAPACITY));
+                }
+                if let Some((_, capacity)) = self.agent.energy() {
+                    let mut new_capacity = capacity;
+                    if ui
+                        .add(egui::DragValue::new(&mut new_capacity).range(1..=999).prefix("max: "))
+                        .changed()
+                    {
+                        self.agent.set_energy_capacity(Some(new_capacity));
+                    }
+                    if ui.small_button("Refill").clicked() {
+                        self.agent.refill_energy();
+                    }
+                }
+            });
+        });
+        if let Some((energy, capacity)) = self.agent.energy() {
+            ui.add(
+                egui::ProgressBar::new(energy as f32 / capacity as f32)
+                    .text(format!("Energy {} / {}", energy, capacity)),
+            );
+        }
+    }
+
+    fn draw_tile_costs(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Movement Costs")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.label(
+                    egui::RichText::new("Cost of entering each tile: sets route choice and move speed")
+                        .small()
+                        .color(egui::Color32::from_gray(120)),
+                );
+                let mut changed = false;
+                egui::Grid::new("tile_costs_grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        for tile in TraversabilityRules::CONFIGURABLE {
+                            ui.label(tile.name());
+                            let mut cost = self.agent.tile_costs().cost(&tile);
+                            if ui
+                                .add(egui::DragValue::new(&mut cost).range(1..=TileCosts::MAX_COST))
+                                .changed()
+                            {
+                                self.agent.tile_costs_mut().set_cost(tile, cost);
+                                changed = true;
+                            }
+                            ui.end_row();
+                        }
+                    });
+                if *self.agent.tile_costs() != TileCosts::default()
+                    && ui.small_button("Reset to defaults").clicked()
+                {
+                    *self.agent.tile_costs_mut() = TileCosts::default();
+                    changed = true;
+                }
+                if changed {
+                    self.agent.refresh_route_comparison(&self.map);
+                }
+            });
+    }
+
+    fn draw_placeable_tiles(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            ui.label(egui::RichText::new("Placeable:").small());
+            for tile in [
+                TileKind::Empty,
+                TileKind::Grass,
+                TileKind::Sand,
+                TileKind::Water,
+                TileKind::Wall,
+                TileKind::Tree,
+            ] {
+                let mut allowed = self.agent.can_place_tile(tile);
+                if ui.checkbox(&mut allowed, tile.name()).changed() {
+                    self.agent.set_tile_placeable(tile, allowed);
+                }
+            }
+        });
+    }
+
+    /// Settings window: app-wide `Settings` plus the agent's model, trail and movement rules
+    fn draw_settings_window(&mut self, ctx: &egui::Context) {
+        if !self.show_settings {
+            return;
+        }
+        let is_processing =
+            AgentPanel::is_processing(&self.event_queue, &self.tool_execution_manager);
+        let before = self.settings;
+        let mut open = true;
+        egui::Window::new("Settings")
+            .open(&mut open)
+            .default_size([360.0, 520.0])
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::CollapsingHeader::new("Display")
+                        .default_open(true)
+                        .show(ui, |ui| SettingsWindow::draw_display(ui, &mut self.settings));
+                    egui::CollapsingHeader::new("Controls")
+                        .default_open(true)
+                        .show(ui, |ui| SettingsWindow::draw_controls(ui, &mut self.settings));
+                    egui::CollapsingHeader::new("Trail")
+                        .show(ui, |ui| self.draw_trail_settings(ui));
+                    egui::CollapsingHeader::new("LLM")
+                        .show(ui, |ui| self.draw_llm_settings(ui, is_processing));
+                    egui::CollapsingHeader::new("Movement Rules").show(ui, |ui| {
+                        ui.add_enabled_ui(!is_processing, |ui| {
+                            self.draw_traversability_rules(ui);
+                            self.draw_tile_costs(ui);
+                            self.draw_movement_error_policy(ui);
+                        });
+                    });
+                    egui::CollapsingHeader::new("Share").show(ui, |ui| {
+                        ui.add_enabled_ui(!is_processing, |ui| self.draw_settings_share(ui));
+                    });
+                });
+            });
+
+        if !open {
+            self.show_settings = false;
+        }
+        if self.settings.topology != before.topology {
+            // Neighbors change with the topology, so the old search overlay no longer applies
+            self.map.set_topology(self.settings.topology);
+            self.teach_mode.clear();
+        }
+        if self.settings != before {
+            self.settings.save();
+        }
+    }
+
+    /// Copy the settings to the clipboard as JSON, or import a pasted export
+    fn draw_settings_share(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.share_include_model, "Include model");
+            ui.checkbox(&mut self.share_include_map, "Include map");
+        });
+        if ui.button("Copy Settings JSON").clicked() {
+            EditorOperations::copy_to_clipboard(&self.export_settings_json());
+            self.agent.log_info("Settings JSON copied to clipboard!");
+        }
+        ui.add_space(4.0);
+        ui.add(
+            egui::TextEdit::multiline(&mut self.settings_import_text)
+                .desired_rows(4)
+                .desired_width(ui.available_width())
+                .hint_text("Paste exported settings JSON here"),
+        );
+        if ui
+            .add_enabled(
+                !self.settings_import_text.trim().is_empty(),
+                egui::Button::new("Import"),
+            )
+            .clicked()
+        {
+            let text = std::mem::take(&mut self.settings_import_text);
+            self.import_settings_json(&text);
+        }
+    }
+
+    /// Settings, plus optionally the model and current map, as one JSON blob to share
+    fn export_settings_json(&self) -> String {
+        let mut blob = serde_json::json!({ "settings": self.settings });
+        if self.share_include_model {
+            blob["model"] = serde_json::json!(self.selected_model);
+        }
+        if self.share_include_map {
+            blob["map"] = EditorOperations::map_json(&self.map, &self.editor_state, &self.agent);
+        }
+        serde_json::to_string_pretty(&blob).unwrap_or_default()
+    }
+
+    /// Apply a blob made by `export_settings_json`, logging which fields were applied
+    /// and which were ignored
+    fn import_settings_json(&mut self, text: &str) {
+        let blob: serde_json::Value = match serde_json::from_str(text) {
+            Ok(blob) => blob,
+            Err(e) => {
+                self.agent
+                    .log_error(ErrorKind::Validation, format!("Invalid settings JSON: {}", e));
+                return;
+            }
+        };
+        let Some(fields) = blob.as_object() else {
+            self.agent
+                .log_error(ErrorKind::Validation, "Settings JSON must be an object");
+            return;
+        };
+
+        let mut applied = Vec::new();
+        let mut ignored = Vec::new();
+        for (key, value) in fields {
+            match key.as_str() {
+                "settings" => match self.settings.apply_json(value) {
+                    Ok(import) => {
+                        applied.extend(import.applied.iter().map(|f| format!("settings.{}", f)));
+                        ignored.extend(import.ignored.iter().map(|f| format!("settings.{}", f)));
+                    }
+                    Err(e) => ignored.push(format!("settings ({})", e)),
+                },
+                "model" => match value.as_str().map(str::trim).filter(|m| !m.is_empty()) {
+                    Some(model) => {
+                        self.selected_model = model.to_string();
+                        if let Some(preset) = ModelPreset::for_model(model) {
+                            self.agent.apply_model_preset(preset);
+                        }
+                        applied.push("model".to_string());
+                    }
+                    None => ignored.push("model (expected a model id string)".to_string()),
+                },
+                "map" => match GridMap::from_json(&value.to_string()) {
+                    Ok(map) => {
+                        self.replace_map(map);
+                        applied.push("map".to_string());
+                    }
+                    Err(e) => ignored.push(format!("map ({})", e)),
+                },
+                _ => ignored.push(format!("{} (unknown field)", key)),
+            }
+        }
+
+        if applied.is_empty() {
+            self.agent.log_info("Imported settings: nothing applied");
+        } else {
+            self.agent
+                .log_info(format!("Imported settings: applied {}", applied.join(", ")));
+        }
+        if !ignored.is_empty() {
+            self.agent.log_error(
+                ErrorKind::Validation,
+                format!("Ignored on import: {}", ignored.join(", ")),
+            );
+        }
+    }
+
+    /// Trail tiles and movement history length
+    fn draw_trail_settings(&mut self, ui: &mut egui::Ui) {
+        let mut leave_trail = self.agent.leaves_trail_tiles();
+        if ui
+            .checkbox(&mut leave_trail, "Trail tiles")
+            .on_hover_text(
+                "Write trail tiles into the map as the agent walks. \
+                When off, the trail is only drawn from movement history.",
+            )
+            .changed()
+        {
+            self.agent.set_leave_trail_tiles(leave_trail, &mut self.map);
+        }
+        ui.add_space(4.0);
+
+        // Trail / movement history cap
+        ui.label("Trail Length");
+        let mut history_limit = self.agent.movement_history_limit();
+        if ui
+            .add(
+                egui::Slider::new(&mut history_limit, 10..=10_000)
+                    .text("positions")
+                    .logarithmic(true),
+            )
+            .changed()
+        {
+            self.agent.set_movement_history_limit(history_limit);
+        }
+        ui.add_space(2.0);
+        ui.label(
+            egui::RichText::new("Older positions stop being recorded and drawn as trail")
+                .small()
+                .color(egui::Color32::from_gray(120)),
+        );
+    }
+
+    /// History, timeout, map view distance and sampling temperature for LLM turns
+    fn draw_llm_settings(&mut self, ui: &mut egui::Ui, is_processing: bool) {
+        // History messages limit
+        ui.label("Max History Messages");
+        ui.add_enabled_ui(!is_processing, |ui| {
+            let mut max_history = self.agent.max_history_messages() as i32;
+            if ui
+                .add(egui::Slider::new(&mut max_history, 1..=50).text("messages"))
+                .changed()
+            {
+                self.agent.set_max_history_messages(max_history as usize);
+            }
+            ui.add_space(2.0);
+            ui.label(
+                egui::RichText::new("Controls how many recent messages are sent to the LLM")
+                    .small()
+                    .color(egui::Color32::from_gray(120)),
+            );
+        });
+        ui.add_space(8.0);
+
+        // LLM stream timeout
+        ui.add_enabled_ui(!is_processing, |ui| {
+            SettingsWindow::draw_llm_timeout(ui, &mut self.settings);
+            SettingsWindow::draw_llm_retries(ui, &mut self.settings);
+        });
+        ui.add_space(8.0);
+
+        ui.add_enabled_ui(!is_processing, |ui| {
+            SettingsWindow::draw_streaming(ui, &mut self.settings);
+        });
+        SettingsWindow::draw_decision_log(ui, &mut self.settings);
+        ui.add_space(8.0);
+
+        ui.add_enabled_ui(!is_processing, |ui| self.draw_tool_verbosity(ui));
+        ui.add_space(8.0);
+
+        // get_map_state visibility settings
+        ui.label("Map View Distance");
+        ui.add_enabled_ui(!is_processing, |ui| {
+            let mut default = self.agent.default_visibility();
+            let mut max = self.agent.max_visibility();
+            let default_changed = ui
+                .add(egui::Slider::new(&mut default, 1..=max).text("default"))
+                .changed();
+            let max_changed = ui
+                .add(egui::Slider::new(&mut max, 1..=MAX_VISIBILITY_LIMIT).text("max"))
+                .changed();
+            if default_changed || max_changed {
+                self.agent.set_visibility_limits(default, max);
+            }
+            ui.add_space(2.0);
+            ui.label(
+                egui::RichText::new("How far get_map_state sees when the model gives no visibility")
+                    .small()
+                    .color(egui::Color32::from_gray(120)),
+            );
+        });
+        ui.add_space(8.0);
+
+        // Sampling temperature
+        ui.label("Temperature");
+        ui.add_enabled_ui(!is_processing, |ui| {
+            ui.horizontal(|ui| {
+                let mut use_default = self.agent.temperature().is_none();
+                if ui.checkbox(&mut use_default, "Provider default").changed() {
+                    self.agent
+                        .set_temperature(if use_default { None } else { Some(0.7) });
+                }
+                if let Some(mut temperature) = self.agent.temperature()
+                    && ui
+                        .add(egui::Slider::new(&mut temperature, 0.0..=2.0))
+                        .changed()
+                {
+                    self.agent.set_temperature(Some(temperature));
+                }
+            });
+        });
+    }
+
+    /// Window showing the request body built by "Preview Request"
+    fn draw_request_preview(&mut self, ctx: &egui::Context) {
+        let Some(preview) = &self.request_preview else {
+            return;
+        };
+
+        let mut open = true;
+        egui::Window::new("Request Preview")
+            .open(&mut open)
+            .default_size([520.0, 480.0])
+            .show(ctx, |ui| {
+                if ui.button("Copy to Clipboard").clicked() {
+                    EditorOperations::copy_to_clipboard(preview);
+                }
+                ui.separator();
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    ui.label(
+                        egui::RichText::new(preview.as_str())
+                            .font(egui::FontId::monospace(11.0)),
+                    );
+                });
+            });
+
+        if !open {
+            self.request_preview = None;
+        }
+    }
+
     fn draw_tile_info(&mut self, ui: &mut egui::Ui) {
         TileInfoPanel::draw(ui, self.selected_tile, &self.map, &self.agent);
     }
@@ -559,24 +1592,66 @@ impl MyApp {
 
                 ui.separator();
                 ui.label("Map:");
-                let mut selected_map = self.current_map_type;
+                let selected_text = match self.current_template {
+                    Some(template) => template.label().to_string(),
+                    None => self.current_map_type.selector_label(),
+                };
+                let mut chosen_template = None;
                 egui::ComboBox::from_id_source("map_selector")
-                    .selected_text(selected_map.name())
+                    .selected_text(selected_text)
                     .show_ui(ui, |ui| {
                         for map_type in MapType::all() {
-                            ui.selectable_value(&mut selected_map, map_type, map_type.name());
+                            let selected = self.current_template.is_none()
+                                && map_type == self.current_map_type;
+                            let clicked = ui
+                                .selectable_label(selected, map_type.selector_label())
+                                .on_hover_text(map_type.author().map_or_else(
+                                    || map_type.description().to_string(),
+                                    |author| format!("{}\nby {}", map_type.description(), author),
+                                ))
+                                .clicked();
+                            if clicked && !selected {
+                                // Defer expensive map creation to avoid blocking UI
+                                self.pending_map_change = Some(map_type);
+                            }
+                        }
+                        ui.separator();
+                        for template in MapTemplate::ALL {
+                            let selected = self.current_template == Some(template);
+                            if ui
+                                .selectable_label(selected, template.label())
+                                .on_hover_text(template.description())
+                                .clicked()
+                                && !selected
+                            {
+                                chosen_template = Some(template);
+                            }
                         }
                     });
-
-                if selected_map != self.current_map_type {
-                    // Defer expensive map creation to avoid blocking UI
-                    self.pending_map_change = Some(selected_map);
+                if let Some(template) = chosen_template {
+                    self.current_template = Some(template);
+                    self.generate_template_map();
                 }
 
                 ui.separator();
                 EditorUI::draw_edit_mode_toggle(ui, &mut self.editor_state);
+
+                ui.separator();
+                if ui.button("Settings").clicked() {
+                    self.show_settings = !self.show_settings;
+                }
+                if ui.button("?").on_hover_text("Keyboard shortcuts").clicked() {
+                    self.show_shortcut_help = !self.show_shortcut_help;
+                }
             });
 
+            // Parameters of the generated map template, if one is selected
+            if let Some(template) = self.current_template
+                && TemplatePanel::draw(ui, template, &mut self.template_params)
+            {
+                self.generate_template_map();
+            }
+
             // Tile palette when in edit mode
             if self.editor_state.edit_mode {
                 if let Some(new_board_dim) = EditorUI::draw_edit_controls(
@@ -594,38 +1669,281 @@ impl MyApp {
                 ui.add_space(8.0);
             }
 
-            ui.heading("Game Board");
+            ui.horizontal(|ui| {
+                ui.heading("Game Board");
+                ui.label(
+                    egui::RichText::new("Right-click a cell to set the goal")
+                        .small()
+                        .color(egui::Color32::from_gray(120)),
+                );
+                let is_busy = self.agent_running
+                    || AgentPanel::is_processing(&self.event_queue, &self.tool_execution_manager);
+                let (sx, sy) = self.agent.spawn();
+                if ui
+                    .add_enabled(!is_busy, egui::Button::new("Reset to Spawn").small())
+                    .on_hover_text(format!(
+                        "Move the agent back to ({}, {}) and clear its trail",
+                        sx, sy
+                    ))
+                    .clicked()
+                {
+                    self.agent.reset_to_spawn(&mut self.map);
+                    self.selected_tile = Some(self.agent.pos());
+                }
+            });
+            if let Some(goal) = self.agent.goal() {
+                Compass::draw(ui, self.agent.pos(), goal);
+            }
+            // Fit the view to the map's aspect ratio so wide or tall maps use the space
             let avail_r = ui.available_size();
-            let board_side = avail_r.x.min(avail_r.y).max(100.0);
-            let (rect, response) =
-                ui.allocate_exact_size(egui::vec2(board_side, board_side), egui::Sense::click());
-            let painter = ui.painter();
-
-            // Render the board
-            BoardRenderer::render(
-                &painter,
-                rect,
+            let max_view = egui::vec2(avail_r.x - MINIMAP_SIDE - 8.0, avail_r.y)
+                .max(egui::vec2(100.0, 100.0));
+            let view_size = BoardRenderer::fit_map_rect(
+                egui::Rect::from_min_size(egui::Pos2::ZERO, max_view),
                 &self.map,
-                &self.agent,
-                self.selected_cell,
-                self.tree_tex.as_ref(),
-            );
-
-            // Handle input
-            BoardInput::handle_input(
-                ui,
-                rect,
-                board_side,
-                self.board_dim,
-                &response,
-                &mut self.editor_state,
-                &mut self.map,
-                &mut self.agent,
-                &mut self.agent_selected,
-                &mut self.selected_cell,
-                &mut self.selected_tile,
-            );
+            )
+            .size()
+            .max(egui::vec2(100.0, 100.0));
+            ui.horizontal_top(|ui| {
+                self.draw_board_view(ui, view_size);
+                ui.vertical(|ui| {
+                    let panned = Minimap::draw(
+                        ui,
+                        &self.map,
+                        &self.agent,
+                        &mut self.viewport,
+                        MINIMAP_SIDE,
+                        self.settings.tile_palette,
+                    );
+                    if panned {
+                        self.follow_agent = false;
+                    }
+                    ui.horizontal(|ui| {
+                        if ui.small_button("-").on_hover_text("Zoom out").clicked() {
+                            self.viewport.set_zoom(self.viewport.zoom() / 1.25);
+                        }
+                        ui.label(
+                            egui::RichText::new(format!("{:.0}%", self.viewport.zoom() * 100.0))
+                                .small(),
+                        );
+                        if ui.small_button("+").on_hover_text("Zoom in").clicked() {
+                            self.viewport.set_zoom(self.viewport.zoom() * 1.25);
+                        }
+                        if self.viewport.is_zoomed() && ui.small_button("Fit").clicked() {
+                            self.viewport = Viewport::default();
+                        }
+                    });
+                    if ui
+                        .small_button("Save PNG")
+                        .on_hover_text("Download the whole board as an image")
+                        .clicked()
+                    {
+                        self.save_board_png();
+                    }
+                    ui.toggle_value(&mut self.follow_agent, "Follow agent")
+                        .on_hover_text("Keep the view centered on the agent while zoomed");
+                    if ui
+                        .button("Find agent")
+                        .on_hover_text("Pan to the agent and briefly highlight it")
+                        .clicked()
+                    {
+                        self.find_agent();
+                    }
+                    ui.label(
+                        egui::RichText::new("Ctrl+scroll to zoom")
+                            .small()
+                            .color(egui::Color32::from_gray(120)),
+                    );
+                    ui.add_space(8.0);
+                    TileLegend::draw(ui, self.settings.tile_palette, self.tree_tex.as_ref());
                 });
             });
+                });
+            });
+    }
+
+    /// Replace the map with one built from the selected template and its parameters
+    fn generate_template_map(&mut self) {
+        let Some(template) = self.current_template else {
+            return;
+        };
+        let map = template.generate(&self.template_params);
+        self.agent.log_info(format!(
+            "Generated {} map {}x{} (seed {})",
+            template.label(),
+            map.width(),
+            map.height(),
+            self.template_params.seed
+        ));
+        self.replace_map(map);
+    }
+
+    /// Pan to the agent once (unlike follow mode) and pulse a ring around it
+    fn find_agent(&mut self) {
+        self.finding_agent = true;
+        self.animation_controller.start_pulse();
+    }
+
+    /// Ease the view toward the agent's center, repainting until it catches up
+    ///
+    /// Returns true while the view is still moving.
+    fn track_agent(&mut self, ctx: &egui::Context, view_size: egui::Vec2) -> bool {
+        // Agent center as a fraction of the unzoomed board rect (the map may be letterboxed)
+        let board = egui::Rect::from_min_size(egui::Pos2::ZERO, view_size);
+        let map_rect = BoardRenderer::fit_map_rect(board, &self.map);
+        let cell = map_rect.height() / self.map.height().max(1) as f32;
+        let (w, h) = self.agent.size();
+        let center =
+            BoardRenderer::cell_rect(map_rect, cell, &self.map, self.agent.x, self.agent.y).min
+                + egui::vec2(w as f32 / 2.0, h as f32 / 2.0) * cell;
+        let target = egui::pos2(center.x / board.width(), center.y / board.height());
+        // Frame-rate independent easing: about 90% of the way there every 0.3s
+        let dt = ctx.input(|i| i.stable_dt).min(0.1);
+        let fraction = 1.0 - (-dt * 8.0).exp();
+        let panning = self.viewport.pan_towards(target, fraction);
+        if panning {
+            ctx.request_repaint();
+        }
+        panning
+    }
+
+    /// Board overlays and styling for this frame, with `patrollers` drawn as moving obstacles
+    fn render_options<'a>(&'a self, patrollers: &'a [Patroller]) -> RenderOptions<'a> {
+        RenderOptions {
+            selected_cell: self.selected_cell,
+            tree_tex: self.tree_tex.as_ref(),
+            agent_tex: self.agent_tex.as_ref(),
+            grid_lines: self.settings.grid_lines,
+            show_trail: self.settings.show_trail,
+            goal: self.agent.goal(),
+            spawn: self.editor_state.edit_mode.then(|| self.agent.spawn()),
+            animation_frame: self.animation_controller.frame(),
+            agent_pulse: self.animation_controller.pulse_progress(),
+            search: self.teach_mode.has_trace().then(|| SearchOverlay {
+                expanded: self.teach_mode.expanded(),
+                path: self.teach_mode.revealed_path(),
+            }),
+            palette: self.settings.tile_palette,
+            out_of_bounds: self.settings.out_of_bounds,
+            patrollers,
+            patrol_draft: self.recording_patrol.as_deref(),
+            unreachable: if self.editor_state.edit_mode {
+                self.editor_state.unreachable_highlight(&self.map)
+            } else {
+                &[]
+            },
+        }
+    }
+
+    /// The live agent's remaining route, including moves waiting in the event queue
+    ///
+    /// None while scrubbing a replay or when nothing is queued. Rebuilt every frame, so
+    /// the overlay shrinks as the agent walks it.
+    fn planned_path(&self) -> Option<Vec<(usize, usize)>> {
+        if self.replay.view().is_some() {
+            return None;
+        }
+        let queued = self.event_queue.queued_moves(self.agent.id);
+        let path = self.agent.planned_path(&self.map, &queued);
+        (path.len() > 1).then_some(path)
+    }
+
+    /// Render the board as shown (including a scrubbed replay step) and download it as a PNG
+    fn save_board_png(&mut self) {
+        let (map, agent, patrollers) = match self.replay.view() {
+            Some(view) => (&view.map, &view.agent, &[][..]),
+            None => (&self.map, &self.agent, &self.patrollers[..]),
+        };
+        let options = self.render_options(patrollers);
+        let planned_path = self.planned_path();
+        let image = BoardImage::render(
+            map,
+            agent,
+            planned_path.as_deref(),
+            &options,
+            SCREENSHOT_CELL_PX,
+        );
+        if let Err(e) = BoardImage::download_png(&image, "board.png") {
+            self.agent
+                .log_error(ErrorKind::Internal, format!("Failed to save screenshot: {}", e));
+        }
+    }
+
+    /// Draw the (possibly zoomed) board into a `view_size` area and handle its input
+    fn draw_board_view(&mut self, ui: &mut egui::Ui, view_size: egui::Vec2) {
+        let (view, response) = ui.allocate_exact_size(view_size, egui::Sense::click());
+
+        // Ctrl+scroll / pinch zooms around the current view center
+        if response.hovered() {
+            let zoom_delta = ui.input(|i| i.zoom_delta());
+            if zoom_delta != 1.0 {
+                self.viewport.set_zoom(self.viewport.zoom() * zoom_delta);
+            }
+        }
+
+        if self.follow_agent || self.finding_agent {
+            let panning = self.track_agent(ui.ctx(), view_size);
+            self.finding_agent &= panning;
+        }
+
+        // The whole board is laid out in a virtual rect and clipped to the view
+        let rect = self.viewport.board_rect(view);
+        let painter = ui.painter_at(view);
+
+        // Render the board, or the recorded state being scrubbed to
+        let (map, agent, patrollers) = match self.replay.view() {
+            Some(view) => (&view.map, &view.agent, &[][..]),
+            None => (&self.map, &self.agent, &self.patrollers[..]),
+        };
+        let planned_path = self.planned_path();
+        BoardRenderer::render(
+            &painter,
+            rect,
+            map,
+            agent,
+            planned_path.as_deref(),
+            &self.render_options(patrollers),
+        );
+
+        // While recording a patrol route, clicks add waypoints
+        if response.clicked()
+            && !self.editor_state.edit_mode
+            && let Some(waypoints) = &mut self.recording_patrol
+            && let Some(pos) = response.interact_pointer_pos()
+            && let Some((r, c)) =
+                BoardRenderer::screen_to_grid(pos, rect, &self.map)
+            && self.agent.traversability().is_cell_passable(&self.map, c, r)
+        {
+            waypoints.push((c, r));
+        }
+
+        // Right-click sets the goal; right-clicking the current goal clears it
+        if response.secondary_clicked()
+            && !self.editor_state.edit_mode
+            && let Some(pos) = response.interact_pointer_pos()
+            && let Some((r, c)) =
+                BoardRenderer::screen_to_grid(pos, rect, &self.map)
+        {
+            if self.agent.goal() == Some((c, r)) {
+                self.agent.set_goal(None);
+                self.agent.log_info("Goal cleared");
+            } else if self.map.in_bounds(c, r) {
+                self.agent.set_goal(Some((c, r)));
+                self.agent.log_info(format!("Goal set to ({}, {})", c, r));
+            }
+        }
+
+        // Handle input
+        BoardInput::handle_input(
+            ui,
+            rect,
+            &response,
+            &mut self.editor_state,
+            &mut self.map,
+            &mut self.agent,
+            &mut self.agent_selected,
+            &mut self.selected_cell,
+            &mut self.selected_tile,
+        );
     }
 }
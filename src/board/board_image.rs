@@ -0,0 +1,213 @@
+use crate::agent::Agent;
+use crate::board::{BoardRenderer, RenderOptions};
+use crate::map::GridMap;
+use crate::rendering::{generate_agent_sprite, generate_tree_sprite};
+use eframe::egui;
+use egui::epaint::{ClippedPrimitive, ImageData, Primitive, Vertex};
+use egui::{Color32, ColorImage, Pos2, Rect};
+use std::collections::HashMap;
+
+/// Renders the board into a `ColorImage` instead of onto the screen
+///
+/// The board is drawn by the regular `BoardRenderer` on an offscreen egui context, so
+/// tiles, trail, overlays and the agent come out exactly as on screen. The resulting
+/// meshes are then rasterized in software, since there is no GPU target to read back.
+pub struct BoardImage;
+
+impl BoardImage {
+    /// Draw the whole map at `cell_px` pixels per cell, with `planned_path` as on screen
+    ///
+    /// Sprite textures in `options` belong to the app's context, so any that are set are
+    /// regenerated on the offscreen one.
+    pub fn render(
+        map: &GridMap,
+        agent: &Agent,
+        planned_path: Option<&[(usize, usize)]>,
+        options: &RenderOptions,
+        cell_px: f32,
+    ) -> ColorImage {
+        let size = egui::vec2(
+            (BoardRenderer::columns(map) * cell_px).ceil(),
+            (map.height().max(1) as f32 * cell_px).ceil(),
+        );
+        let rect = Rect::from_min_size(Pos2::ZERO, size);
+        let ctx = egui::Context::default();
+        let input = egui::RawInput {
+            screen_rect: Some(rect),
+            ..Default::default()
+        };
+        let output = ctx.run(input, |ctx| {
+            let sprite = |name: &str, image: ColorImage| {
+                ctx.load_texture(name, image, egui::TextureOptions::LINEAR)
+            };
+            let tree_tex = options.tree_tex.map(|_| sprite("tree", generate_tree_sprite(48)));
+            let agent_tex = options.agent_tex.map(|_| sprite("agent", generate_agent_sprite(48)));
+            let options = RenderOptions {
+                tree_tex: tree_tex.as_ref(),
+                agent_tex: agent_tex.as_ref(),
+                ..*options
+            };
+            let painter = ctx.layer_painter(egui::LayerId::background());
+            BoardRenderer::render(&painter, rect, map, agent, planned_path, &options);
+        });
+
+        let mut textures = HashMap::new();
+        for (id, delta) in &output.textures_delta.set {
+            let pixels: Vec<Color32> = match &delta.image {
+                ImageData::Color(image) => image.pixels.clone(),
+                ImageData::Font(font) => font.srgba_pixels(None).collect(),
+            };
+            let patch = ColorImage {
+                size: delta.image.size(),
+                pixels,
+            };
+            match delta.pos {
+                Some(pos) => {
+                    if let Some(texture) = textures.get_mut(id) {
+                        Self::blit(texture, &patch, pos);
+                    }
+                }
+                None => {
+                    textures.insert(*id, patch);
+                }
+            }
+        }
+
+        let mut image = ColorImage::new([size.x as usize, size.y as usize], Color32::TRANSPARENT);
+        for ClippedPrimitive { clip_rect, primitive } in
+            ctx.tessellate(output.shapes, output.pixels_per_point)
+        {
+            let Primitive::Mesh(mesh) = primitive else {
+                continue;
+            };
+            let texture = textures.get(&mesh.texture_id);
+            for triangle in mesh.indices.chunks_exact(3) {
+                let corners = [0, 1, 2].map(|i| &mesh.vertices[triangle[i] as usize]);
+                Self::fill_triangle(&mut image, clip_rect, corners, texture);
+            }
+        }
+        image
+    }
+
+    /// Copy `patch` into `texture` with its top-left corner at `pos`
+    fn blit(texture: &mut ColorImage, patch: &ColorImage, [x0, y0]: [usize; 2]) {
+        let [width, height] = texture.size;
+        for y in 0..patch.size[1].min(height.saturating_sub(y0)) {
+            for x in 0..patch.size[0].min(width.saturating_sub(x0)) {
+                texture[(x0 + x, y0 + y)] = patch[(x, y)];
+            }
+        }
+    }
+
+    /// Rasterize one mesh triangle, sampling pixel centers and blending over `image`
+    ///
+    /// Colors are interpolated and blended as premultiplied gamma-space values, like
+    /// egui's own web painter. Missing textures (which shouldn't happen) count as white.
+    fn fill_triangle(
+        image: &mut ColorImage,
+        clip: Rect,
+        [a, b, c]: [&Vertex; 3],
+        texture: Option<&ColorImage>,
+    ) {
+        let edge =
+            |p: Pos2, q: Pos2, r: Pos2| (q.x - p.x) * (r.y - p.y) - (q.y - p.y) * (r.x - p.x);
+        let area = edge(a.pos, b.pos, c.pos);
+        if area.abs() < f32::EPSILON {
+            return;
+        }
+        let [width, height] = image.size;
+        let bounds = Rect::from_points(&[a.pos, b.pos, c.pos]).intersect(clip);
+        if !bounds.is_positive() {
+            return;
+        }
+        let (x_min, x_max) = (bounds.left().max(0.0) as usize, bounds.right().ceil() as usize);
+        let (y_min, y_max) = (bounds.top().max(0.0) as usize, bounds.bottom().ceil() as usize);
+
+        for y in y_min..y_max.min(height) {
+            for x in x_min..x_max.min(width) {
+                let p = egui::pos2(x as f32 + 0.5, y as f32 + 0.5);
+                let weights = [
+                    edge(b.pos, c.pos, p) / area,
+                    edge(c.pos, a.pos, p) / area,
+                    edge(a.pos, b.pos, p) / area,
+                ];
+                if weights.iter().any(|&w| w < 0.0) || !clip.contains(p) {
+                    continue;
+                }
+                let mix = |channel: fn(&Vertex) -> f32| {
+                    weights[0] * channel(a) + weights[1] * channel(b) + weights[2] * channel(c)
+                };
+                let u = mix(|v| v.uv.x);
+                let v = mix(|v| v.uv.y);
+                let texel = texture.map_or([1.0; 4], |texture| Self::sample(texture, u, v));
+                let src = [
+                    mix(|v| v.color.r() as f32) * texel[0],
+                    mix(|v| v.color.g() as f32) * texel[1],
+                    mix(|v| v.color.b() as f32) * texel[2],
+                    mix(|v| v.color.a() as f32) * texel[3],
+                ];
+                let dst = image[(x, y)].to_array().map(|c| c as f32);
+                let keep = 1.0 - src[3] / 255.0;
+                let [r, g, b, alpha] =
+                    [0, 1, 2, 3].map(|i| (src[i] + dst[i] * keep).round().clamp(0.0, 255.0) as u8);
+                image[(x, y)] = Color32::from_rgba_premultiplied(r, g, b, alpha);
+            }
+        }
+    }
+
+    /// Nearest texel at normalized `(u, v)`, as 0..=1 premultiplied channels
+    fn sample(texture: &ColorImage, u: f32, v: f32) -> [f32; 4] {
+        let [width, height] = texture.size;
+        let x = ((u * width as f32) as usize).min(width.saturating_sub(1));
+        let y = ((v * height as f32) as usize).min(height.saturating_sub(1));
+        texture[(x, y)].to_array().map(|c| c as f32 / 255.0)
+    }
+
+    /// Encode `image` as a PNG and offer it to the user as a file download
+    ///
+    /// The browser does the encoding: the pixels are put on a detached canvas, read back
+    /// as a data URL, and clicked through a temporary download link.
+    pub fn download_png(image: &ColorImage, file_name: &str) -> Result<(), String> {
+        use wasm_bindgen::{Clamped, JsCast};
+
+        let document = web_sys::window()
+            .and_then(|window| window.document())
+            .ok_or("No document to download from")?;
+        let (width, height) = (image.size[0] as u32, image.size[1] as u32);
+        let canvas = document
+            .create_element("canvas")
+            .ok()
+            .and_then(|element| element.dyn_into::<web_sys::HtmlCanvasElement>().ok())
+            .ok_or("Couldn't create a canvas")?;
+        canvas.set_width(width);
+        canvas.set_height(height);
+        let context = canvas
+            .get_context("2d")
+            .ok()
+            .flatten()
+            .and_then(|context| context.dyn_into::<web_sys::CanvasRenderingContext2d>().ok())
+            .ok_or("Canvas has no 2D context")?;
+
+        // Canvas pixels are unmultiplied RGBA
+        let rgba: Vec<u8> = image.pixels.iter().flat_map(|p| p.to_srgba_unmultiplied()).collect();
+        let data =
+            web_sys::ImageData::new_with_u8_clamped_array_and_sh(Clamped(&rgba), width, height)
+                .map_err(|_| "Couldn't build the image data")?;
+        context
+            .put_image_data(&data, 0.0, 0.0)
+            .map_err(|_| "Couldn't draw the image")?;
+        let url = canvas
+            .to_data_url_with_type("image/png")
+            .map_err(|_| "Couldn't encode the PNG")?;
+
+        let link = document
+            .create_element("a")
+            .ok()
+            .and_then(|element| element.dyn_into::<web_sys::HtmlAnchorElement>().ok())
+            .ok_or("Couldn't create a download link")?;
+        link.set_href(&url);
+        link.set_download(file_name);
+        link.click();
+        Ok(())
+    }
+}
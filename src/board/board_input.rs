@@ -1,6 +1,7 @@
 use crate::agent::Agent;
 use crate::board::BoardRenderer;
-use crate::editor::{EditorInput, EditorState};
+use crate::editor::{EditorInput, EditorOperations, EditorState};
+use crate::keyboard::{self, Shortcut};
 use crate::map::GridMap;
 use eframe::egui;
 use egui::Rect;
@@ -13,8 +14,6 @@ impl BoardInput {
     pub fn handle_input(
         ui: &mut egui::Ui,
         rect: Rect,
-        board_side: f32,
-        board_dim: usize,
         response: &egui::Response,
         editor_state: &mut EditorState,
         map: &mut GridMap,
@@ -23,23 +22,45 @@ impl BoardInput {
         selected_cell: &mut Option<(usize, usize)>,
         selected_tile: &mut Option<(usize, usize)>,
     ) {
-        let (pressed, released, pointer_pos) = ui.input(|i| {
+        let (pressed, released, pointer_pos, shift) = ui.input(|i| {
             (
                 i.pointer.any_pressed(),
                 i.pointer.any_released(),
                 i.pointer.interact_pos(),
+                i.modifiers.shift,
             )
         });
 
+        // Undo and redo map edits, unless the keys are meant for a text field
+        if editor_state.edit_mode && !keyboard::text_input_focused(ui.ctx()) {
+            let (undo, redo) =
+                ui.input(|i| (Shortcut::Undo.pressed(i), Shortcut::Redo.pressed(i)));
+            if undo {
+                EditorOperations::undo(editor_state, map, agent);
+            } else if redo {
+                EditorOperations::redo(editor_state, map, agent);
+            }
+        }
+
         // Track drag state for edit mode
-        if editor_state.edit_mode && !editor_state.placing_agent {
+        if editor_state.edit_mode && !editor_state.is_placing() && !editor_state.bucket_fill {
             ui.ctx().data_mut(|data| {
                 let drag_key = egui::Id::new("edit_drag_state");
                 let mut is_dragging = data.get_temp::<bool>(drag_key).unwrap_or(false);
 
                 if pressed && response.hovered() {
+                    // A stroke whose release was missed is closed before the next begins
+                    if is_dragging {
+                        EditorOperations::commit_undo(editor_state, map);
+                    }
                     is_dragging = true;
+                    editor_state.scatter.begin_stroke();
+                    // The whole stroke is one undo step
+                    EditorOperations::push_undo(editor_state, map);
                 } else if released {
+                    if is_dragging {
+                        EditorOperations::commit_undo(editor_state, map);
+                    }
                     is_dragging = false;
                 }
 
@@ -48,8 +69,10 @@ impl BoardInput {
                 // Handle painting during drag
                 if is_dragging {
                     if let Some(pos) = pointer_pos {
-                        if let Some((r, c)) = BoardRenderer::screen_to_grid(pos, rect, board_side, board_dim) {
-                            map.set(c, r, editor_state.selected_edit_tile);
+                        if let Some((r, c)) =
+                            BoardRenderer::screen_to_grid(pos, rect, map)
+                        {
+                            editor_state.paint(map, c, r);
                         }
                     }
                 }
@@ -59,7 +82,8 @@ impl BoardInput {
         // Handle hover and click interactions
         if response.hovered() {
             if let Some(pos) = pointer_pos {
-                if let Some((r, c)) = BoardRenderer::screen_to_grid(pos, rect, board_side, board_dim)
+                if let Some((r, c)) =
+                    BoardRenderer::screen_to_grid(pos, rect, map)
                 {
                     // Update selection highlight in both modes
                     *selected_cell = Some((r, c));
@@ -71,7 +95,7 @@ impl BoardInput {
                             map,
                             agent,
                             pressed,
-                            false, // Don't pass down state since we handle dragging separately
+                            shift, // Shift allows placing the agent on blocked cells
                             c,
                             r,
                         );
@@ -1,5 +1,6 @@
-use crate::agent::Agent;
-use crate::map::{GridMap, TileKind};
+use crate::agent::{Agent, AgentSprite, Direction};
+use crate::board::{OutOfBoundsStyle, RenderOptions, SearchOverlay};
+use crate::map::{GridMap, TileKind, Topology};
 use crate::rendering::*;
 use eframe::egui;
 use egui::{Painter, Rect};
@@ -9,104 +10,157 @@ pub struct BoardRenderer;
 
 impl BoardRenderer {
     /// Render the game board with tiles, grid, agent, and trail
+    ///
+    /// The map keeps square cells and is centered in `board_rect` at its own aspect
+    /// ratio; whatever it leaves uncovered is drawn in the out-of-bounds style. Hex maps
+    /// shift odd rows half a cell right. `planned_path`, if given, is the route still
+    /// ahead of the agent, drawn as a line with arrows over the trail.
     pub fn render(
         painter: &Painter,
-        rect: Rect,
+        board_rect: Rect,
         map: &GridMap,
         agent: &Agent,
-        selected_cell: Option<(usize, usize)>,
-        tree_tex: Option<&egui::TextureHandle>,
+        planned_path: Option<&[(usize, usize)]>,
+        options: &RenderOptions,
     ) {
-        let board_dim = map.width().max(map.height());
-        let n = board_dim as f32;
-        let cell = rect.width() / n;
+        let rect = Self::fit_map_rect(board_rect, map);
+        let cell = rect.height() / map.height().max(1) as f32;
+        let cell_rect = |x: usize, y: usize| Self::cell_rect(rect, cell, map, x, y);
 
         // Background
+        Self::draw_out_of_bounds(painter, board_rect, cell, options.out_of_bounds);
         painter.rect_filled(rect, 0.0, egui::Color32::from_rgb(240, 240, 240));
 
         // Grid lines
-        let line_color = egui::Color32::from_gray(180);
-        let stroke = egui::Stroke {
-            width: 1.0,
-            color: line_color,
-        };
-        for i in 0..=board_dim {
-            let x = rect.left() + (i as f32) * cell;
-            let y = rect.top() + (i as f32) * cell;
-            painter.line_segment(
-                [egui::pos2(x, rect.top()), egui::pos2(x, rect.bottom())],
-                stroke,
-            );
-            painter.line_segment(
-                [egui::pos2(rect.left(), y), egui::pos2(rect.right(), y)],
-                stroke,
-            );
+        let grid = options.grid_lines;
+        if grid.visible && grid.width > 0.0 {
+            let stroke = egui::Stroke {
+                width: grid.width,
+                color: grid.color,
+            };
+            if map.topology() == Topology::Square {
+                for i in 0..=map.width() {
+                    let x = rect.left() + (i as f32) * cell;
+                    painter.line_segment(
+                        [egui::pos2(x, rect.top()), egui::pos2(x, rect.bottom())],
+                        stroke,
+                    );
+                }
+                for i in 0..=map.height() {
+                    let y = rect.top() + (i as f32) * cell;
+                    painter.line_segment(
+                        [egui::pos2(rect.left(), y), egui::pos2(rect.right(), y)],
+                        stroke,
+                    );
+                }
+            } else {
+                // Rows don't line up, so outline each cell instead of drawing long lines
+                for y in 0..map.height() {
+                    for x in 0..map.width() {
+                        painter.rect_stroke(cell_rect(x, y), 0.0, stroke);
+                    }
+                }
+            }
         }
 
-        // Paint tiles
+        // Paint tiles, then the purely visual decoration layer over them
         for y in 0..map.height() {
             for x in 0..map.width() {
-                let x0 = rect.left() + (x as f32) * cell;
-                let y0 = rect.top() + (y as f32) * cell;
-                let rcell = egui::Rect::from_min_size(egui::pos2(x0, y0), egui::vec2(cell, cell));
+                let rcell = cell_rect(x, y);
                 if let Some(kind) = map.get(x, y) {
-                    match kind {
-                        TileKind::Empty => {}
-                        TileKind::Grass => draw_grass_tile(painter, rcell),
-                        TileKind::Water => draw_water_tile(painter, rcell),
-                        TileKind::Sand => draw_sand_tile(painter, rcell),
-                        TileKind::Wall => draw_wall_tile(painter, rcell),
-                        TileKind::Trail => {
-                            // Legacy trail tile - should not exist in new system
-                            painter.rect_filled(
-                                rcell.shrink(4.0),
-                                2.0,
-                                egui::Color32::from_rgba_premultiplied(255, 200, 0, 100),
-                            );
-                        }
-                        TileKind::Tree => {
-                            if let Some(tex) = tree_tex {
-                                draw_tree_sprite(painter, rcell, tex);
-                            } else {
-                                draw_grass_tile(painter, rcell);
-                            }
-                        }
-                        TileKind::Custom(code) => {
-                            let r = ((code >> 16) & 0xFF) as u8;
-                            let g = ((code >> 8) & 0xFF) as u8;
-                            let b = (code & 0xFF) as u8;
-                            painter.rect_filled(
-                                rcell.shrink(2.0),
-                                0.0,
-                                egui::Color32::from_rgb(r, g, b),
-                            );
-                        }
-                    }
+                    Self::draw_tile_art(painter, rcell, kind, options);
+                }
+                if let Some(kind) = map.decoration(x, y) {
+                    Self::draw_decoration(painter, rcell, kind, options);
+                }
+            }
+        }
+
+        // Subtle elevation shading: high ground lighter, low ground darker
+        if let Some((low, high)) = map.elevation_range()
+            && high > low
+        {
+            for y in 0..map.height() {
+                for x in 0..map.width() {
+                    let shade = Self::elevation_shade(map.elevation(x, y), low, high);
+                    painter.rect_filled(cell_rect(x, y), 0.0, shade);
                 }
             }
         }
 
+        // Pockets the editor's validation couldn't reach
+        for &(x, y) in options.unreachable.iter().filter(|&&(x, y)| map.in_bounds(x, y)) {
+            painter.rect(
+                cell_rect(x, y).shrink(1.5),
+                0.0,
+                egui::Color32::from_rgba_unmultiplied(220, 40, 40, 70),
+                egui::Stroke::new(1.5, egui::Color32::from_rgb(220, 40, 40)),
+            );
+        }
+
         // Draw agent trail based on movement history
-        for &(trail_x, trail_y) in agent.get_movement_history() {
+        let trail = if options.show_trail { agent.get_movement_history() } else { &[] };
+        for &(trail_x, trail_y) in trail {
             if trail_x < map.width() && trail_y < map.height() {
-                let x0 = rect.left() + (trail_x as f32) * cell;
-                let y0 = rect.top() + (trail_y as f32) * cell;
-                let rcell = egui::Rect::from_min_size(egui::pos2(x0, y0), egui::vec2(cell, cell));
                 painter.rect_filled(
-                    rcell.shrink(4.0),
+                    cell_rect(trail_x, trail_y).shrink(4.0),
                     2.0,
                     egui::Color32::from_rgba_premultiplied(255, 200, 0, 100),
                 );
             }
         }
 
+        // Fog of war: dim what the agent hasn't seen yet
+        if agent.fog_of_war() {
+            let fog = egui::Color32::from_black_alpha(150);
+            for y in 0..map.height() {
+                for x in (0..map.width()).filter(|&x| !agent.explored().contains(&(x, y))) {
+                    painter.rect_filled(cell_rect(x, y), 0.0, fog);
+                }
+            }
+        }
+
+        if let Some(path) = planned_path {
+            Self::draw_planned_path(painter, rect, cell, map, path);
+        }
+
+        if let Some(search) = &options.search {
+            Self::draw_search_overlay(painter, rect, cell, map, search);
+        }
+
+        Self::draw_patrollers(painter, rect, cell, map, options);
+
+        // Spawn marker
+        if let Some((sx, sy)) = options.spawn
+            && map.in_bounds(sx, sy)
+        {
+            let rcell = cell_rect(sx, sy);
+            painter.circle_stroke(
+                rcell.center(),
+                cell * 0.38,
+                egui::Stroke::new(2.0, egui::Color32::from_rgb(40, 160, 90)),
+            );
+            painter.text(
+                rcell.center(),
+                egui::Align2::CENTER_CENTER,
+                "S",
+                egui::FontId::proportional(cell * 0.45),
+                egui::Color32::from_rgb(40, 160, 90),
+            );
+        }
+
+        // Goal marker (drawn under the agent so the agent stays visible when it arrives)
+        if let Some((gx, gy)) = options.goal
+            && gx < map.width()
+            && gy < map.height()
+        {
+            Self::draw_goal_flag(painter, cell_rect(gx, gy));
+        }
+
         // Selection highlight
-        if let Some((sr, sc)) = selected_cell {
-            let x0 = rect.left() + (sc as f32) * cell;
-            let y0 = rect.top() + (sr as f32) * cell;
-            let rcell = egui::Rect::from_min_size(egui::pos2(x0, y0), egui::vec2(cell, cell));
+        if let Some((sr, sc)) = options.selected_cell {
             painter.rect_stroke(
-                rcell.shrink(1.0),
+                cell_rect(sc, sr).shrink(1.0),
                 0.0,
                 egui::Stroke {
                     width: 2.0,
@@ -117,37 +171,462 @@ impl BoardRenderer {
 
         // Draw agent
         if agent.x < map.width() && agent.y < map.height() {
-            let x0 = rect.left() + (agent.x as f32) * cell;
-            let y0 = rect.top() + (agent.y as f32) * cell;
-            let center = egui::pos2(x0 + cell * 0.5, y0 + cell * 0.6);
-            painter.circle_filled(center, cell * 0.18, egui::Color32::from_rgb(230, 70, 50));
-            painter.text(
-                egui::pos2(center.x, y0 + cell * 0.15),
-                egui::Align2::CENTER_CENTER,
-                &agent.name,
-                egui::FontId::proportional((cell * 0.32).max(10.0)),
-                egui::Color32::BLACK,
+            let (w, h) = agent.size();
+            let egui::Pos2 { x: x0, y: y0 } = cell_rect(agent.x, agent.y).min;
+            let color = agent.color;
+            // Multi-cell agents get an outline around the whole footprint
+            if (w, h) != (1, 1) {
+                let footprint = egui::Rect::from_min_size(
+                    egui::pos2(x0, y0),
+                    egui::vec2(cell * w as f32, cell * h as f32),
+                );
+                painter.rect(
+                    footprint.shrink(2.0),
+                    4.0,
+                    color.gamma_multiply(0.2),
+                    egui::Stroke::new(2.0, color),
+                );
+            }
+            let span = cell * (w.min(h) as f32);
+            let center = egui::pos2(
+                x0 + cell * w as f32 * 0.5,
+                y0 + cell * h as f32 * 0.5 + span * 0.1,
             );
+            Self::draw_agent_body(painter, agent, center, span, options.agent_tex);
+            if let Some(progress) = options.agent_pulse {
+                Self::draw_pulse(painter, center, span, progress, color);
+            }
+
+            let label_pos = egui::pos2(center.x, center.y - span * 0.35);
+            let font = egui::FontId::proportional((cell * 0.32).max(10.0));
+            if agent.label_background {
+                let galley =
+                    painter.layout_no_wrap(agent.name.clone(), font, Self::contrast_text(color));
+                let bg =
+                    egui::Rect::from_center_size(label_pos, galley.size() + egui::vec2(6.0, 2.0));
+                painter.rect_filled(bg, 3.0, color);
+                painter.galley(bg.center() - galley.size() * 0.5, galley, egui::Color32::BLACK);
+            } else {
+                painter.text(
+                    label_pos,
+                    egui::Align2::CENTER_CENTER,
+                    &agent.name,
+                    font,
+                    egui::Color32::BLACK,
+                );
+            }
         }
     }
 
-    /// Convert screen position to grid coordinates (hit testing)
-    pub fn screen_to_grid(
-        pos: egui::Pos2,
+    /// Rings expanding out from the agent and fading, three times over the pulse
+    fn draw_pulse(
+        painter: &Painter,
+        center: egui::Pos2,
+        span: f32,
+        progress: f32,
+        color: egui::Color32,
+    ) {
+        let phase = (progress * 3.0).fract();
+        let fade = 1.0 - phase;
+        painter.circle_stroke(
+            center,
+            span * (0.5 + phase * 1.5),
+            egui::Stroke::new(3.0 * fade + 1.0, color.gamma_multiply(fade)),
+        );
+        painter.circle_stroke(
+            center,
+            span * (0.5 + phase * 1.5) + 3.0,
+            egui::Stroke::new(1.5, egui::Color32::WHITE.gamma_multiply(fade)),
+        );
+    }
+
+    /// Overlay for a cell at `height` on a map spanning `low..=high`; mid-height cells are
+    /// left untouched
+    fn elevation_shade(height: i32, low: i32, high: i32) -> egui::Color32 {
+        let t = (height as f32 - low as f32) / (high as f32 - low as f32) - 0.5;
+        let alpha = (t.abs() * 2.0 * 70.0) as u8;
+        if t > 0.0 {
+            egui::Color32::from_white_alpha(alpha)
+        } else {
+            egui::Color32::from_black_alpha(alpha)
+        }
+    }
+
+    /// Terrain art for one cell
+    fn draw_tile_art(painter: &Painter, rcell: Rect, kind: &TileKind, options: &RenderOptions) {
+        let palette = options.palette;
+        match kind {
+            TileKind::Empty => {}
+            TileKind::Grass => draw_grass_tile(painter, rcell, palette),
+            TileKind::Water => draw_water_tile(painter, rcell, options.animation_frame, palette),
+            TileKind::Sand => draw_sand_tile(painter, rcell, palette),
+            TileKind::Wall => draw_wall_tile(painter, rcell, palette),
+            TileKind::Trail => {
+                // Only present when the agent's trail-tile option is on
+                painter.rect_filled(
+                    rcell.shrink(4.0),
+                    2.0,
+                    egui::Color32::from_rgba_premultiplied(255, 200, 0, 100),
+                );
+            }
+            TileKind::Tree => {
+                if let Some(tex) = options.tree_tex {
+                    draw_tree_sprite(painter, rcell, tex);
+                } else {
+                    draw_grass_tile(painter, rcell, palette);
+                }
+            }
+            TileKind::Portal(id) => draw_portal_tile(painter, rcell, *id, palette),
+            TileKind::Fuel => draw_fuel_tile(painter, rcell, palette),
+            TileKind::Custom(code) => {
+                let r = ((code >> 16) & 0xFF) as u8;
+                let g = ((code >> 8) & 0xFF) as u8;
+                let b = (code & 0xFF) as u8;
+                painter.rect_filled(rcell.shrink(2.0), 0.0, egui::Color32::from_rgb(r, g, b));
+            }
+        }
+    }
+
+    /// Decoration over a cell's terrain: sprites at full size, tiles as a smaller inset
+    /// so the terrain underneath stays readable
+    fn draw_decoration(painter: &Painter, rcell: Rect, kind: &TileKind, options: &RenderOptions) {
+        match kind {
+            TileKind::Tree => match options.tree_tex {
+                Some(tex) => draw_tree_sprite(painter, rcell, tex),
+                None => {
+                    painter.circle_filled(
+                        rcell.center(),
+                        rcell.width() * 0.3,
+                        tile_base_color(kind, options.palette),
+                    );
+                }
+            },
+            _ => Self::draw_tile_art(painter, rcell.shrink(rcell.width() * 0.2), kind, options),
+        }
+    }
+
+    /// The agent marker itself, in the agent's chosen sprite
+    fn draw_agent_body(
+        painter: &Painter,
+        agent: &Agent,
+        center: egui::Pos2,
+        span: f32,
+        agent_tex: Option<&egui::TextureHandle>,
+    ) {
+        let color = agent.color;
+        match (agent.sprite, agent_tex) {
+            (AgentSprite::Triangle, _) => {
+                let forward = match agent.facing() {
+                    Direction::Up => egui::vec2(0.0, -1.0),
+                    Direction::Down => egui::vec2(0.0, 1.0),
+                    Direction::Left => egui::vec2(-1.0, 0.0),
+                    Direction::Right => egui::vec2(1.0, 0.0),
+                    Direction::UpLeft => egui::vec2(-0.5, -0.866),
+                    Direction::UpRight => egui::vec2(0.5, -0.866),
+                    Direction::DownLeft => egui::vec2(-0.5, 0.866),
+                    Direction::DownRight => egui::vec2(0.5, 0.866),
+                };
+                let side = forward.rot90();
+                let r = span * 0.24;
+                let points = vec![
+                    center + forward * r,
+                    center - forward * r * 0.7 + side * r * 0.8,
+                    center - forward * r * 0.7 - side * r * 0.8,
+                ];
+                painter.add(egui::Shape::convex_polygon(
+                    points,
+                    color,
+                    egui::Stroke::new(1.0, color.gamma_multiply(0.6)),
+                ));
+            }
+            (AgentSprite::Robot, Some(tex)) => {
+                draw_agent_sprite(painter, center, span * 0.5, tex, color);
+            }
+            _ => {
+                painter.circle_filled(center, span * 0.18, color);
+            }
+        }
+    }
+
+    /// Faint route dots and a diamond for each patroller, plus any route being recorded
+    fn draw_patrollers(
+        painter: &Painter,
         rect: Rect,
-        board_side: f32,
-        board_dim: usize,
-    ) -> Option<(usize, usize)> {
-        let cell = board_side / (board_dim as f32);
-        let rel_x = (pos.x - rect.left()).clamp(0.0, board_side - 0.001);
-        let rel_y = (pos.y - rect.top()).clamp(0.0, board_side - 0.001);
-        let c = (rel_x / cell).floor() as usize;
-        let r = (rel_y / cell).floor() as usize;
-
-        if r < board_dim && c < board_dim {
-            Some((r, c))
+        cell: f32,
+        map: &GridMap,
+        options: &RenderOptions,
+    ) {
+        let center = |(x, y): (usize, usize)| Self::cell_rect(rect, cell, map, x, y).center();
+        let color = egui::Color32::from_rgb(170, 30, 60);
+        for patroller in options.patrollers {
+            for &cellpos in patroller.path() {
+                if map.in_bounds(cellpos.0, cellpos.1) {
+                    painter.circle_filled(center(cellpos), cell * 0.06, color.gamma_multiply(0.4));
+                }
+            }
+            let (x, y) = patroller.pos();
+            if !map.in_bounds(x, y) {
+                continue;
+            }
+            let c = center((x, y));
+            let r = cell * 0.32;
+            painter.add(egui::Shape::convex_polygon(
+                vec![
+                    c + egui::vec2(0.0, -r),
+                    c + egui::vec2(r, 0.0),
+                    c + egui::vec2(0.0, r),
+                    c + egui::vec2(-r, 0.0),
+                ],
+                color,
+                egui::Stroke::new(1.5, egui::Color32::WHITE),
+            ));
+        }
+        if let Some(waypoints) = options.patrol_draft {
+            for (i, &waypoint) in waypoints.iter().enumerate() {
+                painter.circle_stroke(center(waypoint), cell * 0.3, egui::Stroke::new(2.0, color));
+                painter.text(
+                    center(waypoint),
+                    egui::Align2::CENTER_CENTER,
+                    (i + 1).to_string(),
+                    egui::FontId::proportional((cell * 0.4).max(8.0)),
+                    color,
+                );
+            }
+        }
+    }
+
+    /// Black or white, whichever reads better on `background`
+    fn contrast_text(background: egui::Color32) -> egui::Color32 {
+        let luma = 0.299 * background.r() as f32
+            + 0.587 * background.g() as f32
+            + 0.114 * background.b() as f32;
+        if luma > 140.0 {
+            egui::Color32::BLACK
         } else {
-            None
+            egui::Color32::WHITE
+        }
+    }
+
+    /// Shade expanded cells, oldest faintest, and outline the cell being expanded
+    fn draw_search_overlay(
+        painter: &Painter,
+        rect: Rect,
+        cell: f32,
+        map: &GridMap,
+        search: &SearchOverlay,
+    ) {
+        let cell_rect = |(x, y): (usize, usize)| Self::cell_rect(rect, cell, map, x, y);
+        let count = search.expanded.len();
+        for (i, &(x, y)) in search.expanded.iter().enumerate() {
+            if !map.in_bounds(x, y) {
+                continue;
+            }
+            // Recent expansions are more opaque so the frontier stands out
+            let age = (i + 1) as f32 / count as f32;
+            let alpha = (40.0 + 110.0 * age) as u8;
+            painter.rect_filled(
+                cell_rect((x, y)).shrink(1.0),
+                0.0,
+                egui::Color32::from_rgba_unmultiplied(60, 120, 220, alpha),
+            );
+        }
+        if search.path.is_none()
+            && let Some(&current) = search.expanded.last()
+        {
+            painter.rect_stroke(
+                cell_rect(current).shrink(1.0),
+                0.0,
+                egui::Stroke::new(2.0, egui::Color32::from_rgb(255, 140, 0)),
+            );
+        }
+        if let Some(path) = search.path {
+            let points: Vec<egui::Pos2> = path.iter().map(|&c| cell_rect(c).center()).collect();
+            painter.add(egui::Shape::line(
+                points,
+                egui::Stroke::new(3.0, egui::Color32::from_rgb(255, 140, 0)),
+            ));
+        }
+    }
+
+    /// Translucent purple line through the cells of `path`, with an arrow on every step
+    ///
+    /// Only neighboring cells are joined, so the line breaks where a portal teleports.
+    fn draw_planned_path(
+        painter: &Painter,
+        rect: Rect,
+        cell: f32,
+        map: &GridMap,
+        path: &[(usize, usize)],
+    ) {
+        let center = |(x, y): (usize, usize)| Self::cell_rect(rect, cell, map, x, y).center();
+        let color = egui::Color32::from_rgba_unmultiplied(150, 60, 220, 160);
+        let stroke = egui::Stroke::new((cell * 0.08).max(1.5), color);
+        let adjacent = |(ax, ay): (usize, usize), (bx, by): (usize, usize)| {
+            (ax, ay) != (bx, by) && ax.abs_diff(bx) <= 1 && ay.abs_diff(by) <= 1
+        };
+        for step in path.windows(2) {
+            let (from, to) = (step[0], step[1]);
+            if !map.in_bounds(from.0, from.1) || !map.in_bounds(to.0, to.1) || !adjacent(from, to)
+            {
+                continue;
+            }
+            let (a, b) = (center(from), center(to));
+            painter.line_segment([a, b], stroke);
+            // Arrowhead just past the middle of the step, pointing the way it goes
+            let forward = (b - a).normalized();
+            let side = forward.rot90() * cell * 0.12;
+            let tip = a + (b - a) * 0.6;
+            let back = tip - forward * cell * 0.2;
+            painter.add(egui::Shape::convex_polygon(
+                vec![tip, back + side, back - side],
+                color,
+                egui::Stroke::NONE,
+            ));
+        }
+        if path.len() > 1
+            && let Some(&end) = path.last()
+            && map.in_bounds(end.0, end.1)
+        {
+            painter.circle_stroke(center(end), cell * 0.22, stroke);
+        }
+    }
+
+    /// Fill the part of `rect` the map doesn't cover (the map is drawn over it afterwards)
+    fn draw_out_of_bounds(painter: &Painter, rect: Rect, cell: f32, style: OutOfBoundsStyle) {
+        if style == OutOfBoundsStyle::Plain {
+            painter.rect_filled(rect, 0.0, egui::Color32::from_rgb(240, 240, 240));
+            return;
+        }
+        painter.rect_filled(rect, 0.0, egui::Color32::from_gray(70));
+        if style == OutOfBoundsStyle::Hatched {
+            let stroke = egui::Stroke::new(1.0, egui::Color32::from_gray(110));
+            let spacing = (cell * 0.5).max(4.0);
+            let clipped = painter.with_clip_rect(rect.intersect(painter.clip_rect()));
+            let mut offset = -rect.height();
+            while offset < rect.width() {
+                let start = egui::pos2(rect.left() + offset, rect.bottom());
+                let end = start + egui::vec2(rect.height(), -rect.height());
+                clipped.line_segment([start, end], stroke);
+                offset += spacing;
+            }
+        }
+    }
+
+    /// Draw a small flag marking the goal cell
+    fn draw_goal_flag(painter: &Painter, rcell: Rect) {
+        let flag_color = egui::Color32::from_rgb(220, 40, 120);
+        painter.rect_stroke(rcell.shrink(2.0), 2.0, egui::Stroke::new(2.0, flag_color));
+
+        let pole_x = rcell.left() + rcell.width() * 0.35;
+        let top = rcell.top() + rcell.height() * 0.2;
+        let bottom = rcell.bottom() - rcell.height() * 0.15;
+        painter.line_segment(
+            [egui::pos2(pole_x, top), egui::pos2(pole_x, bottom)],
+            egui::Stroke::new(1.5, egui::Color32::from_gray(40)),
+        );
+        painter.add(egui::Shape::convex_polygon(
+            vec![
+                egui::pos2(pole_x, top),
+                egui::pos2(rcell.right() - rcell.width() * 0.2, top + rcell.height() * 0.15),
+                egui::pos2(pole_x, top + rcell.height() * 0.3),
+            ],
+            flag_color,
+            egui::Stroke::NONE,
+        ));
+    }
+
+    /// Largest rect with the map's aspect ratio (square cells), centered in `rect`
+    ///
+    /// Hex maps are half a cell wider to make room for the shifted odd rows.
+    pub fn fit_map_rect(rect: Rect, map: &GridMap) -> Rect {
+        let (w, h) = (Self::columns(map), map.height().max(1) as f32);
+        let cell = (rect.width() / w).min(rect.height() / h);
+        Rect::from_center_size(rect.center(), egui::vec2(w * cell, h * cell))
+    }
+
+    /// Map width in cells as drawn
+    pub fn columns(map: &GridMap) -> f32 {
+        let width = map.width().max(1) as f32;
+        match map.topology() {
+            Topology::Square => width,
+            Topology::Hex if map.height() > 1 => width + 0.5,
+            Topology::Hex => width,
+        }
+    }
+
+    /// Half a cell for odd rows of a hex map, otherwise nothing
+    fn row_shift(map: &GridMap, y: usize) -> f32 {
+        if map.topology() == Topology::Hex && y % 2 == 1 {
+            0.5
+        } else {
+            0.0
+        }
+    }
+
+    /// Screen rect of cell (x, y) for a map drawn into `map_rect` with `cell`-sized cells
+    pub fn cell_rect(map_rect: Rect, cell: f32, map: &GridMap, x: usize, y: usize) -> Rect {
+        Rect::from_min_size(
+            egui::pos2(
+                map_rect.left() + (x as f32 + Self::row_shift(map, y)) * cell,
+                map_rect.top() + y as f32 * cell,
+            ),
+            egui::vec2(cell, cell),
+        )
+    }
+
+    /// Convert screen position to grid coordinates (hit testing)
+    ///
+    /// `rect` is the rect passed to `render`; positions outside the map (including the
+    /// half-cell gaps at the ends of hex rows) return None.
+    pub fn screen_to_grid(pos: egui::Pos2, rect: Rect, map: &GridMap) -> Option<(usize, usize)> {
+        let (width, height) = (map.width(), map.height());
+        if width == 0 || height == 0 {
+            return None;
+        }
+        let map_rect = Self::fit_map_rect(rect, map);
+        if !map_rect.contains(pos) {
+            return None;
+        }
+        let cell = map_rect.height() / height as f32;
+        let r = (((pos.y - map_rect.top()) / cell).floor() as usize).min(height - 1);
+        let column = (pos.x - map_rect.left()) / cell - Self::row_shift(map, r);
+        if column < 0.0 {
+            return None;
+        }
+        let c = column.floor() as usize;
+        if c >= width {
+            return None;
+        }
+        Some((r, c))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wide_map_hit_test_skips_the_letterbox() {
+        let map = GridMap::new(40, 10, TileKind::Grass);
+        let board = Rect::from_min_size(egui::pos2(0.0, 0.0), egui::vec2(400.0, 400.0));
+        let map_rect = BoardRenderer::fit_map_rect(board, &map);
+        assert_eq!(map_rect, Rect::from_min_max(egui::pos2(0.0, 150.0), egui::pos2(400.0, 250.0)));
+
+        let hit = |x: f32, y: f32| BoardRenderer::screen_to_grid(egui::pos2(x, y), board, &map);
+        assert_eq!(hit(5.0, 155.0), Some((0, 0)));
+        assert_eq!(hit(125.0, 185.0), Some((3, 12)));
+        assert_eq!(hit(395.0, 245.0), Some((9, 39)));
+        // Above and below the map is letterbox, even inside the board rect
+        assert_eq!(hit(200.0, 100.0), None);
+        assert_eq!(hit(200.0, 149.0), None);
+        assert_eq!(hit(200.0, 251.0), None);
+        assert_eq!(hit(200.0, 390.0), None);
+
+        // Every cell's center maps back to that cell
+        let cell = map_rect.height() / 10.0;
+        for (x, y) in [(0, 9), (39, 0), (20, 5)] {
+            let center = BoardRenderer::cell_rect(map_rect, cell, &map, x, y).center();
+            assert_eq!(BoardRenderer::screen_to_grid(center, board, &map), Some((y, x)));
         }
     }
 }
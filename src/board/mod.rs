@@ -1,5 +1,11 @@
+mod board_image;
 mod board_input;
 mod board_renderer;
+mod render_options;
+mod viewport;
 
+pub use board_image::BoardImage;
 pub use board_input::BoardInput;
 pub use board_renderer::BoardRenderer;
+pub use render_options::{GridLineStyle, OutOfBoundsStyle, RenderOptions, SearchOverlay};
+pub use viewport::Viewport;
@@ -0,0 +1,115 @@
+use crate::patrol::Patroller;
+use crate::rendering::TilePalette;
+use eframe::egui;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Styling for the grid lines drawn between cells
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GridLineStyle {
+    /// Whether grid lines are drawn at all
+    pub visible: bool,
+    #[serde(with = "rgba")]
+    pub color: egui::Color32,
+    /// Line thickness in points
+    pub width: f32,
+}
+
+impl Default for GridLineStyle {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            color: egui::Color32::from_gray(180),
+            width: 1.0,
+        }
+    }
+}
+
+/// (De)serialize a color as `[r, g, b, a]`, since egui is built without its serde feature
+mod rgba {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        color: &egui::Color32,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        color.to_array().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<egui::Color32, D::Error> {
+        let [r, g, b, a] = <[u8; 4]>::deserialize(deserializer)?;
+        Ok(egui::Color32::from_rgba_premultiplied(r, g, b, a))
+    }
+}
+
+/// How the board area the map doesn't cover (letterboxing around its aspect ratio) is drawn
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OutOfBoundsStyle {
+    /// Dark fill with diagonal hatching
+    #[default]
+    Hatched,
+    /// Dark fill only
+    Solid,
+    /// Same as the board background, like before
+    Plain,
+}
+
+impl OutOfBoundsStyle {
+    pub const ALL: [OutOfBoundsStyle; 3] = [Self::Hatched, Self::Solid, Self::Plain];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Hatched => "Hatched",
+            Self::Solid => "Solid",
+            Self::Plain => "Plain",
+        }
+    }
+}
+
+/// Optional toggles and overlays for board rendering
+///
+/// Built fresh each frame from `MyApp` state, so new overlays can be added here
+/// without growing `BoardRenderer::render`'s parameter list.
+#[derive(Clone, Copy, Default)]
+pub struct RenderOptions<'a> {
+    /// Cell to highlight as (row, col)
+    pub selected_cell: Option<(usize, usize)>,
+    /// Tree sprite texture (falls back to grass when absent)
+    pub tree_tex: Option<&'a egui::TextureHandle>,
+    /// Robot agent sprite texture (falls back to a circle when absent)
+    pub agent_tex: Option<&'a egui::TextureHandle>,
+    pub grid_lines: GridLineStyle,
+    /// Draw the yellow trail of recent positions (trail tiles in the map are unaffected)
+    pub show_trail: bool,
+    /// Navigation goal marker as (x, y)
+    pub goal: Option<(usize, usize)>,
+    /// Agent spawn cell as (x, y), shown while editing
+    pub spawn: Option<(usize, usize)>,
+    /// Frame counter from the `AnimationController`, drives animated tiles
+    pub animation_frame: u64,
+    /// Progress (0..1) of a highlight pulse around the agent, while one is running
+    pub agent_pulse: Option<f32>,
+    /// Teach-mode search visualization
+    pub search: Option<SearchOverlay<'a>>,
+    /// Colors used for the tile kinds
+    pub palette: TilePalette,
+    /// Appearance of the part of the board rect the map doesn't cover
+    pub out_of_bounds: OutOfBoundsStyle,
+    /// Moving obstacles, drawn with their routes
+    pub patrollers: &'a [Patroller],
+    /// Waypoints of a patrol route being recorded
+    pub patrol_draft: Option<&'a [(usize, usize)]>,
+    /// Traversable cells the editor's map validation found unreachable
+    pub unreachable: &'a [(usize, usize)],
+}
+
+/// Cells a pathfinder has expanded so far, drawn over the tiles in teach mode
+#[derive(Clone, Copy)]
+pub struct SearchOverlay<'a> {
+    /// Expansion order; the last cell is the one being expanded now
+    pub expanded: &'a [(usize, usize)],
+    /// Final path, once the search has been fully revealed
+    pub path: Option<&'a [(usize, usize)]>,
+}
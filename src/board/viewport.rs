@@ -0,0 +1,78 @@
+use eframe::egui;
+use egui::{Pos2, Rect};
+
+/// Zoom and pan state of the main board view
+///
+/// The board is drawn into a virtual rect `zoom` times the size of the visible area and
+/// clipped, so rendering and hit-testing work unchanged on the virtual rect. Fractions
+/// are per axis, so the view doesn't have to be square.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    zoom: f32,
+    /// Point of the board shown at the middle of the view, as fractions of the board (0..1)
+    center: Pos2,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self {
+            zoom: 1.0,
+            center: egui::pos2(0.5, 0.5),
+        }
+    }
+}
+
+impl Viewport {
+    pub const MIN_ZOOM: f32 = 1.0;
+    pub const MAX_ZOOM: f32 = 6.0;
+
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    pub fn is_zoomed(&self) -> bool {
+        self.zoom > Self::MIN_ZOOM
+    }
+
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = zoom.clamp(Self::MIN_ZOOM, Self::MAX_ZOOM);
+        self.center_on(self.center);
+    }
+
+    /// Pan so `center` (board fractions) is in the middle, keeping the view on the board
+    pub fn center_on(&mut self, center: Pos2) {
+        let half = 0.5 / self.zoom;
+        self.center = egui::pos2(
+            center.x.clamp(half, 1.0 - half),
+            center.y.clamp(half, 1.0 - half),
+        );
+    }
+
+    /// Pan a `fraction` (0..1) of the remaining way toward `target`, for smooth tracking
+    ///
+    /// Returns true while the view is still moving, false once it has arrived.
+    pub fn pan_towards(&mut self, target: Pos2, fraction: f32) -> bool {
+        let mut arrived = *self;
+        arrived.center_on(target);
+        let remaining = arrived.center - self.center;
+        if remaining.length() < 1e-3 {
+            self.center = arrived.center;
+            return false;
+        }
+        self.center_on(self.center + remaining * fraction.clamp(0.0, 1.0));
+        true
+    }
+
+    /// Virtual rect the whole board is drawn into for a view occupying `visible`
+    pub fn board_rect(&self, visible: Rect) -> Rect {
+        let size = visible.size() * self.zoom;
+        let min = visible.center() - egui::vec2(self.center.x * size.x, self.center.y * size.y);
+        Rect::from_min_size(min, size)
+    }
+
+    /// Part of the board that is on screen, as fractions of the board (0..1)
+    pub fn visible_fraction(&self) -> Rect {
+        let half = 0.5 / self.zoom;
+        Rect::from_center_size(self.center, egui::vec2(half * 2.0, half * 2.0))
+    }
+}
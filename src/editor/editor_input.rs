@@ -1,5 +1,5 @@
-use crate::agent::{Agent, LogEntry};
-use crate::editor::EditorState;
+use crate::agent::{Agent, ErrorKind, LogEntry};
+use crate::editor::{EditorOperations, EditorState};
 use crate::map::GridMap;
 
 /// Handles input events for editor mode
@@ -12,25 +12,120 @@ impl EditorInput {
         map: &mut GridMap,
         agent: &mut Agent,
         pressed: bool,
-        _down: bool,
+        force_placement: bool,
         col: usize,
         row: usize,
     ) {
         if editor_state.placing_agent {
             // Agent placement mode - only react to press (not drag)
             if pressed {
-                agent.x = col;
-                agent.y = row;
-                agent.log(LogEntry::Info(format!(
-                    "Agent moved to position ({}, {})",
-                    col, row
-                )));
+                let before = (agent.pos(), agent.spawn());
+                Self::place_agent(map, agent, force_placement, col, row);
+                if (agent.pos(), agent.spawn()) != before {
+                    editor_state.previous_agent_placement = Some(before);
+                }
                 editor_state.exit_placement_mode();
             }
+        } else if editor_state.placing_spawn && pressed {
+            Self::set_spawn(map, agent, force_placement, col, row);
+            editor_state.exit_placement_mode();
+        } else if editor_state.placing_goal && pressed {
+            Self::set_goal(agent, col, row);
+            editor_state.exit_placement_mode();
+        } else if editor_state.bucket_fill && pressed {
+            EditorOperations::push_undo(editor_state, map);
+            EditorOperations::flood_fill(map, col, row, editor_state.selected_edit_tile);
+            EditorOperations::commit_undo(editor_state, map);
+            agent.relocate_if_blocked(map);
         }
         // Note: Tile painting during dragging is now handled in BoardInput::handle_input
     }
 
+    /// Place the agent at the clicked cell, snapping to the nearest open cell if it's blocked
+    /// `force` (Shift held) places it anyway, for testing stuck scenarios
+    /// The placed position becomes the agent's spawn point
+    fn place_agent(map: &GridMap, agent: &mut Agent, force: bool, col: usize, row: usize) {
+        Self::snap_agent(map, agent, force, col, row);
+        agent.set_spawn(agent.x, agent.y);
+    }
+
+    /// Put the agent (and its spawn) back where they were before the last placement
+    ///
+    /// Only one placement is remembered; a position the current map no longer has is
+    /// dropped instead of restored.
+    pub fn undo_agent_placement(editor_state: &mut EditorState, map: &GridMap, agent: &mut Agent) {
+        let Some(((x, y), (sx, sy))) = editor_state.previous_agent_placement.take() else {
+            return;
+        };
+        if !map.in_bounds(x, y) || !map.in_bounds(sx, sy) {
+            agent.log_error(ErrorKind::Validation, format!(
+                "Can't undo placement: ({}, {}) is no longer on the map",
+                x, y
+            ));
+            return;
+        }
+        agent.set_pos(x, y);
+        agent.set_spawn(sx, sy);
+        agent.log(LogEntry::Info(format!("Agent placement undone; back at ({}, {})", x, y)));
+    }
+
+    /// Set the spawn cell without moving the agent; blocked cells need `force` (Shift)
+    fn set_spawn(map: &GridMap, agent: &mut Agent, force: bool, col: usize, row: usize) {
+        if !force && !agent.can_enter(map, col, row) {
+            let tile = map.get(col, row).map(|t| t.name()).unwrap_or("unknown");
+            agent.log_error(ErrorKind::Validation, format!(
+                "Spawn not set: cell ({}, {}) is blocked by {}. Hold Shift to use it anyway.",
+                col, row, tile
+            ));
+            return;
+        }
+        agent.set_spawn(col, row);
+        agent.log(LogEntry::Info(format!("Spawn set to ({}, {})", col, row)));
+    }
+
+    /// Set the goal cell, or clear it when the current goal is clicked
+    fn set_goal(agent: &mut Agent, col: usize, row: usize) {
+        if agent.goal() == Some((col, row)) {
+            agent.set_goal(None);
+            agent.log(LogEntry::Info("Goal cleared".to_string()));
+        } else {
+            agent.set_goal(Some((col, row)));
+            agent.log(LogEntry::Info(format!("Goal set to ({}, {})", col, row)));
+        }
+    }
+
+    fn snap_agent(map: &GridMap, agent: &mut Agent, force: bool, col: usize, row: usize) {
+        if force || agent.can_enter(map, col, row) {
+            agent.set_pos(col, row);
+            let note = if agent.can_enter(map, col, row) {
+                ""
+            } else {
+                " (blocked cell, placed with Shift override)"
+            };
+            agent.log(LogEntry::Info(format!(
+                "Agent moved to position ({}, {}){}",
+                col, row, note
+            )));
+            return;
+        }
+
+        let tile = map.get(col, row).map(|t| t.name()).unwrap_or("unknown");
+        match map.nearest_cell_where((col, row), |x, y| agent.can_enter(map, x, y)) {
+            Some((x, y)) => {
+                agent.set_pos(x, y);
+                agent.log(LogEntry::Info(format!(
+                    "Cell ({}, {}) is blocked by {}; agent placed at nearest open cell ({}, {}). \
+                    Hold Shift to place on blocked cells.",
+                    col, row, tile, x, y
+                )));
+            }
+            None => agent.log_error(ErrorKind::Validation, format!(
+                "Cell ({}, {}) is blocked by {} and no open cell fits the agent",
+                col, row, tile
+            )),
+        }
+    }
+
     /// Handle board input during play mode
     pub fn handle_play_input(
         agent: &mut Agent,
@@ -1,7 +1,11 @@
-use crate::agent::{Agent, LogEntry};
+use crate::agent::{Agent, ErrorKind, LogEntry};
 use crate::editor::EditorState;
-use crate::map::{GridMap, TileKind};
+use crate::map::{
+    DecorationLayer, ElevationLayer, GridMap, MIN_MAP_DIMENSION, MapCell, TileKind,
+};
+use crate::pathfinding::{connected_components, reachable_cells};
 use serde::Serialize;
+use std::collections::HashSet;
 use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::JsFuture;
 
@@ -10,9 +14,34 @@ use wasm_bindgen_futures::JsFuture;
 struct MapJson {
     name: String,
     description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    author: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    difficulty: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    spawn: Option<MapCell>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    goal: Option<MapCell>,
     width: usize,
     height: usize,
     tiles: Vec<Vec<TileKind>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    decorations: Option<DecorationLayer>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    elevation: Option<ElevationLayer>,
+}
+
+/// Which traversable cells can be walked to from a start cell
+pub struct Reachability {
+    pub reachable: HashSet<(usize, usize)>,
+    /// Traversable cells `reachable` doesn't include, in reading order
+    pub unreachable: Vec<(usize, usize)>,
+}
+
+impl Reachability {
+    pub fn unreachable_count(&self) -> usize {
+        self.unreachable.len()
+    }
 }
 
 /// Map editing operations
@@ -27,20 +56,14 @@ impl EditorOperations {
         new_height: usize,
         agent: &mut Agent,
     ) -> (GridMap, usize) {
-        // Create new map with specified dimensions
-        let mut new_map = GridMap::new(new_width, new_height, TileKind::Grass);
-
-        // Copy as much of the old map as possible
-        let copy_width = new_width.min(current_map.width());
-        let copy_height = new_height.min(current_map.height());
-
-        for y in 0..copy_height {
-            for x in 0..copy_width {
-                if let Some(tile) = current_map.get(x, y) {
-                    let _ = new_map.set(x, y, *tile);
-                }
-            }
+        if new_width < MIN_MAP_DIMENSION || new_height < MIN_MAP_DIMENSION {
+            agent.log(LogEntry::Info(format!(
+                "Map size {}x{} is below the {}x{} minimum; clamping",
+                new_width, new_height, MIN_MAP_DIMENSION, MIN_MAP_DIMENSION
+            )));
         }
+        let new_map = current_map.resized(new_width, new_height, TileKind::Grass);
+        let (new_width, new_height) = (new_map.width(), new_map.height());
 
         // Ensure agent stays within bounds
         if agent.x >= new_width {
@@ -54,6 +77,7 @@ impl EditorOperations {
             "Map resized to {}x{}",
             new_width, new_height
         )));
+        agent.relocate_if_blocked(&new_map);
 
         let board_dim = new_width.max(new_height);
         (new_map, board_dim)
@@ -64,24 +88,208 @@ impl EditorOperations {
         map.clear(tile);
     }
 
+    /// Enclose the map in `tile`, moving the agent off the border if it now blocks it
+    pub fn add_border(map: &mut GridMap, tile: TileKind, agent: &mut Agent) {
+        let replaced = map.add_border(tile);
+        agent.log_info(format!("Border of {} set ({} cells changed)", tile.name(), replaced.len()));
+        agent.relocate_if_blocked(map);
+    }
+
+    /// Snapshot `map` before an edit so the edit can be undone
+    ///
+    /// Follow the edit with `commit_undo`. Past `undo_depth` snapshots the oldest is dropped.
+    pub fn push_undo(editor_state: &mut EditorState, map: &GridMap) {
+        editor_state.undo_stack.push(map.clone());
+        let excess = editor_state
+            .undo_stack
+            .len()
+            .saturating_sub(editor_state.undo_depth.max(1));
+        editor_state.undo_stack.drain(..excess);
+    }
+
+    /// Finish an edit started with `push_undo`
+    ///
+    /// An edit that changed nothing drops its snapshot; a real one makes the undone
+    /// snapshots unreachable, so the redo stack is cleared.
+    pub fn commit_undo(editor_state: &mut EditorState, map: &GridMap) {
+        if editor_state.undo_stack.last() == Some(map) {
+            editor_state.undo_stack.pop();
+        } else {
+            editor_state.redo_stack.clear();
+        }
+    }
+
+    /// Restore the map from before the last edit; false if there is nothing to undo
+    pub fn undo(editor_state: &mut EditorState, map: &mut GridMap, agent: &mut Agent) -> bool {
+        let Some(previous) = editor_state.undo_stack.pop() else {
+            return false;
+        };
+        let replaced = Self::restore(editor_state, map, previous, agent);
+        editor_state.redo_stack.push(replaced);
+        agent.log_info(format!(
+            "Undid map edit ({} more to undo)",
+            editor_state.undo_stack.len()
+        ));
+        true
+    }
+
+    /// Reapply the last undone edit; false if there is nothing to redo
+    pub fn redo(editor_state: &mut EditorState, map: &mut GridMap, agent: &mut Agent) -> bool {
+        let Some(next) = editor_state.redo_stack.pop() else {
+            return false;
+        };
+        let replaced = Self::restore(editor_state, map, next, agent);
+        editor_state.undo_stack.push(replaced);
+        agent.log_info(format!(
+            "Redid map edit ({} more to redo)",
+            editor_state.redo_stack.len()
+        ));
+        true
+    }
+
+    /// Swap `snapshot` in for `map` and return the replaced map
+    ///
+    /// The topology is an app setting rather than part of the edit, so it is kept.
+    fn restore(
+        editor_state: &mut EditorState,
+        map: &mut GridMap,
+        mut snapshot: GridMap,
+        agent: &mut Agent,
+    ) -> GridMap {
+        snapshot.set_topology(map.topology());
+        let replaced = std::mem::replace(map, snapshot);
+        editor_state.set_target_dimensions(map.width(), map.height());
+        agent.relocate_if_blocked(map);
+        replaced
+    }
+
+    /// Replace the region of same-kind tiles around (x, y) with `new_tile`
+    ///
+    /// The region is 4-connected (up, down, left, right) on every topology. Returns the
+    /// number of cells changed; filling with the kind already there changes nothing.
+    pub fn flood_fill(map: &mut GridMap, x: usize, y: usize, new_tile: TileKind) -> usize {
+        let Some(&old_tile) = map.get(x, y) else {
+            return 0;
+        };
+        if old_tile == new_tile {
+            return 0;
+        }
+        // Each cell is changed as it is pushed, so it can't be pushed twice
+        map.set(x, y, new_tile);
+        let mut stack = vec![(x, y)];
+        let mut filled = 1;
+        while let Some((cx, cy)) = stack.pop() {
+            let neighbors = [
+                (cx.wrapping_sub(1), cy),
+                (cx + 1, cy),
+                (cx, cy.wrapping_sub(1)),
+                (cx, cy + 1),
+            ];
+            for (nx, ny) in neighbors {
+                if map.get(nx, ny) == Some(&old_tile) {
+                    map.set(nx, ny, new_tile);
+                    filled += 1;
+                    stack.push((nx, ny));
+                }
+            }
+        }
+        filled
+    }
+
+    /// Flood the map's traversable tiles from `start`, portals included
+    ///
+    /// Uses the tiles' own traversability rather than the agent's rules or size, so the
+    /// result describes the map itself.
+    pub fn validate_reachability(map: &GridMap, start: (usize, usize)) -> Reachability {
+        let reachable = reachable_cells(map, start, |x, y| map.is_traversable(x, y));
+        let unreachable = (0..map.height())
+            .flat_map(|y| (0..map.width()).map(move |x| (x, y)))
+            .filter(|&(x, y)| map.is_traversable(x, y) && !reachable.contains(&(x, y)))
+            .collect();
+        Reachability { reachable, unreachable }
+    }
+
+    /// Check reachability from the agent, log the outcome and highlight any unreachable
+    /// pockets until the map changes
+    pub fn validate_map(map: &GridMap, editor_state: &mut EditorState, agent: &mut Agent) {
+        let start = agent.pos();
+        let result = Self::validate_reachability(map, start);
+        editor_state.set_unreachable_highlight(map, result.unreachable.clone());
+        if result.unreachable.is_empty() {
+            agent.log_info(format!(
+                "Map OK: all {} traversable cells are reachable from ({}, {})",
+                result.reachable.len(),
+                start.0,
+                start.1
+            ));
+            return;
+        }
+        let unreachable: HashSet<(usize, usize)> = result.unreachable.iter().copied().collect();
+        let pockets = connected_components(map, |x, y| unreachable.contains(&(x, y))).len();
+        agent.log_error(
+            ErrorKind::Validation,
+            format!(
+                "{} traversable cells in {} pocket(s) can't be reached from ({}, {}); they are \
+                highlighted on the board",
+                result.unreachable_count(),
+                pockets,
+                start.0,
+                start.1
+            ),
+        );
+    }
+
     /// Copy map JSON to clipboard
     pub fn copy_map_to_clipboard(map: &GridMap, editor_state: &EditorState, agent: &mut Agent) {
+        let json = serde_json::to_string_pretty(&Self::map_json(map, editor_state, agent))
+            .unwrap_or_default();
+        Self::copy_to_clipboard(&json);
+        agent.log(LogEntry::Info(
+            "Map JSON copied to clipboard!".to_string(),
+        ));
+    }
+
+    /// The map in its saved JSON form, as `GridMap::from_json` reads it back
+    pub fn map_json(map: &GridMap, editor_state: &EditorState, agent: &Agent) -> serde_json::Value {
         let map_json = MapJson {
             name: editor_state.map_name.clone(),
             description: editor_state.map_description.clone(),
+            author: Some(editor_state.map_author.trim().to_string()).filter(|a| !a.is_empty()),
+            difficulty: editor_state.map_difficulty,
+            // The agent's spawn (set by placing it) becomes the map's start cell
+            spawn: Some(agent.spawn())
+                .filter(|&(x, y)| map.in_bounds(x, y))
+                .map(|(x, y)| MapCell { x, y }),
+            goal: agent
+                .goal()
+                .filter(|&(x, y)| map.in_bounds(x, y))
+                .map(|(x, y)| MapCell { x, y }),
             width: map.width(),
             height: map.height(),
-            tiles: map.tiles().clone(),
+            tiles: Self::exported_tiles(map, agent),
+            decorations: map.decorations().cloned(),
+            elevation: map.elevation_layer().cloned(),
         };
-        let json = serde_json::to_string_pretty(&map_json).unwrap_or_default();
-        Self::copy_to_clipboard(&json);
-        agent.log(LogEntry::Info(
-            "Map JSON copied to clipboard!".to_string(),
-        ));
+        serde_json::to_value(&map_json).unwrap_or_default()
+    }
+
+    /// Tiles as they should be saved: trail tiles are swapped back for the terrain they
+    /// replaced (grass if unknown), so walking the agent never changes an exported map
+    pub fn exported_tiles(map: &GridMap, agent: &Agent) -> Vec<Vec<TileKind>> {
+        (0..map.height())
+            .map(|y| {
+                (0..map.width())
+                    .map(|x| match agent.terrain_at(map, x, y) {
+                        Some(TileKind::Trail) | None => TileKind::Grass,
+                        Some(tile) => tile,
+                    })
+                    .collect()
+            })
+            .collect()
     }
 
     /// Copy text to clipboard using web_sys
-    fn copy_to_clipboard(text: &str) {
+    pub fn copy_to_clipboard(text: &str) {
         if let Some(window) = web_sys::window() {
             if let Ok(navigator) = window.navigator().dyn_into::<web_sys::Navigator>() {
                 if let Ok(clipboard) = navigator.clipboard().dyn_into::<web_sys::Clipboard>() {
@@ -95,3 +303,94 @@ impl EditorOperations {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::Direction;
+
+    /// 5x4 grass map split by a wall down column 2
+    fn split_map() -> GridMap {
+        let mut map = GridMap::new(5, 4, TileKind::Grass);
+        for y in 0..4 {
+            map.set(2, y, TileKind::Wall);
+        }
+        map
+    }
+
+    #[test]
+    fn flood_fill_with_the_same_kind_changes_nothing() {
+        let mut map = split_map();
+        assert_eq!(EditorOperations::flood_fill(&mut map, 0, 0, TileKind::Grass), 0);
+        assert_eq!(map.tiles_flat(), split_map().tiles_flat());
+        assert_eq!(EditorOperations::flood_fill(&mut map, 5, 0, TileKind::Sand), 0);
+        assert_eq!(map.tiles_flat(), split_map().tiles_flat());
+    }
+
+    #[test]
+    fn flood_fill_stops_at_other_tiles() {
+        let mut map = split_map();
+        assert_eq!(EditorOperations::flood_fill(&mut map, 1, 2, TileKind::Sand), 8);
+        for y in 0..4 {
+            assert_eq!(map.get(0, y), Some(&TileKind::Sand));
+            assert_eq!(map.get(1, y), Some(&TileKind::Sand));
+            assert_eq!(map.get(2, y), Some(&TileKind::Wall));
+            assert_eq!(map.get(3, y), Some(&TileKind::Grass));
+        }
+    }
+
+    #[test]
+    fn flood_fill_from_a_map_edge() {
+        let mut map = split_map();
+        assert_eq!(EditorOperations::flood_fill(&mut map, 4, 3, TileKind::Water), 8);
+        assert_eq!(map.get(3, 0), Some(&TileKind::Water));
+        assert_eq!(map.get(1, 0), Some(&TileKind::Grass));
+        // The wall column touches the top and bottom edges
+        assert_eq!(EditorOperations::flood_fill(&mut map, 2, 0, TileKind::Tree), 4);
+    }
+
+    #[test]
+    fn elevation_brush_paints_heights_and_undoes_as_one_stroke() {
+        let mut map = split_map();
+        let mut agent = Agent::new(1, "a", 0, 0);
+        let mut state = EditorState::new(5, 4);
+        state.paint_elevation = true;
+        state.elevation_height = 0;
+        state.paint(&mut map, 0, 0);
+        assert!(!map.has_elevation());
+
+        EditorOperations::push_undo(&mut state, &map);
+        state.elevation_height = 2;
+        state.paint(&mut map, 0, 1);
+        state.paint(&mut map, 1, 1);
+        EditorOperations::commit_undo(&mut state, &map);
+        assert_eq!(map.elevation_range(), Some((0, 2)));
+        assert_eq!(map.elevation(1, 1), 2);
+        // Only the elevation layer was painted
+        assert_eq!(map.tiles_flat(), split_map().tiles_flat());
+
+        EditorOperations::undo(&mut state, &mut map, &mut agent);
+        assert!(!map.has_elevation());
+        EditorOperations::redo(&mut state, &mut map, &mut agent);
+        assert_eq!(map.elevation(0, 1), 2);
+        map.clear_elevation();
+        assert_eq!(map, split_map());
+    }
+
+    #[test]
+    fn export_after_walking_has_no_trail_tiles() {
+        let mut map = GridMap::new(4, 2, TileKind::Grass);
+        map.set(1, 0, TileKind::Sand);
+        let original: Vec<Vec<TileKind>> = map.rows().map(<[TileKind]>::to_vec).collect();
+        let mut agent = Agent::new(1, "a", 0, 0);
+        agent.set_leave_trail_tiles(true, &mut map);
+        for direction in [Direction::Right, Direction::Right, Direction::Down] {
+            agent.execute_move_step(direction, &mut map).unwrap();
+        }
+        assert!(map.tiles_flat().contains(&TileKind::Trail));
+
+        let exported = EditorOperations::exported_tiles(&map, &agent);
+        assert!(exported.iter().flatten().all(|&tile| tile != TileKind::Trail));
+        assert_eq!(exported, original);
+    }
+}
@@ -1,4 +1,8 @@
-use crate::map::TileKind;
+use crate::editor::ScatterBrush;
+use crate::map::{GridMap, TileKind};
+
+/// Default number of map edits that can be undone
+const DEFAULT_UNDO_DEPTH: usize = 50;
 
 /// State for map editing functionality
 pub struct EditorState {
@@ -17,11 +21,57 @@ pub struct EditorState {
     /// Whether the user is in agent placement mode
     pub placing_agent: bool,
 
+    /// Agent position and spawn before the last placement, for a one-step undo
+    pub previous_agent_placement: Option<((usize, usize), (usize, usize))>,
+
+    /// Whether the next click sets the map's spawn cell
+    pub placing_spawn: bool,
+
+    /// Whether the next click sets the map's goal cell
+    pub placing_goal: bool,
+
     /// Map name for metadata
     pub map_name: String,
 
     /// Map description for metadata
     pub map_description: String,
+
+    /// Map author for metadata (empty means not set)
+    pub map_author: String,
+
+    /// Map difficulty rating for metadata (1-5)
+    pub map_difficulty: Option<u8>,
+
+    /// Pair id used when painting portal tiles
+    pub portal_id: u8,
+
+    /// Weighted random tile brush, used instead of `selected_edit_tile` when enabled
+    pub scatter: ScatterBrush,
+
+    /// Paint onto the visual decoration layer instead of the terrain
+    pub paint_decorations: bool,
+
+    /// Paint `elevation_height` onto the elevation layer instead of tiles
+    pub paint_elevation: bool,
+
+    /// Height the elevation brush paints
+    pub elevation_height: i32,
+
+    /// Clicks flood-fill the clicked region with `selected_edit_tile` instead of painting
+    pub bucket_fill: bool,
+
+    /// Snapshots of the map from before each edit, oldest first (see `EditorOperations`)
+    pub undo_stack: Vec<GridMap>,
+
+    /// Snapshots replaced by undo, most recently undone last
+    pub redo_stack: Vec<GridMap>,
+
+    /// Most undo snapshots kept; older ones are dropped
+    pub undo_depth: usize,
+
+    /// Cells the last "Validate Map" found unreachable, with the `content_hash` of the
+    /// map they were found on
+    unreachable_highlight: Option<(u64, Vec<(usize, usize)>)>,
 }
 
 impl EditorState {
@@ -32,8 +82,23 @@ impl EditorState {
             edit_map_width: initial_map_width,
             edit_map_height: initial_map_height,
             placing_agent: false,
+            previous_agent_placement: None,
+            placing_spawn: false,
+            placing_goal: false,
             map_name: String::new(),
             map_description: String::new(),
+            map_author: String::new(),
+            map_difficulty: None,
+            portal_id: 1,
+            scatter: ScatterBrush::default(),
+            paint_decorations: false,
+            paint_elevation: false,
+            elevation_height: 1,
+            bucket_fill: false,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            undo_depth: DEFAULT_UNDO_DEPTH,
+            unreachable_highlight: None,
         }
     }
 
@@ -45,8 +110,58 @@ impl EditorState {
     /// Set the selected tile type for painting
     pub fn set_selected_tile(&mut self, tile: TileKind) {
         self.selected_edit_tile = tile;
-        // When selecting a tile, exit agent placement mode
+        // When selecting a tile, exit agent/spawn/goal placement mode
         self.placing_agent = false;
+        self.placing_spawn = false;
+        self.placing_goal = false;
+    }
+
+    /// Tile to paint at (x, y) in the current stroke, or None to leave the cell alone
+    pub fn paint_tile(&mut self, x: usize, y: usize) -> Option<TileKind> {
+        if self.scatter.enabled {
+            self.scatter.pick((x, y))
+        } else {
+            Some(self.selected_edit_tile)
+        }
+    }
+
+    /// Paint the brush at (x, y) onto the active layer; on the decoration layer, empty
+    /// erases
+    pub fn paint(&mut self, map: &mut GridMap, x: usize, y: usize) {
+        if self.paint_elevation {
+            // Height 0 is what a flat map already has, so it doesn't need a layer
+            if self.elevation_height != 0 || map.has_elevation() {
+                map.set_elevation(x, y, self.elevation_height);
+            }
+            return;
+        }
+        let Some(tile) = self.paint_tile(x, y) else {
+            return;
+        };
+        if self.paint_decorations {
+            map.set_decoration(x, y, (tile != TileKind::Empty).then_some(tile));
+        } else {
+            map.set(x, y, tile);
+        }
+    }
+
+    /// Forget undo and redo snapshots (e.g. when another map is loaded)
+    pub fn clear_undo_history(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+
+    /// Remember `cells` as unreachable on `map`; an empty list clears the highlight
+    pub fn set_unreachable_highlight(&mut self, map: &GridMap, cells: Vec<(usize, usize)>) {
+        self.unreachable_highlight = (!cells.is_empty()).then(|| (map.content_hash(), cells));
+    }
+
+    /// Unreachable cells to highlight, as long as `map` hasn't changed since validation
+    pub fn unreachable_highlight(&self, map: &GridMap) -> &[(usize, usize)] {
+        match &self.unreachable_highlight {
+            Some((hash, cells)) if *hash == map.content_hash() => cells,
+            _ => &[],
+        }
     }
 
     /// Toggle agent placement mode
@@ -54,9 +169,16 @@ impl EditorState {
         self.placing_agent = !self.placing_agent;
     }
 
-    /// Exit agent placement mode (called after placing)
+    /// Exit agent/spawn/goal placement mode (called after placing)
     pub fn exit_placement_mode(&mut self) {
         self.placing_agent = false;
+        self.placing_spawn = false;
+        self.placing_goal = false;
+    }
+
+    /// Whether a click places something instead of painting tiles
+    pub fn is_placing(&self) -> bool {
+        self.placing_agent || self.placing_spawn || self.placing_goal
     }
 
     /// Update target map dimensions
@@ -66,13 +188,17 @@ impl EditorState {
     }
 
     /// Initialize metadata from existing map
-    pub fn initialize_from_map(&mut self, map: &crate::map::GridMap) {
+    pub fn initialize_from_map(&mut self, map: &GridMap) {
         if let Some(metadata) = &map.metadata {
             self.map_name = metadata.name.clone();
             self.map_description = metadata.description.clone();
+            self.map_author = metadata.author.clone().unwrap_or_default();
+            self.map_difficulty = metadata.difficulty;
         } else {
             self.map_name.clear();
             self.map_description.clear();
+            self.map_author.clear();
+            self.map_difficulty = None;
         }
     }
 }
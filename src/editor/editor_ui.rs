@@ -1,6 +1,7 @@
 use crate::agent::Agent;
-use crate::editor::{EditorOperations, EditorState};
-use crate::map::{GridMap, TileKind};
+use crate::editor::{EditorInput, EditorOperations, EditorState};
+use crate::map::{GridMap, MAX_MAP_DIMENSION, TileKind};
+use crate::map_type::{MAX_DIFFICULTY, difficulty_stars};
 use eframe::egui;
 
 /// UI rendering for editor mode
@@ -28,6 +29,28 @@ impl EditorUI {
             ui.label("Description:");
             ui.text_edit_singleline(&mut editor_state.map_description);
         });
+        ui.horizontal(|ui| {
+            ui.label("Author:");
+            ui.text_edit_singleline(&mut editor_state.map_author);
+        });
+        ui.horizontal(|ui| {
+            ui.label("Difficulty:");
+            let label = editor_state
+                .map_difficulty
+                .map_or_else(|| "Unrated".to_string(), difficulty_stars);
+            egui::ComboBox::from_id_source("map_difficulty")
+                .selected_text(label)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut editor_state.map_difficulty, None, "Unrated");
+                    for difficulty in 1..=MAX_DIFFICULTY {
+                        ui.selectable_value(
+                            &mut editor_state.map_difficulty,
+                            Some(difficulty),
+                            difficulty_stars(difficulty),
+                        );
+                    }
+                });
+        });
 
         ui.add_space(8.0);
 
@@ -38,8 +61,8 @@ impl EditorUI {
             let mut width_str = editor_state.edit_map_width.to_string();
             if ui.text_edit_singleline(&mut width_str).changed() {
                 if let Ok(w) = width_str.parse::<usize>() {
-                    if w > 0 && w <= 100 {
-                        // Reasonable limits
+                    // Too small is clamped (and logged) on resize; too large is ignored
+                    if w > 0 && w <= MAX_MAP_DIMENSION {
                         editor_state.edit_map_width = w;
                     }
                 }
@@ -49,8 +72,8 @@ impl EditorUI {
             let mut height_str = editor_state.edit_map_height.to_string();
             if ui.text_edit_singleline(&mut height_str).changed() {
                 if let Ok(h) = height_str.parse::<usize>() {
-                    if h > 0 && h <= 100 {
-                        // Reasonable limits
+                    // Too small is clamped (and logged) on resize; too large is ignored
+                    if h > 0 && h <= MAX_MAP_DIMENSION {
                         editor_state.edit_map_height = h;
                     }
                 }
@@ -63,18 +86,72 @@ impl EditorUI {
                     editor_state.edit_map_height,
                     agent,
                 );
+                EditorOperations::push_undo(editor_state, map);
                 *map = resized_map;
+                EditorOperations::commit_undo(editor_state, map);
                 new_board_dim = Some(board_dim);
             }
         });
 
         ui.add_space(4.0);
 
-        // Agent placement mode
-        ui.checkbox(&mut editor_state.placing_agent, "Place Agent");
+        // Agent, spawn and goal placement modes (mutually exclusive)
+        ui.horizontal(|ui| {
+            let mut placing_agent = editor_state.placing_agent;
+            let mut placing_spawn = editor_state.placing_spawn;
+            let mut placing_goal = editor_state.placing_goal;
+            let agent_toggled = ui.checkbox(&mut placing_agent, "Place Agent").changed();
+            let spawn_toggled = ui.checkbox(&mut placing_spawn, "Set Spawn").changed();
+            let goal_toggled = ui.checkbox(&mut placing_goal, "Set Goal").changed();
+            if agent_toggled || spawn_toggled || goal_toggled {
+                editor_state.exit_placement_mode();
+                editor_state.placing_agent = agent_toggled && placing_agent;
+                editor_state.placing_spawn = spawn_toggled && placing_spawn;
+                editor_state.placing_goal = goal_toggled && placing_goal;
+            }
+            if editor_state.previous_agent_placement.is_some()
+                && ui
+                    .small_button("Undo Placement")
+                    .on_hover_text("Move the agent back to where it was before the last placement")
+                    .clicked()
+            {
+                EditorInput::undo_agent_placement(editor_state, map, agent);
+            }
+
+            let (sx, sy) = agent.spawn();
+            let goal = agent
+                .goal()
+                .map_or_else(|| "none".to_string(), |(gx, gy)| format!("({}, {})", gx, gy));
+            ui.label(
+                egui::RichText::new(format!("spawn: ({}, {}), goal: {}", sx, sy, goal))
+                    .small()
+                    .color(egui::Color32::from_gray(120)),
+            );
+        });
+        if editor_state.placing_goal {
+            ui.label(
+                egui::RichText::new(
+                    "Click on the map to set the goal (saved with the map JSON); \
+                    click the current goal to clear it",
+                )
+                    .small()
+                    .italics(),
+            );
+        }
+        if editor_state.placing_spawn {
+            ui.label(
+                egui::RichText::new(
+                    "Click on the map to set where the agent starts (saved with the map JSON)",
+                )
+                    .small()
+                    .italics(),
+            );
+        }
         if editor_state.placing_agent {
             ui.label(
-                egui::RichText::new("Click on the map to move the agent")
+                egui::RichText::new(
+                    "Click on the map to move the agent (hold Shift to allow blocked cells)",
+                )
                     .small()
                     .italics(),
             );
@@ -92,6 +169,8 @@ impl EditorUI {
                 (TileKind::Water, "Water"),
                 (TileKind::Wall, "Wall"),
                 (TileKind::Tree, "Tree"),
+                (TileKind::Portal(editor_state.portal_id), "Portal"),
+                (TileKind::Fuel, "Fuel"),
             ];
 
             for (tile_kind, label) in tile_types {
@@ -104,16 +183,155 @@ impl EditorUI {
             }
         });
 
+        // Decorations are drawn over the terrain but never block or slow the agent
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut editor_state.paint_decorations, "Decoration layer")
+                .on_hover_text("Paint purely visual tiles over the terrain; Empty erases them");
+            if ui
+                .add_enabled(map.has_decorations(), egui::Button::new("Clear").small())
+                .on_hover_text("Remove every decoration")
+                .clicked()
+            {
+                EditorOperations::push_undo(editor_state, map);
+                map.clear_decorations();
+                EditorOperations::commit_undo(editor_state, map);
+            }
+        });
+
+        // Heights only matter to cost-aware routes, where climbing costs extra
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut editor_state.paint_elevation, "Elevation")
+                .on_hover_text("Paint heights instead of tiles; climbing costs the rise");
+            ui.add_enabled(
+                editor_state.paint_elevation,
+                egui::DragValue::new(&mut editor_state.elevation_height).range(-99..=99),
+            );
+            if ui
+                .add_enabled(map.has_elevation(), egui::Button::new("Flatten").small())
+                .on_hover_text("Remove the elevation layer")
+                .clicked()
+            {
+                EditorOperations::push_undo(editor_state, map);
+                map.clear_elevation();
+                EditorOperations::commit_undo(editor_state, map);
+            }
+        });
+
+        if ui
+            .checkbox(&mut editor_state.bucket_fill, "Bucket fill")
+            .on_hover_text(
+                "Click to replace the connected area of the clicked tile with the selected \
+                tile (terrain only)",
+            )
+            .changed()
+            && editor_state.bucket_fill
+        {
+            editor_state.exit_placement_mode();
+        }
+
+        // Scatter brush: each painted cell rolls a tile from the weighted mix
+        ui.checkbox(&mut editor_state.scatter.enabled, "Scatter brush")
+            .on_hover_text("Paint a random mix of tiles instead of the selected tile");
+        if editor_state.scatter.enabled {
+            ui.horizontal_wrapped(|ui| {
+                for i in 0..editor_state.scatter.weights.len() {
+                    let (kind, _) = editor_state.scatter.weights[i];
+                    let percent = editor_state.scatter.percent(kind);
+                    ui.label(kind.name());
+                    ui.add(
+                        egui::DragValue::new(&mut editor_state.scatter.weights[i].1)
+                            .range(0..=100),
+                    )
+                    .on_hover_text(format!("{:.0}% of painted cells", percent));
+                }
+            });
+        }
+
+        // Portals are paired by id; paint two portals with the same id to link them
+        if let TileKind::Portal(_) = editor_state.selected_edit_tile {
+            ui.horizontal(|ui| {
+                ui.label("Portal id:");
+                if ui
+                    .add(egui::DragValue::new(&mut editor_state.portal_id).range(0..=9))
+                    .changed()
+                {
+                    editor_state.selected_edit_tile = TileKind::Portal(editor_state.portal_id);
+                }
+                let count = map
+                    .portals()
+                    .filter(|&(id, _, _)| id == editor_state.portal_id)
+                    .count();
+                let (text, color) = match count {
+                    0 => ("not placed".to_string(), egui::Color32::from_gray(120)),
+                    1 => ("unpaired".to_string(), egui::Color32::from_rgb(200, 120, 20)),
+                    2 => ("paired".to_string(), egui::Color32::from_rgb(50, 150, 50)),
+                    n => (
+                        format!("{} placed, only the first two pair", n),
+                        egui::Color32::from_rgb(200, 120, 20),
+                    ),
+                };
+                ui.label(egui::RichText::new(text).small().color(color));
+            });
+        }
+
         ui.add_space(4.0);
 
         // Map operations
         ui.horizontal(|ui| {
             if ui.button("Fill All").clicked() {
+                EditorOperations::push_undo(editor_state, map);
                 EditorOperations::fill_all(map, editor_state.selected_edit_tile);
+                EditorOperations::commit_undo(editor_state, map);
+            }
+            if ui
+                .button("Add Border")
+                .on_hover_text("Set the outer ring of cells to the selected tile")
+                .clicked()
+            {
+                EditorOperations::push_undo(editor_state, map);
+                EditorOperations::add_border(map, editor_state.selected_edit_tile, agent);
+                EditorOperations::commit_undo(editor_state, map);
             }
             if ui.button("Copy JSON").clicked() {
                 EditorOperations::copy_map_to_clipboard(map, editor_state, agent);
             }
+            if ui
+                .button("Validate Map")
+                .on_hover_text(
+                    "Check that every traversable cell can be reached from the agent and \
+                    highlight any that can't",
+                )
+                .clicked()
+            {
+                EditorOperations::validate_map(map, editor_state, agent);
+            }
+        });
+
+        // Undo history
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(!editor_state.undo_stack.is_empty(), egui::Button::new("Undo"))
+                .on_hover_text("Ctrl+Z")
+                .clicked()
+            {
+                EditorOperations::undo(editor_state, map, agent);
+            }
+            if ui
+                .add_enabled(!editor_state.redo_stack.is_empty(), egui::Button::new("Redo"))
+                .on_hover_text("Ctrl+Shift+Z")
+                .clicked()
+            {
+                EditorOperations::redo(editor_state, map, agent);
+            }
+            ui.label("Keep:");
+            if ui
+                .add(egui::DragValue::new(&mut editor_state.undo_depth).range(1..=500))
+                .on_hover_text("How many edits can be undone")
+                .changed()
+            {
+                let excess = editor_state.undo_stack.len().saturating_sub(editor_state.undo_depth);
+                editor_state.undo_stack.drain(..excess);
+            }
         });
 
         ui.add_space(8.0);
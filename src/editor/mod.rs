@@ -2,8 +2,10 @@ mod editor_input;
 mod editor_operations;
 mod editor_state;
 mod editor_ui;
+mod scatter_brush;
 
 pub use editor_input::EditorInput;
 pub use editor_operations::EditorOperations;
 pub use editor_state::EditorState;
 pub use editor_ui::EditorUI;
+pub use scatter_brush::ScatterBrush;
@@ -0,0 +1,75 @@
+use crate::map::TileKind;
+use crate::rng::SeededRng;
+use std::collections::HashSet;
+
+/// Brush that paints each cell with a tile drawn from a weighted distribution
+///
+/// A cell is only rolled once per stroke, so holding the pointer over it doesn't keep
+/// re-rolling the tile underneath.
+pub struct ScatterBrush {
+    /// Whether painting uses the scatter distribution instead of the selected tile
+    pub enabled: bool,
+    /// Relative weight of each tile; zero-weight tiles are never picked
+    pub weights: Vec<(TileKind, u32)>,
+    rng: SeededRng,
+    /// Cells already painted in the current stroke
+    stroke: HashSet<(usize, usize)>,
+}
+
+impl Default for ScatterBrush {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            weights: vec![
+                (TileKind::Grass, 70),
+                (TileKind::Tree, 20),
+                (TileKind::Water, 10),
+                (TileKind::Sand, 0),
+                (TileKind::Wall, 0),
+            ],
+            rng: SeededRng::new(Self::SEED),
+            stroke: HashSet::new(),
+        }
+    }
+}
+
+impl ScatterBrush {
+    pub const SEED: u64 = 0x5EED;
+
+    /// Start a new stroke; every cell may be painted again
+    pub fn begin_stroke(&mut self) {
+        self.stroke.clear();
+    }
+
+    /// Tile to paint at `cell`, or None if it was already painted this stroke or
+    /// every weight is zero
+    pub fn pick(&mut self, cell: (usize, usize)) -> Option<TileKind> {
+        let total: u64 = self.weights.iter().map(|&(_, w)| w as u64).sum();
+        if total == 0 || !self.stroke.insert(cell) {
+            return None;
+        }
+        let mut roll = self.rng.below(total);
+        for &(kind, weight) in &self.weights {
+            if roll < weight as u64 {
+                return Some(kind);
+            }
+            roll -= weight as u64;
+        }
+        None
+    }
+
+    /// Weight of `kind` as a percentage of the total, for display
+    pub fn percent(&self, kind: TileKind) -> f32 {
+        let total: u32 = self.weights.iter().map(|&(_, w)| w).sum();
+        let weight = self
+            .weights
+            .iter()
+            .find(|&&(k, _)| k == kind)
+            .map_or(0, |&(_, w)| w);
+        if total == 0 {
+            0.0
+        } else {
+            weight as f32 * 100.0 / total as f32
+        }
+    }
+}
@@ -1,7 +1,10 @@
 use std::sync::{Arc, Mutex};
 use std::collections::VecDeque;
+use std::fmt;
 use web_time::{Duration, Instant};
-use crate::agent::Direction;
+use crate::agent::{Agent, Direction};
+use crate::clock::{RealClock, SharedClock};
+use crate::map::GridMap;
 
 /// Unique identifier for events
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -16,6 +19,11 @@ impl EventId {
         *counter += 1;
         EventId(id)
     }
+
+    /// Raw numeric id (for display)
+    pub fn value(&self) -> u64 {
+        self.0
+    }
 }
 
 /// Status of an event
@@ -39,6 +47,79 @@ pub enum Event {
     Delay {
         ticks: u32,
     },
+    /// Escape hatch for features that schedule their own behavior (see `CustomEvent`)
+    Custom(CustomEvent),
+}
+
+impl Event {
+    /// Run the event against `agent` and `map`, as the tick loop does when it comes due
+    pub fn apply(self, agent: &mut Agent, map: &mut GridMap) -> Result<(), String> {
+        match self {
+            Event::AgentMove {
+                agent_id,
+                direction,
+            } => {
+                if agent_id == agent.id {
+                    agent.execute_move_step(direction, map)
+                } else {
+                    Err(format!("Unknown agent id: {}", agent_id))
+                }
+            }
+            // Delay events just complete successfully
+            Event::Delay { .. } => Ok(()),
+            Event::Custom(custom) => custom.run(agent, map),
+        }
+    }
+}
+
+type CustomEventHandler =
+    Arc<dyn Fn(&mut Agent, &mut GridMap) -> Result<(), String> + Send + Sync>;
+
+/// A named event whose behavior is supplied by the code that schedules it
+///
+/// Lets features like door toggles schedule work without adding a variant to `Event`.
+/// The handler follows the same conventions as built-in events: return `Err` to mark the
+/// event failed, with an "ABORT:" prefix to cancel the agent's remaining events.
+/// Handlers are `Send + Sync` so the queue stays shareable like the rest of the app state.
+#[derive(Clone)]
+pub struct CustomEvent {
+    pub name: String,
+    /// Agent the event belongs to, so it's cancelled along with that agent's other events
+    pub agent_id: Option<u32>,
+    handler: CustomEventHandler,
+}
+
+impl CustomEvent {
+    pub fn new(
+        name: impl Into<String>,
+        handler: impl Fn(&mut Agent, &mut GridMap) -> Result<(), String> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            agent_id: None,
+            handler: Arc::new(handler),
+        }
+    }
+
+    /// Tie the event to an agent
+    pub fn for_agent(mut self, agent_id: u32) -> Self {
+        self.agent_id = Some(agent_id);
+        self
+    }
+
+    /// Run the handler
+    pub fn run(&self, agent: &mut Agent, map: &mut GridMap) -> Result<(), String> {
+        (self.handler)(agent, map)
+    }
+}
+
+impl fmt::Debug for CustomEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomEvent")
+            .field("name", &self.name)
+            .field("agent_id", &self.agent_id)
+            .finish_non_exhaustive()
+    }
 }
 
 /// A scheduled event with its execution time
@@ -51,21 +132,22 @@ pub struct ScheduledEvent {
 }
 
 impl ScheduledEvent {
-    pub fn new(event: Event, delay: Duration) -> Self {
+    /// Schedule `event` to run `delay` after `now`
+    pub fn new(event: Event, delay: Duration, now: Instant) -> Self {
         Self {
             id: EventId::new(),
             event,
-            execute_at: Instant::now() + delay,
+            execute_at: now + delay,
             status: EventStatus::Pending,
         }
     }
 
-    pub fn immediate(event: Event) -> Self {
-        Self::new(event, Duration::from_millis(0))
+    pub fn immediate(event: Event, now: Instant) -> Self {
+        Self::new(event, Duration::from_millis(0), now)
     }
 
-    pub fn is_ready(&self) -> bool {
-        Instant::now() >= self.execute_at
+    pub fn is_ready(&self, now: Instant) -> bool {
+        now >= self.execute_at
     }
 }
 
@@ -74,19 +156,31 @@ impl ScheduledEvent {
 pub struct EventQueue {
     events: Arc<Mutex<VecDeque<ScheduledEvent>>>,
     completed: Arc<Mutex<Vec<(EventId, Result<(), String>)>>>,
+    clock: SharedClock,
 }
 
 impl EventQueue {
     pub fn new() -> Self {
+        Self::with_clock(RealClock::shared())
+    }
+
+    /// Create a queue that schedules against the given clock
+    pub fn with_clock(clock: SharedClock) -> Self {
         Self {
             events: Arc::new(Mutex::new(VecDeque::new())),
             completed: Arc::new(Mutex::new(Vec::new())),
+            clock,
         }
     }
 
+    /// Current time according to the queue's clock
+    pub fn now(&self) -> Instant {
+        self.clock.now()
+    }
+
     /// Submit an event to be executed after a delay
     pub fn submit(&self, event: Event, delay: Duration) -> EventId {
-        let scheduled = ScheduledEvent::new(event, delay);
+        let scheduled = ScheduledEvent::new(event, delay, self.now());
         let id = scheduled.id;
 
         let mut queue = self.events.lock().unwrap();
@@ -115,6 +209,27 @@ impl EventQueue {
         ids
     }
 
+    /// Submit a sequence where each event waits its own delay after the previous one
+    ///
+    /// `delays_after[i]` is the gap between event i and event i + 1; a missing entry
+    /// counts as no gap.
+    pub fn submit_timed_sequence(
+        &self,
+        events: Vec<Event>,
+        delays_after: &[Duration],
+    ) -> Vec<EventId> {
+        let mut current_delay = Duration::from_millis(0);
+        events
+            .into_iter()
+            .enumerate()
+            .map(|(index, event)| {
+                let id = self.submit(event, current_delay);
+                current_delay += delays_after.get(index).copied().unwrap_or_default();
+                id
+            })
+            .collect()
+    }
+
     /// Cancel events by their IDs
     pub fn cancel_events(&self, event_ids: &[EventId]) {
         let mut queue = self.events.lock().unwrap();
@@ -131,17 +246,34 @@ impl EventQueue {
         queue.retain(|event| {
             match &event.event {
                 Event::AgentMove { agent_id: id, .. } => *id != agent_id,
+                Event::Custom(custom) => custom.agent_id != Some(agent_id),
                 _ => true, // Keep other event types
             }
         });
     }
 
+    /// Directions of the agent's move events still waiting to run, in queue order
+    pub fn queued_moves(&self, agent_id: u32) -> Vec<Direction> {
+        let queue = self.events.lock().unwrap();
+        queue
+            .iter()
+            .filter(|event| event.status == EventStatus::Pending)
+            .filter_map(|event| match &event.event {
+                Event::AgentMove { agent_id: id, direction } if *id == agent_id => {
+                    Some(direction.clone())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
     /// Get the next ready event to process
     pub fn pop_ready(&self) -> Option<ScheduledEvent> {
+        let now = self.now();
         let mut queue = self.events.lock().unwrap();
 
         // Find first ready event
-        if let Some(pos) = queue.iter().position(|e| e.is_ready() && e.status == EventStatus::Pending) {
+        if let Some(pos) = queue.iter().position(|e| e.is_ready(now) && e.status == EventStatus::Pending) {
             if let Some(mut event) = queue.remove(pos) {
                 event.status = EventStatus::Processing;
                 // Put it back at the end while processing
@@ -175,6 +307,17 @@ impl EventQueue {
             .map(|(_, result)| result.clone())
     }
 
+    /// Copy of all queued events (pending and processing), in queue order
+    pub fn snapshot(&self) -> Vec<ScheduledEvent> {
+        self.events.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// The most recent completed results, newest first
+    pub fn recent_completed(&self, limit: usize) -> Vec<(EventId, Result<(), String>)> {
+        let completed = self.completed.lock().unwrap();
+        completed.iter().rev().take(limit).cloned().collect()
+    }
+
     /// Get count of pending events
     pub fn pending_count(&self) -> usize {
         self.events.lock().unwrap().len()
@@ -209,13 +352,14 @@ impl PendingToolExecution {
         tool_name: String,
         initial_result: String,
         event_ids: Vec<EventId>,
+        created_at: Instant,
     ) -> Self {
         Self {
             tool_call_id,
             tool_name,
             initial_result,
             event_ids,
-            created_at: Instant::now(),
+            created_at,
         }
     }
 
@@ -244,3 +388,91 @@ impl PendingToolExecution {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::map::TileKind;
+
+    fn mock_queue() -> EventQueue {
+        EventQueue::with_clock(Arc::new(MockClock::new()) as SharedClock)
+    }
+
+    /// Pop, apply and complete every ready event, like the app's tick loop
+    fn drain(queue: &EventQueue, agent: &mut Agent, map: &mut GridMap) {
+        while let Some(scheduled) = queue.pop_ready() {
+            let result = scheduled.event.apply(agent, map);
+            queue.complete(scheduled.id, result);
+        }
+    }
+
+    #[test]
+    fn delayed_events_become_due_as_the_clock_advances() {
+        let clock = Arc::new(MockClock::new());
+        let queue = EventQueue::with_clock(clock.clone() as SharedClock);
+        let delays = [Duration::from_millis(100), Duration::from_millis(300)];
+        let ids = queue.submit_timed_sequence(
+            vec![Event::Delay { ticks: 1 }, Event::Delay { ticks: 2 }, Event::Delay { ticks: 3 }],
+            &delays,
+        );
+
+        let due = |by: u64| {
+            clock.advance(Duration::from_millis(by));
+            let mut ready = Vec::new();
+            while let Some(scheduled) = queue.pop_ready() {
+                ready.push(scheduled.id);
+                queue.complete(scheduled.id, Ok(()));
+            }
+            ready
+        };
+        assert_eq!(due(0), vec![ids[0]]);
+        assert_eq!(due(99), vec![]);
+        assert_eq!(due(1), vec![ids[1]]);
+        assert_eq!(due(299), vec![]);
+        assert_eq!(due(1), vec![ids[2]]);
+        assert_eq!(queue.pending_count(), 0);
+    }
+
+    #[test]
+    fn custom_events_run_their_handler_when_delivered() {
+        let queue = mock_queue();
+        let mut agent = Agent::new(1, "a", 0, 0);
+        let mut map = GridMap::new(3, 3, TileKind::Grass);
+        let door = CustomEvent::new("close door", |_: &mut Agent, map: &mut GridMap| {
+            map.set(2, 2, TileKind::Wall);
+            Ok(())
+        });
+        let failing = CustomEvent::new("jammed", |_: &mut Agent, _: &mut GridMap| {
+            Err("door is jammed".to_string())
+        });
+        let door_id = queue.submit_immediate(Event::Custom(door));
+        let failing_id = queue.submit_immediate(Event::Custom(failing));
+
+        drain(&queue, &mut agent, &mut map);
+        assert_eq!(map.get(2, 2), Some(&TileKind::Wall));
+        assert_eq!(queue.is_completed(door_id), Some(Ok(())));
+        assert_eq!(queue.is_completed(failing_id), Some(Err("door is jammed".to_string())));
+        assert_eq!(queue.pending_count(), 0);
+    }
+
+    #[test]
+    fn custom_events_for_an_agent_are_cancelled_with_it() {
+        let queue = mock_queue();
+        let noop = |_: &mut Agent, _: &mut GridMap| Ok(());
+        queue.submit_immediate(Event::Custom(CustomEvent::new("mine", noop).for_agent(1)));
+        queue.submit_immediate(Event::Custom(CustomEvent::new("other", noop).for_agent(2)));
+        queue.submit_immediate(Event::Custom(CustomEvent::new("global", noop)));
+
+        queue.cancel_agent_events(1);
+        let names: Vec<String> = queue
+            .snapshot()
+            .into_iter()
+            .filter_map(|scheduled| match scheduled.event {
+                Event::Custom(custom) => Some(custom.name),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(names, vec!["other", "global"]);
+    }
+}
@@ -0,0 +1,155 @@
+use crate::agent::Direction;
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+/// Returns true when a text widget (instruction, API key, editor fields) owns the keyboard.
+///
+/// Global shortcuts must check this before reacting so typing never triggers them.
+/// ESC is the one exception: it always cancels a run, even from inside a text field.
+pub fn text_input_focused(ctx: &egui::Context) -> bool {
+    ctx.wants_keyboard_input()
+}
+
+/// Which keys drive manual agent movement
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MovementKeyScheme {
+    Arrows,
+    Wasd,
+    #[default]
+    Both,
+}
+
+impl MovementKeyScheme {
+    pub fn all() -> [Self; 3] {
+        [Self::Arrows, Self::Wasd, Self::Both]
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Arrows => "Arrow keys",
+            Self::Wasd => "WASD",
+            Self::Both => "Arrows + WASD",
+        }
+    }
+
+    /// Key bindings active under this scheme
+    ///
+    /// Q/E/Z/C are the diagonal moves of hex grids and do nothing on square ones.
+    pub fn bindings(&self) -> Vec<(egui::Key, Direction)> {
+        let arrows = [
+            (egui::Key::ArrowUp, Direction::Up),
+            (egui::Key::ArrowDown, Direction::Down),
+            (egui::Key::ArrowLeft, Direction::Left),
+            (egui::Key::ArrowRight, Direction::Right),
+        ];
+        let wasd = [
+            (egui::Key::W, Direction::Up),
+            (egui::Key::S, Direction::Down),
+            (egui::Key::A, Direction::Left),
+            (egui::Key::D, Direction::Right),
+            (egui::Key::Q, Direction::UpLeft),
+            (egui::Key::E, Direction::UpRight),
+            (egui::Key::Z, Direction::DownLeft),
+            (egui::Key::C, Direction::DownRight),
+        ];
+        match self {
+            Self::Arrows => arrows.to_vec(),
+            Self::Wasd => wasd.to_vec(),
+            Self::Both => arrows.into_iter().chain(wasd).collect(),
+        }
+    }
+
+    /// Returns the direction for the first movement key pressed this frame, if any
+    pub fn pressed_direction(&self, input: &egui::InputState) -> Option<Direction> {
+        self.bindings()
+            .into_iter()
+            .find(|(key, _)| input.key_pressed(*key) && input.modifiers.is_none())
+            .map(|(_, direction)| direction)
+    }
+}
+
+/// Every shortcut the app responds to; the help overlay is generated from this list
+///
+/// Keyboard shortcuts are matched through `pressed`, so the binding and its help text
+/// live in one place. Mouse gestures are handled by their widgets and only listed here.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shortcut {
+    CancelRun,
+    SubmitInstruction,
+    MoveAgent,
+    ToggleHelp,
+    CommandPalette,
+    SetGoal,
+    PlaceAgentAnywhere,
+    Zoom,
+    Undo,
+    Redo,
+}
+
+impl Shortcut {
+    pub const ALL: [Shortcut; 10] = [
+        Self::CancelRun,
+        Self::SubmitInstruction,
+        Self::MoveAgent,
+        Self::ToggleHelp,
+        Self::CommandPalette,
+        Self::SetGoal,
+        Self::PlaceAgentAnywhere,
+        Self::Zoom,
+        Self::Undo,
+        Self::Redo,
+    ];
+
+    /// Keys or gesture, as shown in the help overlay
+    pub fn keys(&self, movement_keys: MovementKeyScheme) -> String {
+        match self {
+            Self::CancelRun => "Esc".to_string(),
+            Self::SubmitInstruction => "Shift+Enter".to_string(),
+            Self::MoveAgent => movement_keys.label().to_string(),
+            Self::ToggleHelp => "?".to_string(),
+            Self::CommandPalette => "Ctrl+P".to_string(),
+            Self::SetGoal => "Right-click".to_string(),
+            Self::PlaceAgentAnywhere => "Shift+click".to_string(),
+            Self::Zoom => "Ctrl+scroll".to_string(),
+            Self::Undo => "Ctrl+Z".to_string(),
+            Self::Redo => "Ctrl+Shift+Z".to_string(),
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::CancelRun => "Cancel the running instruction (works while typing)",
+            Self::SubmitInstruction => "Run the instruction (in the instruction box)",
+            Self::MoveAgent => "Move the agent manually when idle (Q/E/Z/C diagonal on hex grids)",
+            Self::ToggleHelp => "Show or hide this shortcut list",
+            Self::CommandPalette => "Search and run an action (load a map, reset the agent...)",
+            Self::SetGoal => "Set or clear the goal on the board",
+            Self::PlaceAgentAnywhere => "Place the agent on a blocked cell (edit mode)",
+            Self::Zoom => "Zoom the board",
+            Self::Undo => "Undo the last map edit (edit mode)",
+            Self::Redo => "Redo the last undone map edit (edit mode)",
+        }
+    }
+
+    /// Whether this keyboard shortcut fired this frame; always false for mouse gestures
+    /// and for movement, which goes through `MovementKeyScheme::pressed_direction`
+    pub fn pressed(&self, input: &egui::InputState) -> bool {
+        match self {
+            Self::CancelRun => input.key_pressed(egui::Key::Escape),
+            Self::SubmitInstruction => input.key_pressed(egui::Key::Enter) && input.modifiers.shift,
+            Self::ToggleHelp => {
+                input.key_pressed(egui::Key::Questionmark)
+                    || (input.key_pressed(egui::Key::Slash) && input.modifiers.shift)
+            }
+            Self::CommandPalette => {
+                input.key_pressed(egui::Key::P) && input.modifiers.command_only()
+            }
+            Self::Undo => input.key_pressed(egui::Key::Z) && input.modifiers.command_only(),
+            Self::Redo => {
+                let command_shift = egui::Modifiers::COMMAND | egui::Modifiers::SHIFT;
+                input.key_pressed(egui::Key::Z) && input.modifiers.matches_exact(command_shift)
+            }
+            Self::MoveAgent | Self::SetGoal | Self::PlaceAgentAnywhere | Self::Zoom => false,
+        }
+    }
+}
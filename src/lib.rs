@@ -3,12 +3,23 @@ mod agent;
 mod animation;
 mod app;
 mod board;
+mod clock;
 mod editor;
 mod events;
+mod keyboard;
 mod map;
 mod map_type;
+mod model_presets;
 mod openrouter;
+mod pathfinding;
+mod patrol;
 mod rendering;
+mod replay;
+mod rng;
+mod route;
+mod settings;
+mod stress_test;
+mod teach;
 mod tool_execution;
 mod ui;
 
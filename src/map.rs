@@ -1,4 +1,14 @@
-#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+use crate::pathfinding::connected_components;
+use crate::rng::SeededRng;
+use std::collections::{HashMap, HashSet};
+
+/// Smallest width or height a map may have; below 2x2 the agent has nowhere to move
+pub const MIN_MAP_DIMENSION: usize = 2;
+
+/// Largest width or height the editor offers
+pub const MAX_MAP_DIMENSION: usize = 100;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum TileKind {
     Empty,
@@ -8,21 +18,132 @@ pub enum TileKind {
     Sand,
     Trail,
     Tree,
+    /// Teleporter; stepping on one moves the agent to the other portal with the same id
+    Portal(u8),
+    /// Refuel station; entering one refills an agent's energy budget
+    Fuel,
     Custom(u32),
 }
 
-#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct MapMetadata {
     pub name: String,
     pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Difficulty rating from 1 (easy) to 5 (hard)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<u8>,
+    /// Cell (x, y) the agent starts on when the map is loaded
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "MapCell::serialize_opt",
+        deserialize_with = "MapCell::deserialize_opt"
+    )]
+    pub spawn: Option<(usize, usize)>,
+    /// Cell (x, y) the agent is meant to reach, making the map a ready-made challenge
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "MapCell::serialize_opt",
+        deserialize_with = "MapCell::deserialize_opt"
+    )]
+    pub goal: Option<(usize, usize)>,
+}
+
+/// JSON form of a cell coordinate in map metadata: `{"x": 6, "y": 10}`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct MapCell {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl MapCell {
+    fn serialize_opt<S: serde::Serializer>(
+        spawn: &Option<(usize, usize)>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        use serde::Serialize;
+        spawn.map(|(x, y)| MapCell { x, y }).serialize(serializer)
+    }
+
+    fn deserialize_opt<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<(usize, usize)>, D::Error> {
+        use serde::Deserialize;
+        Ok(Option::<MapCell>::deserialize(deserializer)?.map(|p| (p.x, p.y)))
+    }
+}
+
+/// One row-major layer of optional decoration tiles
+pub type DecorationLayer = Vec<Vec<Option<TileKind>>>;
+
+/// Row-major terrain heights, one per tile
+pub type ElevationLayer = Vec<Vec<i32>>;
+
+/// How cells connect to each other
+///
+/// Hex grids reuse the same row-major storage as "odd-r" offset coordinates: odd rows
+/// are shifted half a cell to the right, giving every cell six neighbors.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum Topology {
+    /// Four neighbors: up, down, left, right
+    #[default]
+    Square,
+    /// Six neighbors: left, right and two each above and below
+    Hex,
 }
 
-#[derive(Clone, Debug, serde::Serialize)]
+impl Topology {
+    pub const ALL: [Topology; 2] = [Self::Square, Self::Hex];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Square => "Square",
+            Self::Hex => "Hex",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct GridMap {
     pub metadata: Option<MapMetadata>,
     width: usize,
     height: usize,
-    tiles: Vec<Vec<TileKind>>,
+    /// Row-major, indexed by `y * width + x`; serialized as nested rows
+    tiles: Vec<TileKind>,
+    /// Optional purely visual layer drawn over `tiles`; never affects traversability
+    decorations: Option<DecorationLayer>,
+    /// Optional terrain heights; climbing costs extra (see `climb_cost`), absent means flat
+    elevation: Option<ElevationLayer>,
+    /// Adjacency model; an app setting rather than part of the map file
+    topology: Topology,
+}
+
+impl serde::Serialize for GridMap {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("GridMap", 6)?;
+        state.serialize_field("metadata", &self.metadata)?;
+        state.serialize_field("width", &self.width)?;
+        state.serialize_field("height", &self.height)?;
+        // Rows rather than the flat buffer, so the file format stays the same
+        state.serialize_field("tiles", &self.rows().collect::<Vec<_>>())?;
+        match &self.decorations {
+            Some(layer) => state.serialize_field("decorations", layer)?,
+            None => state.skip_field("decorations")?,
+        }
+        match &self.elevation {
+            Some(layer) => state.serialize_field("elevation", layer)?,
+            None => state.skip_field("elevation")?,
+        }
+        state.end()
+    }
 }
 
 impl<'de> serde::Deserialize<'de> for GridMap {
@@ -34,15 +155,50 @@ impl<'de> serde::Deserialize<'de> for GridMap {
         struct GridMapData {
             name: Option<String>,
             description: Option<String>,
+            #[serde(default)]
+            author: Option<String>,
+            #[serde(default)]
+            difficulty: Option<u8>,
+            #[serde(default)]
+            spawn: Option<MapCell>,
+            #[serde(default)]
+            goal: Option<MapCell>,
             width: usize,
             height: usize,
             tiles: Vec<Vec<TileKind>>,
+            #[serde(default)]
+            decorations: Option<DecorationLayer>,
+            #[serde(default)]
+            elevation: Option<ElevationLayer>,
         }
 
         let data = GridMapData::deserialize(deserializer)?;
+        if data.tiles.len() != data.height {
+            return Err(serde::de::Error::custom(format!(
+                "Map declares height {} but has {} rows",
+                data.height,
+                data.tiles.len()
+            )));
+        }
+        let ragged = data.tiles.iter().enumerate().find(|(_, row)| row.len() != data.width);
+        if let Some((y, row)) = ragged {
+            return Err(serde::de::Error::custom(format!(
+                "Map declares width {} but row {} has {} tiles",
+                data.width,
+                y,
+                row.len()
+            )));
+        }
 
         let metadata = match (data.name, data.description) {
-            (Some(name), Some(description)) => Some(MapMetadata { name, description }),
+            (Some(name), Some(description)) => Some(MapMetadata {
+                name,
+                description,
+                author: data.author,
+                difficulty: data.difficulty,
+                spawn: data.spawn.map(|p| (p.x, p.y)),
+                goal: data.goal.map(|p| (p.x, p.y)),
+            }),
             _ => None,
         };
 
@@ -50,15 +206,104 @@ impl<'de> serde::Deserialize<'de> for GridMap {
             metadata,
             width: data.width,
             height: data.height,
-            tiles: data.tiles,
+            tiles: data.tiles.into_iter().flatten().collect(),
+            decorations: data.decorations,
+            elevation: data.elevation,
+            topology: Topology::default(),
         })
     }
 }
 
 impl GridMap {
+    /// Map of `fill` tiles; each side is at least `MIN_MAP_DIMENSION`
     pub fn new(width: usize, height: usize, fill: TileKind) -> Self {
-        let tiles = vec![vec![fill; width]; height];
-        Self { metadata: None, width, height, tiles }
+        let (width, height) = (width.max(MIN_MAP_DIMENSION), height.max(MIN_MAP_DIMENSION));
+        let tiles = vec![fill; width * height];
+        Self {
+            metadata: None,
+            width,
+            height,
+            tiles,
+            decorations: None,
+            elevation: None,
+            topology: Topology::default(),
+        }
+    }
+
+    /// Designated agent start cell from the metadata, if set and on the map
+    pub fn spawn_point(&self) -> Option<(usize, usize)> {
+        let (x, y) = self.metadata.as_ref()?.spawn?;
+        self.in_bounds(x, y).then_some((x, y))
+    }
+
+    /// Designated goal cell from the metadata, if set and on the map
+    pub fn goal_point(&self) -> Option<(usize, usize)> {
+        let (x, y) = self.metadata.as_ref()?.goal?;
+        self.in_bounds(x, y).then_some((x, y))
+    }
+
+    /// Copy of this map at a new size
+    ///
+    /// The overlapping top-left region is kept and new cells are filled with `fill`.
+    /// Metadata, decorations and elevation in the kept region carry over unchanged; new
+    /// cells are at elevation 0. Each side is at least `MIN_MAP_DIMENSION`.
+    pub fn resized(&self, width: usize, height: usize, fill: TileKind) -> GridMap {
+        let (width, height) = (width.max(MIN_MAP_DIMENSION), height.max(MIN_MAP_DIMENSION));
+        let tiles = (0..height)
+            .flat_map(|y| (0..width).map(move |x| self.get(x, y).copied().unwrap_or(fill)))
+            .collect();
+        let decorations = self.decorations.as_ref().map(|_| {
+            (0..height)
+                .map(|y| (0..width).map(|x| self.decoration(x, y).copied()).collect())
+                .collect()
+        });
+        let elevation = self.elevation.as_ref().map(|_| {
+            (0..height)
+                .map(|y| (0..width).map(|x| self.elevation(x, y)).collect())
+                .collect()
+        });
+        let mut map = GridMap {
+            metadata: self.metadata.clone(),
+            width,
+            height,
+            tiles,
+            decorations,
+            elevation,
+            topology: self.topology,
+        };
+        map.drop_empty_decorations();
+        map
+    }
+
+    /// Parse a map from JSON in the same format as the bundled map files
+    ///
+    /// Fails if the JSON is malformed or the tile rows don't match the declared size.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let map: GridMap =
+            serde_json::from_str(json).map_err(|e| format!("Invalid map JSON: {}", e))?;
+        if map.width < MIN_MAP_DIMENSION || map.height < MIN_MAP_DIMENSION {
+            return Err(format!(
+                "Map must be at least {}x{} ({}x{})",
+                MIN_MAP_DIMENSION, MIN_MAP_DIMENSION, map.width, map.height
+            ));
+        }
+        if let Some(layer) = &map.decorations
+            && (layer.len() != map.height || layer.iter().any(|row| row.len() != map.width))
+        {
+            return Err(format!(
+                "Decoration layer must be {}x{} like the tiles",
+                map.width, map.height
+            ));
+        }
+        if let Some(layer) = &map.elevation
+            && (layer.len() != map.height || layer.iter().any(|row| row.len() != map.width))
+        {
+            return Err(format!(
+                "Elevation layer must be {}x{} like the tiles",
+                map.width, map.height
+            ));
+        }
+        Ok(map)
     }
 
     #[inline]
@@ -73,38 +318,159 @@ impl GridMap {
     }
 
     pub fn get(&self, x: usize, y: usize) -> Option<&TileKind> {
-        self.tiles.get(y).and_then(|row| row.get(x))
+        self.in_bounds(x, y).then(|| &self.tiles[y * self.width + x])
     }
 
     pub fn set(&mut self, x: usize, y: usize, kind: TileKind) -> bool {
-        if let Some(row) = self.tiles.get_mut(y) {
-            if let Some(tile) = row.get_mut(x) {
-                *tile = kind;
-                return true;
-            }
+        if !self.in_bounds(x, y) {
+            return false;
+        }
+        self.tiles[y * self.width + x] = kind;
+        true
+    }
+
+    /// Decoration drawn over (x, y), if the map has one there
+    pub fn decoration(&self, x: usize, y: usize) -> Option<&TileKind> {
+        self.decorations.as_ref()?.get(y)?.get(x)?.as_ref()
+    }
+
+    /// Place (Some) or remove (None) the decoration at (x, y), creating the layer on demand
+    pub fn set_decoration(&mut self, x: usize, y: usize, kind: Option<TileKind>) -> bool {
+        if !self.in_bounds(x, y) {
+            return false;
+        }
+        if kind.is_none() && self.decorations.is_none() {
+            return true;
+        }
+        let (width, height) = (self.width, self.height);
+        let layer = self
+            .decorations
+            .get_or_insert_with(|| vec![vec![None; width]; height]);
+        layer[y][x] = kind;
+        if kind.is_none() {
+            self.drop_empty_decorations();
+        }
+        true
+    }
+
+    pub fn has_decorations(&self) -> bool {
+        self.decorations.is_some()
+    }
+
+    /// Remove the decoration layer entirely
+    pub fn clear_decorations(&mut self) {
+        self.decorations = None;
+    }
+
+    /// The decoration layer, if any, for serialization
+    pub fn decorations(&self) -> Option<&DecorationLayer> {
+        self.decorations.as_ref()
+    }
+
+    /// A layer with nothing on it is dropped, so an undecorated map stays layer-free
+    fn drop_empty_decorations(&mut self) {
+        if let Some(layer) = &self.decorations
+            && layer.iter().flatten().all(Option::is_none)
+        {
+            self.decorations = None;
+        }
+    }
+
+    /// Height of (x, y); 0 off the map or when the map has no elevation layer
+    pub fn elevation(&self, x: usize, y: usize) -> i32 {
+        self.elevation
+            .as_ref()
+            .and_then(|layer| layer.get(y)?.get(x).copied())
+            .unwrap_or(0)
+    }
+
+    /// Set the height of (x, y), creating a flat layer on first use
+    /// Returns false if (x, y) is off the map
+    pub fn set_elevation(&mut self, x: usize, y: usize, height: i32) -> bool {
+        if !self.in_bounds(x, y) {
+            return false;
         }
-        false
+        let (width, rows) = (self.width, self.height);
+        self.elevation.get_or_insert_with(|| vec![vec![0; width]; rows])[y][x] = height;
+        true
+    }
+
+    pub fn has_elevation(&self) -> bool {
+        self.elevation.is_some()
+    }
+
+    /// Flatten the map by dropping the elevation layer
+    pub fn clear_elevation(&mut self) {
+        self.elevation = None;
+    }
+
+    pub fn elevation_layer(&self) -> Option<&ElevationLayer> {
+        self.elevation.as_ref()
+    }
+
+    /// Lowest and highest elevation, or None on a flat map
+    pub fn elevation_range(&self) -> Option<(i32, i32)> {
+        let mut heights = self.elevation.as_ref()?.iter().flatten();
+        let first = *heights.next()?;
+        Some(heights.fold((first, first), |(lo, hi), &h| (lo.min(h), hi.max(h))))
+    }
+
+    /// Extra cost of stepping from `from` onto `to`: one per unit of height gained
+    ///
+    /// Going downhill or staying level is free, so costs never drop below the tile's own.
+    pub fn climb_cost(&self, from: (usize, usize), to: (usize, usize)) -> u32 {
+        let rise = self.elevation(to.0, to.1).saturating_sub(self.elevation(from.0, from.1));
+        rise.max(0) as u32
     }
 
     pub fn clear(&mut self, kind: TileKind) {
-        for row in &mut self.tiles {
-            for tile in row {
-                *tile = kind;
+        self.tiles.fill(kind);
+    }
+
+    /// Cells on the outer ring, each listed once (maps one cell wide or tall included)
+    pub fn border_cells(&self) -> Vec<(usize, usize)> {
+        let (w, h) = (self.width, self.height);
+        if w == 0 || h == 0 {
+            return Vec::new();
+        }
+        let mut cells: Vec<(usize, usize)> = (0..w).map(|x| (x, 0)).collect();
+        if h > 1 {
+            cells.extend((0..w).map(|x| (x, h - 1)));
+        }
+        for y in 1..h - 1 {
+            cells.push((0, y));
+            if w > 1 {
+                cells.push((w - 1, y));
+            }
+        }
+        cells
+    }
+
+    /// Set the outer ring of cells to `tile`, leaving the interior and metadata alone
+    ///
+    /// Returns each cell that changed with the tile it replaced, so the edit can be undone.
+    pub fn add_border(&mut self, tile: TileKind) -> Vec<((usize, usize), TileKind)> {
+        let mut replaced = Vec::new();
+        for (x, y) in self.border_cells() {
+            if let Some(&old) = self.get(x, y)
+                && old != tile
+            {
+                self.set(x, y, tile);
+                replaced.push(((x, y), old));
             }
         }
+        replaced
     }
 
     pub fn fill_rect(&mut self, x: usize, y: usize, w: usize, h: usize, kind: TileKind) {
         let x1 = (x + w).min(self.width);
         let y1 = (y + h).min(self.height);
+        if x >= x1 {
+            return;
+        }
         for yy in y..y1 {
-            for xx in x..x1 {
-                if let Some(row) = self.tiles.get_mut(yy) {
-                    if let Some(tile) = row.get_mut(xx) {
-                        *tile = kind;
-                    }
-                }
-            }
+            let start = yy * self.width;
+            self.tiles[start + x..start + x1].fill(kind);
         }
     }
 
@@ -114,16 +480,515 @@ impl GridMap {
             .unwrap_or(false)
     }
 
-    /// Get reference to the tiles grid
-    pub fn tiles(&self) -> &Vec<Vec<TileKind>> {
+    /// Find the cell closest to `from` (Manhattan distance) that `accept` allows, including
+    /// `from` itself
+    ///
+    /// Searches expanding diamond-shaped rings; ties within a ring go to the smaller
+    /// x offset first, then up before down. Returns None if no cell on the map is accepted.
+    pub fn nearest_cell_where(
+        &self,
+        from: (usize, usize),
+        accept: impl Fn(usize, usize) -> bool,
+    ) -> Option<(usize, usize)> {
+        let (fx, fy) = (from.0 as i64, from.1 as i64);
+        let max_radius = (self.width + self.height) as i64 + fx + fy;
+        for radius in 0..=max_radius {
+            for dx in -radius..=radius {
+                let dy = radius - dx.abs();
+                for (cx, cy) in [(fx + dx, fy - dy), (fx + dx, fy + dy)] {
+                    if cx < 0 || cy < 0 {
+                        continue;
+                    }
+                    let (cx, cy) = (cx as usize, cy as usize);
+                    if self.in_bounds(cx, cy) && accept(cx, cy) {
+                        return Some((cx, cy));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Find the exit of the portal at (x, y): the first other portal with the same id
+    ///
+    /// Returns None if (x, y) isn't a portal or the portal is unpaired. Pathfinding can
+    /// treat this as an extra edge from the portal cell.
+    pub fn portal_exit(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        let Some(&TileKind::Portal(id)) = self.get(x, y) else {
+            return None;
+        };
+        self.portals()
+            .find(|&(other_id, px, py)| other_id == id && (px, py) != (x, y))
+            .map(|(_, px, py)| (px, py))
+    }
+
+    /// Iterate over all portals as (id, x, y) in row-major order
+    pub fn portals(&self) -> impl Iterator<Item = (u8, usize, usize)> + '_ {
+        let width = self.width;
+        self.tiles.iter().enumerate().filter_map(move |(i, tile)| match tile {
+            TileKind::Portal(id) => Some((*id, i % width, i / width)),
+            _ => None,
+        })
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+
+    /// All tiles in row-major order, indexed by `y * width + x`
+    pub fn tiles_flat(&self) -> &[TileKind] {
         &self.tiles
     }
+
+    /// The tile rows from north to south, borrowed from the flat buffer
+    pub fn rows(&self) -> impl Iterator<Item = &[TileKind]> + '_ {
+        self.tiles.chunks(self.width.max(1))
+    }
+
+    /// Stable hash of the map contents (dimensions, tiles, elevation and topology)
+    ///
+    /// Metadata is deliberately excluded so renaming a map or editing its description
+    /// doesn't invalidate caches keyed on the terrain, and the hash survives JSON round-trips.
+    pub fn content_hash(&self) -> u64 {
+        use std::hash::{Hash, Hasher};
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        self.width.hash(&mut hasher);
+        self.height.hash(&mut hasher);
+        // Hashed row by row, so the value matches the old nested-rows storage
+        self.height.hash(&mut hasher);
+        for row in self.rows() {
+            row.hash(&mut hasher);
+        }
+        self.elevation.hash(&mut hasher);
+        self.topology.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+// Procedural generators. Each one is deterministic for a given seed, keeps the open cells
+// connected, and records a spawn and goal in the metadata.
+impl GridMap {
+    /// Perfect maze carved by a randomized depth-first search (recursive backtracker)
+    ///
+    /// Cells sit on odd coordinates with walls between them; the search knocks out the
+    /// wall to a random unvisited neighbor and backtracks at dead ends, so every open cell
+    /// is reachable by exactly one route. The size is rounded down to odd (at least 3).
+    /// Spawn is the top-left cell, goal the bottom-right one, both on the maze path.
+    pub fn maze(width: usize, height: usize, seed: u64) -> GridMap {
+        let odd = |side: usize| (side.max(3) - 1) / 2 * 2 + 1;
+        let mut map = GridMap::new(odd(width), odd(height), TileKind::Wall);
+        let (cells_x, cells_y) = (map.width / 2, map.height / 2);
+        let mut visited = vec![false; cells_x * cells_y];
+        let mut rng = SeededRng::new(seed);
+
+        // Explicit stack instead of recursion, so large mazes can't overflow
+        let mut stack = vec![(0, 0)];
+        visited[0] = true;
+        map.set(1, 1, TileKind::Grass);
+        while let Some(&(cx, cy)) = stack.last() {
+            let unvisited: Vec<(usize, usize)> = [(0, -1), (0, 1), (-1, 0), (1, 0)]
+                .into_iter()
+                .filter_map(|(dx, dy)| {
+                    let (nx, ny) = (cx as i64 + dx, cy as i64 + dy);
+                    (nx >= 0 && ny >= 0 && nx < cells_x as i64 && ny < cells_y as i64)
+                        .then_some((nx as usize, ny as usize))
+                })
+                .filter(|&(nx, ny)| !visited[ny * cells_x + nx])
+                .collect();
+            if unvisited.is_empty() {
+                stack.pop();
+                continue;
+            }
+            let (nx, ny) = unvisited[rng.below(unvisited.len() as u64) as usize];
+            visited[ny * cells_x + nx] = true;
+            // Open the wall between the two cells, then the new cell itself
+            map.set(cx + nx + 1, cy + ny + 1, TileKind::Grass);
+            map.set(nx * 2 + 1, ny * 2 + 1, TileKind::Grass);
+            stack.push((nx, ny));
+        }
+
+        let (width, height) = (map.width, map.height);
+        map.metadata = Some(MapMetadata {
+            name: "Perfect maze".to_string(),
+            description: format!("Recursive backtracker maze (seed {})", seed),
+            author: None,
+            difficulty: None,
+            spawn: Some((1, 1)),
+            goal: Some((width - 2, height - 2)),
+        });
+        map
+    }
+
+    /// Organic cave grown by cellular-automata smoothing of random noise
+    ///
+    /// Each interior cell starts as wall with probability `fill_prob`. Every pass then
+    /// turns a cell to wall when more than 4 of its 8 neighbors are walls (the map edge
+    /// counts as wall) and opens it when fewer than 4 are, over `iterations` passes.
+    /// Finally only the largest open region is kept, so nothing is cut off. Spawn and
+    /// goal are the first and last cells of that region in reading order.
+    pub fn cave(
+        width: usize,
+        height: usize,
+        seed: u64,
+        fill_prob: f32,
+        iterations: usize,
+    ) -> GridMap {
+        let mut map = GridMap::new(width, height, TileKind::Grass);
+        let (width, height) = (map.width, map.height);
+        let mut rng = SeededRng::new(seed);
+        let threshold = (fill_prob.clamp(0.0, 1.0) as f64 * u32::MAX as f64) as u64;
+        let mut walls: Vec<bool> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let edge = x == 0 || y == 0 || x == width - 1 || y == height - 1;
+                // Always draw, so the noise for a seed doesn't depend on the size of the edge
+                let wall = (rng.next_u64() >> 32) < threshold;
+                edge || wall
+            })
+            .collect();
+
+        for _ in 0..iterations {
+            let walled_neighbors = |x: usize, y: usize| {
+                (-1..=1i64)
+                    .flat_map(|dy| (-1..=1i64).map(move |dx| (dx, dy)))
+                    .filter(|&offset| offset != (0, 0))
+                    .filter(|&(dx, dy)| {
+                        let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+                        nx < 0
+                            || ny < 0
+                            || nx >= width as i64
+                            || ny >= height as i64
+                            || walls[ny as usize * width + nx as usize]
+                    })
+                    .count()
+            };
+            walls = (0..height)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .map(|(x, y)| {
+                    if x == 0 || y == 0 || x == width - 1 || y == height - 1 {
+                        return true;
+                    }
+                    match walled_neighbors(x, y) {
+                        0..4 => false,
+                        4 => walls[y * width + x],
+                        _ => true,
+                    }
+                })
+                .collect();
+        }
+
+        for (index, _) in walls.iter().enumerate().filter(|&(_, &wall)| wall) {
+            map.set(index % width, index / width, TileKind::Wall);
+        }
+        let mut regions = connected_components(&map, |x, y| map.is_traversable(x, y));
+        for region in regions.iter().skip(1) {
+            for &(x, y) in region {
+                map.set(x, y, TileKind::Wall);
+            }
+        }
+        let cave = if regions.is_empty() {
+            // Solid rock; open the middle so there's somewhere to stand
+            let center = (width / 2, height / 2);
+            map.set(center.0, center.1, TileKind::Grass);
+            vec![center]
+        } else {
+            regions.swap_remove(0)
+        };
+
+        let by_reading_order = |&(x, y): &(usize, usize)| (y, x);
+        map.metadata = Some(MapMetadata {
+            name: "Cave".to_string(),
+            description: format!("Cellular-automata cave (seed {})", seed),
+            author: None,
+            difficulty: None,
+            spawn: cave.iter().copied().min_by_key(by_reading_order),
+            goal: cave.iter().copied().max_by_key(by_reading_order),
+        });
+        map
+    }
+
+    /// Maze of one-cell walls between corridors `corridor_width` cells wide
+    ///
+    /// Walls sit on a lattice of nodes spaced `corridor_width + 1` apart and grow from
+    /// random nodes onto free neighboring ones, so they form trees and never enclose a
+    /// pocket. `complexity` (0..=1) sets how many walls are grown. The size is rounded
+    /// down to fit whole corridors. Spawn is top-left, goal bottom-right.
+    pub fn wall_maze(
+        width: usize,
+        height: usize,
+        seed: u64,
+        corridor_width: usize,
+        complexity: f32,
+    ) -> GridMap {
+        let step = corridor_width.max(1) + 1;
+        let nodes_x = (width.saturating_sub(1) / step).max(1);
+        let nodes_y = (height.saturating_sub(1) / step).max(1);
+        let mut map = GridMap::new(nodes_x * step + 1, nodes_y * step + 1, TileKind::Grass);
+        map.add_border(TileKind::Wall);
+
+        // Border nodes start out walled; interior ones are free until a wall reaches them
+        let columns = nodes_x + 1;
+        let mut walled: Vec<bool> = (0..=nodes_y)
+            .flat_map(|ny| (0..=nodes_x).map(move |nx| (nx, ny)))
+            .map(|(nx, ny)| nx == 0 || ny == 0 || nx == nodes_x || ny == nodes_y)
+            .collect();
+
+        let mut rng = SeededRng::new(seed);
+        let complexity = complexity.clamp(0.0, 1.0);
+        let walls = (complexity * (nodes_x * nodes_y) as f32 * 0.75).round() as usize;
+        let max_length = 1 + (complexity * (nodes_x + nodes_y) as f32) as usize;
+        for _ in 0..walls {
+            let mut node = (
+                rng.below(nodes_x as u64 + 1) as usize,
+                rng.below(nodes_y as u64 + 1) as usize,
+            );
+            // Start from an existing wall or a fresh free node
+            walled[node.1 * columns + node.0] = true;
+            map.set(node.0 * step, node.1 * step, TileKind::Wall);
+            for _ in 0..max_length {
+                let free: Vec<(usize, usize)> = [(0, -1), (0, 1), (-1, 0), (1, 0)]
+                    .into_iter()
+                    .filter_map(|(dx, dy)| {
+                        let (nx, ny) = (node.0 as i64 + dx, node.1 as i64 + dy);
+                        (nx >= 0 && ny >= 0 && nx <= nodes_x as i64 && ny <= nodes_y as i64)
+                            .then_some((nx as usize, ny as usize))
+                    })
+                    .filter(|&(nx, ny)| !walled[ny * columns + nx])
+                    .collect();
+                if free.is_empty() {
+                    break;
+                }
+                let next = free[rng.below(free.len() as u64) as usize];
+                walled[next.1 * columns + next.0] = true;
+                let (x0, x1) = (node.0.min(next.0) * step, node.0.max(next.0) * step);
+                let (y0, y1) = (node.1.min(next.1) * step, node.1.max(next.1) * step);
+                map.fill_rect(x0, y0, x1 - x0 + 1, y1 - y0 + 1, TileKind::Wall);
+                node = next;
+            }
+        }
+
+        let (width, height) = (map.width, map.height);
+        map.metadata = Some(MapMetadata {
+            name: "Maze".to_string(),
+            description: format!("Generated maze (seed {})", seed),
+            author: None,
+            difficulty: None,
+            spawn: Some((1, 1)),
+            goal: Some((width - 2, height - 2)),
+        });
+        map
+    }
+
+    /// Rectangular rooms in solid rock, each joined to the previous one by a corridor
+    ///
+    /// Room sides are drawn from `room_size` (min, max). Up to `room_count` rooms are
+    /// placed without touching each other; fewer fit on a crowded map. Spawn is in the
+    /// first room, goal in the last.
+    pub fn rooms(
+        width: usize,
+        height: usize,
+        seed: u64,
+        room_count: usize,
+        room_size: (usize, usize),
+    ) -> GridMap {
+        let mut map = GridMap::new(width, height, TileKind::Wall);
+        let (width, height) = (map.width, map.height);
+        // Rooms keep a wall ring around them, so they need at least 3 cells of headroom
+        let largest = width.min(height).saturating_sub(2).max(1);
+        let min_side = room_size.0.clamp(1, largest);
+        let max_side = room_size.1.clamp(min_side, largest);
+
+        let mut rng = SeededRng::new(seed);
+        let mut rooms: Vec<(usize, usize, usize, usize)> = Vec::new();
+        let mut attempts = room_count.max(1) * 20;
+        while rooms.len() < room_count.max(1) && attempts > 0 {
+            attempts -= 1;
+            let side = |rng: &mut SeededRng| {
+                min_side + rng.below((max_side - min_side + 1) as u64) as usize
+            };
+            let (w, h) = (side(&mut rng), side(&mut rng));
+            if w + 2 > width || h + 2 > height {
+                continue;
+            }
+            let x = 1 + rng.below((width - w - 1) as u64) as usize;
+            let y = 1 + rng.below((height - h - 1) as u64) as usize;
+            // Keep a wall between rooms so they stay distinct
+            let touches = rooms.iter().any(|&(rx, ry, rw, rh)| {
+                x <= rx + rw && rx <= x + w && y <= ry + rh && ry <= y + h
+            });
+            if !touches {
+                rooms.push((x, y, w, h));
+            }
+        }
+        if rooms.is_empty() {
+            // Nothing fit; fall back to one room filling the interior
+            rooms.push((1, 1, width - 2, height - 2));
+        }
+
+        let center = |&(x, y, w, h): &(usize, usize, usize, usize)| (x + w / 2, y + h / 2);
+        for room in &rooms {
+            map.fill_rect(room.0, room.1, room.2, room.3, TileKind::Grass);
+        }
+        // L-shaped corridors between consecutive rooms, turning at a random corner
+        for pair in rooms.windows(2) {
+            let (a, b) = (center(&pair[0]), center(&pair[1]));
+            let corner = if rng.below(2) == 0 { (b.0, a.1) } else { (a.0, b.1) };
+            for (from, to) in [(a, corner), (corner, b)] {
+                let (x0, x1) = (from.0.min(to.0), from.0.max(to.0));
+                let (y0, y1) = (from.1.min(to.1), from.1.max(to.1));
+                map.fill_rect(x0, y0, x1 - x0 + 1, y1 - y0 + 1, TileKind::Grass);
+            }
+        }
+
+        map.metadata = Some(MapMetadata {
+            name: "Rooms".to_string(),
+            description: format!("{} generated rooms (seed {})", rooms.len(), seed),
+            author: None,
+            difficulty: None,
+            spawn: rooms.first().map(center),
+            goal: rooms.last().map(center),
+        });
+        map
+    }
+}
+
+/// Which tile kinds an agent may walk on, so traversability can be varied per experiment
+///
+/// Only the plain terrain kinds are configurable. Trail, portal and fuel tiles are always
+/// passable and custom tiles always block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraversabilityRules {
+    passable: HashSet<TileKind>,
+}
+
+impl Default for TraversabilityRules {
+    fn default() -> Self {
+        Self {
+            passable: [TileKind::Empty, TileKind::Grass, TileKind::Sand]
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+impl TraversabilityRules {
+    /// Tile kinds whose passability can be toggled
+    pub const CONFIGURABLE: [TileKind; 6] = [
+        TileKind::Empty,
+        TileKind::Grass,
+        TileKind::Sand,
+        TileKind::Water,
+        TileKind::Wall,
+        TileKind::Tree,
+    ];
+
+    pub fn is_passable(&self, tile: &TileKind) -> bool {
+        match tile {
+            TileKind::Trail | TileKind::Portal(_) | TileKind::Fuel => true,
+            TileKind::Custom(_) => false,
+            _ => self.passable.contains(tile),
+        }
+    }
+
+    /// Check the tile at (x, y); off-map cells are never passable
+    pub fn is_cell_passable(&self, map: &GridMap, x: usize, y: usize) -> bool {
+        map.get(x, y).is_some_and(|tile| self.is_passable(tile))
+    }
+
+    /// Allow or forbid walking on a configurable tile kind
+    pub fn set_passable(&mut self, tile: TileKind, passable: bool) {
+        if !Self::CONFIGURABLE.contains(&tile) {
+            return;
+        }
+        if passable {
+            self.passable.insert(tile);
+        } else {
+            self.passable.remove(&tile);
+        }
+    }
+
+    /// Names of the configurable kinds that are (or aren't) passable, for prompts and messages
+    pub fn tile_names(&self, passable: bool) -> Vec<&'static str> {
+        Self::CONFIGURABLE
+            .iter()
+            .filter(|tile| self.is_passable(tile) == passable)
+            .map(|tile| tile.name())
+            .collect()
+    }
+}
+
+/// Movement cost of entering each tile kind, consulted by the weighted pathfinder
+///
+/// Starts from `TileKind::movement_cost` (sand is slow, grass and trails are quick);
+/// trail, portal, fuel and custom tiles always cost their default.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TileCosts {
+    costs: HashMap<TileKind, u32>,
+}
+
+impl Default for TileCosts {
+    fn default() -> Self {
+        Self {
+            costs: TraversabilityRules::CONFIGURABLE
+                .into_iter()
+                .map(|tile| (tile, tile.movement_cost()))
+                .collect(),
+        }
+    }
+}
+
+impl TileCosts {
+    /// Cost of a plain step, e.g. onto grass
+    pub const DEFAULT_COST: u32 = 1;
+    pub const MAX_COST: u32 = 99;
+
+    pub fn cost(&self, tile: &TileKind) -> u32 {
+        self.costs.get(tile).copied().unwrap_or_else(|| tile.movement_cost())
+    }
+
+    /// Cost of the tile at (x, y); off-map cells use the default
+    pub fn cell_cost(&self, map: &GridMap, x: usize, y: usize) -> u32 {
+        map.get(x, y).map_or(Self::DEFAULT_COST, |tile| self.cost(tile))
+    }
+
+    /// True when every tile costs the default, so cost only counts moves
+    pub fn is_uniform(&self) -> bool {
+        self.costs.values().all(|&cost| cost == Self::DEFAULT_COST)
+    }
+
+    /// Configurable kinds whose cost differs from the default, in `CONFIGURABLE` order
+    pub fn non_default(&self) -> impl Iterator<Item = (TileKind, u32)> + '_ {
+        TraversabilityRules::CONFIGURABLE
+            .into_iter()
+            .map(|tile| (tile, self.cost(&tile)))
+            .filter(|&(_, cost)| cost != Self::DEFAULT_COST)
+    }
+
+    /// Set the cost of a configurable tile kind (clamped to 1..=MAX_COST)
+    pub fn set_cost(&mut self, tile: TileKind, cost: u32) {
+        if TraversabilityRules::CONFIGURABLE.contains(&tile) {
+            self.costs.insert(tile, cost.clamp(1, Self::MAX_COST));
+        }
+    }
 }
 
 impl TileKind {
     /// Returns true if this tile can be walked on by agents
     pub fn is_traversable(&self) -> bool {
-        matches!(self, TileKind::Empty | TileKind::Grass | TileKind::Sand | TileKind::Trail)
+        matches!(
+            self,
+            TileKind::Empty
+                | TileKind::Grass
+                | TileKind::Sand
+                | TileKind::Trail
+                | TileKind::Portal(_)
+                | TileKind::Fuel
+        )
     }
 
     /// Returns true if this tile blocks movement
@@ -131,6 +996,40 @@ impl TileKind {
         !self.is_traversable()
     }
 
+    /// Default cost of entering this tile: the starting point of `TileCosts`
+    ///
+    /// Blocking kinds only come into play when traversability rules open them up, so
+    /// they are priced as rough going rather than left at 1.
+    pub fn movement_cost(&self) -> u32 {
+        match self {
+            TileKind::Sand => 3,
+            TileKind::Tree => 2,
+            TileKind::Water => 5,
+            TileKind::Wall => 10,
+            TileKind::Empty
+            | TileKind::Grass
+            | TileKind::Trail
+            | TileKind::Portal(_)
+            | TileKind::Fuel
+            | TileKind::Custom(_) => TileCosts::DEFAULT_COST,
+        }
+    }
+
+    /// Parse a tile from its serialized name (e.g. "wall"), as used by tool arguments
+    /// Only the built-in tile kinds are accepted; trail and custom tiles can't be named
+    pub fn from_name(name: &str) -> Option<TileKind> {
+        match name.to_lowercase().as_str() {
+            "empty" => Some(TileKind::Empty),
+            "wall" => Some(TileKind::Wall),
+            "water" => Some(TileKind::Water),
+            "grass" => Some(TileKind::Grass),
+            "sand" => Some(TileKind::Sand),
+            "tree" => Some(TileKind::Tree),
+            "fuel" => Some(TileKind::Fuel),
+            _ => None,
+        }
+    }
+
     /// Returns a display name for this tile type
     pub fn name(&self) -> &'static str {
         match self {
@@ -139,9 +1038,231 @@ impl TileKind {
             TileKind::Water => "water",
             TileKind::Grass => "grass",
             TileKind::Sand => "sand",
-            TileKind::Trail => "grass", // Hide trail from LLM - functionally identical to grass
+            TileKind::Trail => "trail",
             TileKind::Tree => "tree",
+            TileKind::Portal(_) => "portal",
+            TileKind::Fuel => "fuel",
             TileKind::Custom(_) => "custom",
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 3x2 map with a distinct tile in every cell
+    fn sample() -> GridMap {
+        let mut map = GridMap::new(3, 2, TileKind::Empty);
+        map.set(0, 0, TileKind::Wall);
+        map.set(1, 0, TileKind::Water);
+        map.set(2, 0, TileKind::Sand);
+        map.set(0, 1, TileKind::Tree);
+        map.set(1, 1, TileKind::Grass);
+        map.set(2, 1, TileKind::Portal(1));
+        map
+    }
+
+    #[test]
+    fn resized_shrink_keeps_top_left() {
+        let map = sample().resized(2, 2, TileKind::Grass);
+        assert_eq!((map.width(), map.height()), (2, 2));
+        assert_eq!(map.get(0, 0), Some(&TileKind::Wall));
+        assert_eq!(map.get(1, 0), Some(&TileKind::Water));
+        assert_eq!(map.get(1, 1), Some(&TileKind::Grass));
+        assert_eq!(map.get(2, 0), None);
+        assert_eq!(map.get(0, 2), None);
+    }
+
+    #[test]
+    fn resized_grow_fills_new_cells() {
+        let original = sample();
+        let map = original.resized(4, 3, TileKind::Grass);
+        assert_eq!((map.width(), map.height()), (4, 3));
+        for y in 0..3 {
+            for x in 0..4 {
+                let expected = original.get(x, y).copied().unwrap_or(TileKind::Grass);
+                assert_eq!(map.get(x, y), Some(&expected), "cell ({}, {})", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn resized_same_size_is_identical() {
+        let original = sample();
+        let map = original.resized(3, 2, TileKind::Grass);
+        assert_eq!(map.tiles, original.tiles);
+        assert_eq!(map.content_hash(), original.content_hash());
+    }
+
+    #[test]
+    fn nearest_cell_searches_rings_outward() {
+        let mut map = GridMap::new(5, 5, TileKind::Grass);
+        map.fill_rect(1, 1, 3, 3, TileKind::Wall);
+        let open = |x, y| map.is_traversable(x, y);
+        assert_eq!(map.nearest_cell_where((0, 4), open), Some((0, 4)));
+        // Every cell one step from the center is a wall; of the four two steps away, the
+        // smallest x offset wins
+        assert_eq!(map.nearest_cell_where((2, 2), open), Some((0, 2)));
+        // Within the same x offset, up wins over down
+        assert_eq!(map.nearest_cell_where((1, 2), open), Some((0, 2)));
+        assert_eq!(map.nearest_cell_where((2, 1), open), Some((2, 0)));
+
+        let mut corner = GridMap::new(3, 3, TileKind::Grass);
+        corner.set(0, 0, TileKind::Wall);
+        let open = |x, y| corner.is_traversable(x, y);
+        assert_eq!(corner.nearest_cell_where((0, 0), open), Some((0, 1)));
+
+        let walls = GridMap::new(3, 3, TileKind::Wall);
+        assert_eq!(walls.nearest_cell_where((1, 1), |x, y| walls.is_traversable(x, y)), None);
+    }
+
+    #[test]
+    fn content_hash_follows_terrain_not_metadata() {
+        let map = sample();
+        assert_eq!(map.content_hash(), sample().content_hash());
+
+        let mut named = sample();
+        named.metadata = Some(MapMetadata {
+            name: "Renamed".to_string(),
+            description: "Same terrain".to_string(),
+            author: Some("someone".to_string()),
+            difficulty: Some(3),
+            spawn: Some((1, 1)),
+            goal: Some((2, 0)),
+        });
+        assert_eq!(named.content_hash(), map.content_hash());
+
+        let mut edited = sample();
+        edited.set(1, 1, TileKind::Wall);
+        assert_ne!(edited.content_hash(), map.content_hash());
+        assert_ne!(
+            GridMap::new(2, 3, TileKind::Empty).content_hash(),
+            GridMap::new(3, 2, TileKind::Empty).content_hash()
+        );
+    }
+
+    #[test]
+    fn resized_keeps_decorations_in_kept_region() {
+        let mut original = sample();
+        original.set_decoration(0, 0, Some(TileKind::Tree));
+        original.set_decoration(2, 1, Some(TileKind::Sand));
+
+        let mut map = original.resized(2, 2, TileKind::Grass);
+        assert_eq!(map.decoration(0, 0), Some(&TileKind::Tree));
+        assert_eq!(map.decoration(1, 1), None);
+
+        // Removing the last decoration drops the layer
+        map.set_decoration(0, 0, None);
+        assert!(!map.has_decorations());
+    }
+
+    #[test]
+    fn resized_keeps_topology() {
+        let mut original = sample();
+        original.set_topology(Topology::Hex);
+        let map = original.resized(4, 4, TileKind::Grass);
+        assert_eq!(map.topology(), Topology::Hex);
+        assert_ne!(map.content_hash(), sample().resized(4, 4, TileKind::Grass).content_hash());
+    }
+
+    #[test]
+    fn elevation_costs_only_climbing() {
+        let mut map = sample();
+        assert_eq!(map.climb_cost((0, 0), (1, 0)), 0);
+        assert_eq!(map.elevation_range(), None);
+
+        map.set_elevation(1, 0, 3);
+        assert_eq!(map.climb_cost((0, 0), (1, 0)), 3);
+        assert_eq!(map.climb_cost((1, 0), (0, 0)), 0);
+        assert_eq!(map.elevation_range(), Some((0, 3)));
+
+        // Growing keeps the kept heights and puts new cells at 0
+        let grown = map.resized(4, 3, TileKind::Grass);
+        assert_eq!(grown.elevation(1, 0), 3);
+        assert_eq!(grown.elevation(3, 2), 0);
+    }
+
+    #[test]
+    fn tiny_maps_are_clamped_to_the_minimum() {
+        let map = GridMap::new(1, 0, TileKind::Grass);
+        assert_eq!((map.width(), map.height()), (MIN_MAP_DIMENSION, MIN_MAP_DIMENSION));
+        let shrunk = sample().resized(1, 1, TileKind::Grass);
+        assert_eq!((shrunk.width(), shrunk.height()), (2, 2));
+        assert_eq!(shrunk.get(1, 0), Some(&TileKind::Water));
+        assert!(GridMap::from_json(r#"{"width": 1, "height": 1, "tiles": [["grass"]]}"#).is_err());
+    }
+
+    #[test]
+    fn flat_tiles_round_trip_as_nested_rows() {
+        let map = sample();
+        assert_eq!(map.tiles_flat()[map.width() + 2], TileKind::Portal(1));
+        let json = serde_json::to_value(&map).unwrap();
+        assert_eq!(json["tiles"].as_array().map(Vec::len), Some(2));
+        assert_eq!(json["tiles"][1].as_array().map(Vec::len), Some(3));
+        let parsed = GridMap::from_json(&json.to_string()).unwrap();
+        assert_eq!(parsed.tiles_flat(), map.tiles_flat());
+        assert_eq!(parsed.content_hash(), map.content_hash());
+        let ragged = r#"{"width": 2, "height": 2, "tiles": [["grass", "grass"], ["grass"]]}"#;
+        assert!(GridMap::from_json(ragged).is_err());
+    }
+
+    #[test]
+    fn maze_is_perfect_with_spawn_and_goal_open() {
+        for (seed, width, height) in [(0, 21, 15), (7, 20, 10), (3, 2, 2)] {
+            let map = GridMap::maze(width, height, seed);
+            assert_eq!(map.width() % 2, 1);
+            let open: Vec<(usize, usize)> = (0..map.height())
+                .flat_map(|y| (0..map.width()).map(move |x| (x, y)))
+                .filter(|&(x, y)| map.get(x, y) == Some(&TileKind::Grass))
+                .collect();
+            // A spanning tree of the cells: one fewer passage than open cells
+            let passages = open
+                .iter()
+                .filter(|&&(x, y)| map.get(x + 1, y) == Some(&TileKind::Grass))
+                .chain(open.iter().filter(|&&(x, y)| map.get(x, y + 1) == Some(&TileKind::Grass)))
+                .count();
+            assert_eq!(passages + 1, open.len());
+            assert!(open.contains(&map.spawn_point().unwrap()));
+            assert!(open.contains(&map.goal_point().unwrap()));
+        }
+    }
+
+    #[test]
+    fn cave_keeps_a_single_open_region() {
+        for seed in 0..10 {
+            let map = GridMap::cave(30, 20, seed, 0.45, 4);
+            let regions = connected_components(&map, |x, y| map.is_traversable(x, y));
+            assert_eq!(regions.len(), 1, "seed {}", seed);
+            assert!(regions[0].contains(&map.spawn_point().unwrap()));
+            assert!(regions[0].contains(&map.goal_point().unwrap()));
+        }
+        // Solid noise still leaves a cell to stand on
+        let solid = GridMap::cave(10, 10, 0, 1.0, 2);
+        assert_eq!(solid.spawn_point(), Some((5, 5)));
+    }
+
+    #[test]
+    fn tile_costs_start_from_the_tile_table() {
+        let costs = TileCosts::default();
+        assert_eq!(costs.cost(&TileKind::Grass), 1);
+        assert_eq!(costs.cost(&TileKind::Sand), TileKind::Sand.movement_cost());
+        assert_eq!(costs.cost(&TileKind::Trail), 1);
+        assert!(!costs.is_uniform());
+        assert!(costs.non_default().any(|(tile, cost)| tile == TileKind::Sand && cost > 1));
+    }
+
+    #[test]
+    fn border_sets_each_ring_cell_once() {
+        let mut map = sample();
+        let replaced = map.add_border(TileKind::Wall);
+        // Every cell of a 3x2 map is on the ring; (0, 0) was already a wall
+        assert_eq!(replaced.len(), 5);
+        assert!(replaced.contains(&((2, 1), TileKind::Portal(1))));
+        assert!(map.tiles.iter().all(|&tile| tile == TileKind::Wall));
+
+        assert_eq!(GridMap::new(2, 4, TileKind::Grass).border_cells().len(), 8);
+        assert_eq!(GridMap::new(4, 2, TileKind::Grass).border_cells().len(), 8);
+        assert_eq!(GridMap::new(4, 3, TileKind::Grass).border_cells().len(), 10);
+    }
+}
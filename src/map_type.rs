@@ -2,3 +2,143 @@
 // Manual additions can be made below the include
 
 include!(concat!(env!("OUT_DIR"), "/map_registry.rs"));
+
+use crate::map::GridMap;
+
+/// Maximum difficulty rating shown in the map selector
+pub const MAX_DIFFICULTY: u8 = 5;
+
+/// Render a difficulty rating as filled/empty stars (e.g. "★★★☆☆")
+pub fn difficulty_stars(difficulty: u8) -> String {
+    let filled = difficulty.min(MAX_DIFFICULTY) as usize;
+    let empty = MAX_DIFFICULTY as usize - filled;
+    format!("{}{}", "★".repeat(filled), "☆".repeat(empty))
+}
+
+impl MapType {
+    /// Label for the map selector, including difficulty stars when the map has a rating
+    pub fn selector_label(&self) -> String {
+        match self.difficulty() {
+            Some(difficulty) => format!("{} {}", self.name(), difficulty_stars(difficulty)),
+            None => self.name().to_string(),
+        }
+    }
+}
+
+/// Map generated from parameters instead of loaded from a bundled JSON file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MapTemplate {
+    Maze,
+    PerfectMaze,
+    Rooms,
+    Cave,
+}
+
+impl MapTemplate {
+    pub const ALL: [MapTemplate; 4] = [Self::Maze, Self::PerfectMaze, Self::Rooms, Self::Cave];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Maze => "Maze (generated)",
+            Self::PerfectMaze => "Perfect maze (generated)",
+            Self::Rooms => "Rooms (generated)",
+            Self::Cave => "Cave (generated)",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Maze => "Walls grown on a lattice, with adjustable corridor width and complexity",
+            Self::PerfectMaze => "Recursive backtracker maze with exactly one route between cells",
+            Self::Rooms => "Rectangular rooms joined by corridors",
+            Self::Cave => "Organic caverns smoothed from random noise by cellular automata",
+        }
+    }
+
+    /// Build the map for `params`; the same parameters always give the same map
+    pub fn generate(&self, params: &TemplateParams) -> GridMap {
+        let (width, height, seed) = (params.width, params.height, params.seed);
+        match self {
+            Self::Maze => {
+                GridMap::wall_maze(width, height, seed, params.corridor_width, params.complexity)
+            }
+            Self::PerfectMaze => GridMap::maze(width, height, seed),
+            Self::Rooms => GridMap::rooms(width, height, seed, params.room_count, params.room_size),
+            Self::Cave => GridMap::cave(width, height, seed, params.fill_prob, params.iterations),
+        }
+    }
+}
+
+/// Settings for the generated templates; each template reads only the ones it uses
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TemplateParams {
+    pub width: usize,
+    pub height: usize,
+    pub seed: u64,
+    /// Maze: open cells between walls
+    pub corridor_width: usize,
+    /// Maze: 0 (nearly empty) to 1 (dense)
+    pub complexity: f32,
+    /// Rooms: how many rooms to try to place
+    pub room_count: usize,
+    /// Rooms: smallest and largest room side
+    pub room_size: (usize, usize),
+    /// Cave: chance each cell starts as wall
+    pub fill_prob: f32,
+    /// Cave: smoothing passes
+    pub iterations: usize,
+}
+
+impl Default for TemplateParams {
+    fn default() -> Self {
+        Self {
+            width: 24,
+            height: 24,
+            seed: 1,
+            corridor_width: 1,
+            complexity: 0.75,
+            room_count: 8,
+            room_size: (3, 7),
+            fill_prob: 0.45,
+            iterations: 4,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::TraversabilityRules;
+    use crate::pathfinding::check_solvable;
+
+    #[test]
+    fn bundled_maps_are_solvable() {
+        let rules = TraversabilityRules::default();
+        for map_type in MapType::all() {
+            let map = map_type.create_map(0, 0).expect("bundled map should parse");
+            if let Err(e) = check_solvable(&map, |x, y| rules.is_cell_passable(&map, x, y)) {
+                panic!("{}: {}", map_type.name(), e);
+            }
+        }
+    }
+
+    #[test]
+    fn generated_templates_are_solvable_and_reproducible() {
+        let rules = TraversabilityRules::default();
+        for template in MapTemplate::ALL {
+            for seed in 0..20 {
+                let params = TemplateParams {
+                    seed,
+                    corridor_width: 1 + seed as usize % 3,
+                    ..TemplateParams::default()
+                };
+                let map = template.generate(&params);
+                assert!(map.spawn_point().is_some() && map.goal_point().is_some());
+                if let Err(e) = check_solvable(&map, |x, y| rules.is_cell_passable(&map, x, y)) {
+                    panic!("{} seed {}: {}", template.label(), seed, e);
+                }
+                assert_eq!(map.content_hash(), template.generate(&params).content_hash());
+            }
+        }
+    }
+}
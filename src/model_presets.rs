@@ -0,0 +1,47 @@
+/// Default agent configuration for a specific model
+///
+/// Applied when the user switches to the model; every value can still be changed afterwards.
+#[derive(Clone, Copy, Debug)]
+pub struct ModelPreset {
+    /// OpenRouter model id
+    pub id: &'static str,
+    /// Human-readable name for the model selector
+    pub label: &'static str,
+    pub max_history_messages: usize,
+    /// Sampling temperature, or None to use the provider default
+    pub temperature: Option<f32>,
+}
+
+pub const MODEL_PRESETS: &[ModelPreset] = &[
+    ModelPreset {
+        id: "anthropic/claude-haiku-4.5",
+        label: "Claude Haiku 4.5",
+        max_history_messages: 30,
+        temperature: None,
+    },
+    ModelPreset {
+        id: "anthropic/claude-sonnet-4.5",
+        label: "Claude Sonnet 4.5",
+        max_history_messages: 50,
+        temperature: None,
+    },
+    ModelPreset {
+        id: "x-ai/grok-4-fast",
+        label: "Grok 4 Fast",
+        max_history_messages: 50,
+        temperature: Some(0.3),
+    },
+    ModelPreset {
+        id: "x-ai/grok-code-fast-1",
+        label: "Grok Code Fast",
+        max_history_messages: 20,
+        temperature: Some(0.2),
+    },
+];
+
+impl ModelPreset {
+    /// Look up the preset for a model id
+    pub fn for_model(id: &str) -> Option<&'static ModelPreset> {
+        MODEL_PRESETS.iter().find(|preset| preset.id == id)
+    }
+}
@@ -6,6 +6,10 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use serde_json::json;
 use std::pin::Pin;
+use web_time::Duration;
+
+/// Wait before the first retry of a failed request; doubled for each retry after that
+const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(500);
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ToolCall {
@@ -47,53 +51,152 @@ pub struct Function {
     pub parameters: serde_json::Value,
 }
 
-#[derive(Debug)]
+/// A model offered by OpenRouter (subset of the `/models` response)
+#[derive(Deserialize, Clone, Debug)]
+pub struct ModelInfo {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+}
+
+/// Fetch the list of models available on OpenRouter
+pub async fn fetch_models(
+    api_key: String,
+) -> Result<Vec<ModelInfo>, Box<dyn std::error::Error + Send + Sync>> {
+    #[derive(Deserialize)]
+    struct ModelsResponse {
+        data: Vec<ModelInfo>,
+    }
+
+    let response = Client::new()
+        .get("https://openrouter.ai/api/v1/models")
+        .header("Authorization", format!("Bearer {}", api_key))
+        .header("X-Title", "pk-chat-agent")
+        .send()
+        .await?
+        .error_for_status()?;
+
+    let mut models = response.json::<ModelsResponse>().await?.data;
+    models.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(models)
+}
+
+#[derive(Debug, PartialEq)]
 pub enum OpenRouterEvent {
     Content(String),
     ToolCallDelta {
         name: Option<String>,
         arguments_delta: Option<String>,
     },
+    /// Tokens used by the request, sent once at the end; `cost` is in credits (USD)
+    Usage {
+        prompt_tokens: u64,
+        completion_tokens: u64,
+        cost: Option<f64>,
+    },
 }
 
-pub fn open_router_event_stream(
-    api_key: String,
-    model: String,
-    messages: Vec<Message>,
-    system_prompt: Option<String>,
-    tools: Option<Vec<Tool>>,
-) -> Pin<Box<dyn Stream<Item = Result<OpenRouterEvent, Box<dyn std::error::Error + Send + Sync>>>>>
-{
-    let full_messages = if let Some(system) = system_prompt {
-        let mut msgs = vec![Message {
-            role: "system".to_string(),
-            content: Some(system),
-            tool_calls: None,
-            tool_call_id: None,
-            name: None,
-        }];
-        msgs.extend(messages);
-        msgs
-    } else {
-        messages
-    };
+/// The `usage` object of a response or final stream chunk, if it has one
+fn usage_event(json: &Value) -> Option<OpenRouterEvent> {
+    let usage = json.get("usage")?;
+    Some(OpenRouterEvent::Usage {
+        prompt_tokens: usage["prompt_tokens"].as_u64()?,
+        completion_tokens: usage["completion_tokens"].as_u64().unwrap_or(0),
+        cost: usage["cost"].as_f64(),
+    })
+}
 
-    let json_body = json!({
+/// Build the JSON body for a chat completion request
+///
+/// Pure so the exact payload can be previewed without sending it. `stream` picks
+/// between `open_router_event_stream` (true) and `open_router_completion` (false).
+pub fn build_chat_request(
+    model: &str,
+    messages: &[Message],
+    tools: Option<&[Tool]>,
+    temperature: Option<f32>,
+    stream: bool,
+) -> Value {
+    let mut json_body = json!({
         "model": model,
-        "messages": full_messages,
-        "stream": true,
-        "tools": tools
+        "messages": messages,
+        "stream": stream,
+        "tools": tools,
+        // Ask OpenRouter to report the cost alongside the token counts
+        "usage": {"include": true}
     });
+    if let Some(temperature) = temperature {
+        json_body["temperature"] = json!(temperature);
+    }
+    json_body
+}
 
-    Box::pin(stream! {
-        let client = Client::new();
-        let response = client
+/// POST a chat request, retrying transient failures up to `retries` times
+///
+/// Network errors, 429 and 5xx responses are retried after 500ms, 1s, 2s and so on. Any
+/// other error status (such as 401 for a bad key) fails right away.
+async fn send_chat_request(
+    api_key: &str,
+    json_body: &Value,
+    retries: u32,
+) -> Result<reqwest::Response, reqwest::Error> {
+    let mut delay = INITIAL_RETRY_DELAY;
+    let mut retries_left = retries;
+    loop {
+        let result = Client::new()
             .post("https://openrouter.ai/api/v1/chat/completions")
             .header("Authorization", format!("Bearer {}", api_key))
             .header("Content-Type", "application/json")
             .header("X-Title", "pk-chat-agent")
-            .json(&json_body)
+            .json(json_body)
             .send()
+            .await;
+        let transient = match &result {
+            Ok(response) => {
+                let status = response.status();
+                status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+            }
+            Err(e) => !e.is_builder(),
+        };
+        if !transient || retries_left == 0 {
+            return result?.error_for_status();
+        }
+        retries_left -= 1;
+        sleep(delay).await;
+        delay *= 2;
+    }
+}
+
+/// Resolve after `duration`, using the browser's timer
+async fn sleep(duration: Duration) {
+    let promise = web_sys::js_sys::Promise::new(&mut |resolve, _| {
+        let scheduled = web_sys::window().is_some_and(|window| {
+            window
+                .set_timeout_with_callback_and_timeout_and_arguments_0(
+                    &resolve,
+                    duration.as_millis() as i32,
+                )
+                .is_ok()
+        });
+        // No timer to wait on, so don't wait at all rather than forever
+        if !scheduled {
+            let _ = resolve.call0(&wasm_bindgen::JsValue::NULL);
+        }
+    });
+    let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+}
+
+/// Send a prebuilt chat request (see `build_chat_request`) and stream the response events
+///
+/// Transient failures to connect are retried up to `retries` times (see `send_chat_request`).
+pub fn open_router_event_stream(
+    api_key: String,
+    json_body: Value,
+    retries: u32,
+) -> Pin<Box<dyn Stream<Item = Result<OpenRouterEvent, Box<dyn std::error::Error + Send + Sync>>>>>
+{
+    Box::pin(stream! {
+        let response = send_chat_request(&api_key, &json_body, retries)
             .await
             .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
 
@@ -106,6 +209,9 @@ pub fn open_router_event_stream(
                 let data = &line[6..];
                 if data == "[DONE]" { break; }
                 if let Ok(json) = serde_json::from_str::<Value>(data) {
+                    if let Some(usage) = usage_event(&json) {
+                        yield Ok(usage);
+                    }
                     if let Some(choice) = json["choices"].as_array().and_then(|c| c.first()) {
                         if let Some(delta) = choice["delta"].as_object() {
                             if let Some(content) = delta.get("content").and_then(|c| c.as_str()) {
@@ -129,3 +235,148 @@ pub fn open_router_event_stream(
         }
     })
 }
+
+/// Send a prebuilt chat request without streaming and wait for the whole response
+///
+/// The reply's content and tool calls come back as the same events the stream would
+/// have produced, so callers can handle both paths alike. Provider errors reported in
+/// the response body are returned as errors rather than dropped. Transient failures are
+/// retried up to `retries` times, as for the stream.
+pub async fn open_router_completion(
+    api_key: String,
+    mut json_body: Value,
+    retries: u32,
+) -> Result<Vec<OpenRouterEvent>, Box<dyn std::error::Error + Send + Sync>> {
+    json_body["stream"] = json!(false);
+    let response = send_chat_request(&api_key, &json_body, retries).await?;
+    let json = response.json::<Value>().await?;
+    Ok(completion_events(&json)?)
+}
+
+/// Events equivalent to the streamed deltas for a non-streaming completion response
+fn completion_events(json: &Value) -> Result<Vec<OpenRouterEvent>, String> {
+    if let Some(error) = json.get("error") {
+        let message = error["message"].as_str().map_or_else(|| error.to_string(), String::from);
+        return Err(format!("Completion error: {}", message));
+    }
+    let message = json["choices"]
+        .as_array()
+        .and_then(|choices| choices.first())
+        .and_then(|choice| choice.get("message"))
+        .ok_or("Completion response has no choices[0].message")?;
+
+    let mut events = Vec::new();
+    if let Some(content) = message["content"].as_str()
+        && !content.is_empty()
+    {
+        events.push(OpenRouterEvent::Content(content.to_string()));
+    }
+    let tool_calls = message["tool_calls"].as_array().into_iter().flatten();
+    for function in tool_calls.map(|tc| &tc["function"]) {
+        let name = function["name"].as_str().map(String::from);
+        let arguments_delta = function["arguments"].as_str().map(String::from);
+        if name.is_some() || arguments_delta.is_some() {
+            events.push(OpenRouterEvent::ToolCallDelta { name, arguments_delta });
+        }
+    }
+    events.extend(usage_event(json));
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(text: &str) -> Value {
+        serde_json::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn completion_events_extract_content_tool_calls_and_usage() {
+        let response = fixture(
+            r#"{
+                "choices": [{
+                    "message": {
+                        "role": "assistant",
+                        "content": "Heading east.",
+                        "tool_calls": [
+                            {"id": "call_1", "type": "function", "function": {
+                                "name": "move", "arguments": "{\"direction\":\"right\"}"
+                            }},
+                            {"id": "call_2", "type": "function",
+                             "function": {"name": "look", "arguments": "{}"}},
+                            {"id": "call_3", "type": "function", "function": {}}
+                        ]
+                    }
+                }],
+                "usage": {"prompt_tokens": 120, "completion_tokens": 30, "cost": 0.0015}
+            }"#,
+        );
+        let tool = |name: &str, arguments: &str| OpenRouterEvent::ToolCallDelta {
+            name: Some(name.to_string()),
+            arguments_delta: Some(arguments.to_string()),
+        };
+        assert_eq!(
+            completion_events(&response).unwrap(),
+            vec![
+                OpenRouterEvent::Content("Heading east.".to_string()),
+                tool("move", r#"{"direction":"right"}"#),
+                tool("look", "{}"),
+                OpenRouterEvent::Usage {
+                    prompt_tokens: 120,
+                    completion_tokens: 30,
+                    cost: Some(0.0015),
+                },
+            ]
+        );
+
+        let empty = fixture(r#"{"choices": [{"message": {"content": "", "tool_calls": null}}]}"#);
+        assert_eq!(completion_events(&empty).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn completion_events_surface_provider_errors() {
+        let error = fixture(r#"{"error": {"code": 429, "message": "Rate limited"}}"#);
+        assert_eq!(completion_events(&error).unwrap_err(), "Completion error: Rate limited");
+        let bare = fixture(r#"{"error": "overloaded"}"#);
+        assert_eq!(completion_events(&bare).unwrap_err(), "Completion error: \"overloaded\"");
+        assert!(completion_events(&fixture(r#"{"choices": []}"#)).is_err());
+    }
+
+    #[test]
+    fn usage_event_tolerates_missing_fields() {
+        assert_eq!(usage_event(&fixture(r#"{"choices": []}"#)), None);
+        // Without a prompt count there's nothing worth reporting
+        assert_eq!(usage_event(&fixture(r#"{"usage": {"completion_tokens": 5}}"#)), None);
+        assert_eq!(
+            usage_event(&fixture(r#"{"usage": {"prompt_tokens": 40}}"#)),
+            Some(OpenRouterEvent::Usage { prompt_tokens: 40, completion_tokens: 0, cost: None })
+        );
+    }
+
+    #[test]
+    fn chat_request_body_without_streaming() {
+        let messages = [Message {
+            role: "user".to_string(),
+            content: Some("hi".to_string()),
+            tool_calls: None,
+            tool_call_id: None,
+            name: None,
+        }];
+        let body = build_chat_request("some/model", &messages, None, Some(0.5), false);
+        assert_eq!(
+            body,
+            json!({
+                "model": "some/model",
+                "messages": [{"role": "user", "content": "hi"}],
+                "stream": false,
+                "tools": null,
+                "usage": {"include": true},
+                "temperature": 0.5
+            })
+        );
+        let body = build_chat_request("some/model", &messages, None, None, true);
+        assert_eq!(body["stream"], json!(true));
+        assert!(body.get("temperature").is_none());
+    }
+}
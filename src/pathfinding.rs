@@ -0,0 +1,681 @@
+use crate::agent::Direction;
+use crate::map::{GridMap, Topology};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet, VecDeque};
+
+/// Cells one of `moves` takes `from` to that are on the map and `passable`, in the order
+/// of `moves` so results are deterministic
+///
+/// `moves` is usually `Agent::directions` or `Direction::for_topology`. A square-grid
+/// diagonal also needs both cells beside it passable, so routes never cut a corner
+/// (as `Agent::execute_move_step` refuses to).
+fn neighbors<'a>(
+    map: &'a GridMap,
+    from: (usize, usize),
+    moves: &'a [Direction],
+    passable: &'a impl Fn(usize, usize) -> bool,
+) -> impl Iterator<Item = (usize, usize)> + 'a {
+    let topology = map.topology();
+    let step = move |dir: &Direction| {
+        let (dx, dy) = dir.offset(topology, from.1 as i64);
+        let (nx, ny) = (from.0 as i64 + dx, from.1 as i64 + dy);
+        (nx >= 0 && ny >= 0 && map.in_bounds(nx as usize, ny as usize))
+            .then_some((nx as usize, ny as usize))
+            .filter(|&(x, y)| passable(x, y))
+    };
+    moves.iter().filter_map(move |dir| {
+        let cell = step(dir)?;
+        let sides_open = dir
+            .corner_steps(topology)
+            .is_none_or(|sides| sides.iter().all(|side| step(side).is_some()));
+        sides_open.then_some(cell)
+    })
+}
+
+/// Fewest moves between two cells on an open map: Manhattan distance on square grids,
+/// hex distance on hex grids
+pub fn grid_distance(topology: Topology, a: (usize, usize), b: (usize, usize)) -> u32 {
+    match topology {
+        Topology::Square => (a.0.abs_diff(b.0) + a.1.abs_diff(b.1)) as u32,
+        Topology::Hex => {
+            // Odd-r offset to axial coordinates, where hex distance has a closed form
+            let axial = |(x, y): (usize, usize)| {
+                let (x, y) = (x as i64, y as i64);
+                (x - (y - (y & 1)) / 2, y)
+            };
+            let ((q1, r1), (q2, r2)) = (axial(a), axial(b));
+            let (dq, dr) = (q1 - q2, r1 - r2);
+            ((dq.abs() + dr.abs() + (dq + dr).abs()) / 2) as u32
+        }
+    }
+}
+
+/// Fewest of `moves` between two cells on an open map: `grid_distance`, or the
+/// Chebyshev distance when square-grid diagonals are among them
+pub fn move_distance(
+    topology: Topology,
+    moves: &[Direction],
+    a: (usize, usize),
+    b: (usize, usize),
+) -> u32 {
+    if moves.iter().any(|dir| dir.corner_steps(topology).is_some()) {
+        return a.0.abs_diff(b.0).max(a.1.abs_diff(b.1)) as u32;
+    }
+    grid_distance(topology, a, b)
+}
+
+/// Find a shortest path from `start` to `goal` using breadth-first search
+///
+/// `passable(x, y)` decides whether the agent may stand on a cell (e.g. `Agent::can_enter`,
+/// so multi-cell footprints are respected) and `moves` which steps it may take (see
+/// `neighbors`). Stepping onto a paired portal lands on its exit for free, mirroring how
+/// the agent actually moves.
+///
+/// Returns the positions from `start` to `goal` inclusive, so the number of moves is
+/// `path.len() - 1`. Returns None if the goal can't be reached.
+pub fn shortest_path(
+    map: &GridMap,
+    start: (usize, usize),
+    goal: (usize, usize),
+    moves: &[Direction],
+    passable: impl Fn(usize, usize) -> bool,
+) -> Option<Vec<(usize, usize)>> {
+    bfs(map, start, goal, moves, passable, &mut Vec::new(), &mut Vec::new())
+}
+
+/// Breadth-first search, recording each cell as it is taken off the queue in `expanded`
+/// and every equally short second way into a cell in `ties`
+fn bfs(
+    map: &GridMap,
+    start: (usize, usize),
+    goal: (usize, usize),
+    moves: &[Direction],
+    passable: impl Fn(usize, usize) -> bool,
+    expanded: &mut Vec<(usize, usize)>,
+    ties: &mut Vec<TieBreak>,
+) -> Option<Vec<(usize, usize)>> {
+    if !map.in_bounds(start.0, start.1) {
+        return None;
+    }
+    if start == goal {
+        expanded.push(start);
+        return Some(vec![start]);
+    }
+
+    let width = map.width();
+    let index = |(x, y): (usize, usize)| y * width + x;
+    let mut previous: Vec<Option<(usize, usize)>> = vec![None; width * map.height()];
+    let mut visited = vec![false; width * map.height()];
+    let mut depth = vec![0u32; width * map.height()];
+    let mut queue = VecDeque::new();
+
+    visited[index(start)] = true;
+    queue.push_back(start);
+
+    while let Some(current) = queue.pop_front() {
+        expanded.push(current);
+        for (nx, ny) in neighbors(map, current, moves, &passable) {
+            // A portal only teleports if its exit is usable; otherwise the agent stays on it
+            let landing = match map.portal_exit(nx, ny) {
+                Some((ex, ey)) if passable(ex, ey) => (ex, ey),
+                _ => (nx, ny),
+            };
+            if visited[index(landing)] {
+                if depth[index(landing)] == depth[index(current)] + 1
+                    && let Some(kept) = previous[index(landing)]
+                    && kept != current
+                {
+                    ties.push(TieBreak {
+                        cell: landing,
+                        kept,
+                        rejected: current,
+                    });
+                }
+                continue;
+            }
+            visited[index(landing)] = true;
+            depth[index(landing)] = depth[index(current)] + 1;
+            previous[index(landing)] = Some(current);
+
+            if landing == goal {
+                expanded.push(goal);
+                return Some(walk_back(&previous, width, goal));
+            }
+            queue.push_back(landing);
+        }
+    }
+
+    None
+}
+
+/// Split the cells where `passable(x, y)` holds into groups reachable from one another
+///
+/// Only ordinary moves join cells; portals are ignored. Each group lists its cells in
+/// the order they were found, and groups are sorted largest first.
+pub fn connected_components(
+    map: &GridMap,
+    passable: impl Fn(usize, usize) -> bool,
+) -> Vec<Vec<(usize, usize)>> {
+    let moves = Direction::for_topology(map.topology());
+    let (width, height) = (map.width(), map.height());
+    let mut seen = vec![false; width * height];
+    let mut components = Vec::new();
+    for start in (0..height).flat_map(|y| (0..width).map(move |x| (x, y))) {
+        if seen[start.1 * width + start.0] || !passable(start.0, start.1) {
+            continue;
+        }
+        seen[start.1 * width + start.0] = true;
+        let mut component = vec![start];
+        let mut queue = VecDeque::from([start]);
+        while let Some(cell) = queue.pop_front() {
+            for (nx, ny) in neighbors(map, cell, moves, &passable) {
+                if !seen[ny * width + nx] {
+                    seen[ny * width + nx] = true;
+                    component.push((nx, ny));
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+        components.push(component);
+    }
+    components.sort_by_key(|component| Reverse(component.len()));
+    components
+}
+
+/// Every cell reachable from `start` through cells where `passable(x, y)` holds
+///
+/// Moves follow `shortest_path`, portals included: a portal cell counts as reached when
+/// stepped on, along with its exit. `start` itself is always included if on the map.
+pub fn reachable_cells(
+    map: &GridMap,
+    start: (usize, usize),
+    passable: impl Fn(usize, usize) -> bool,
+) -> HashSet<(usize, usize)> {
+    let mut reached = HashSet::new();
+    if !map.in_bounds(start.0, start.1) {
+        return reached;
+    }
+    reached.insert(start);
+    let moves = Direction::for_topology(map.topology());
+    let mut queue = VecDeque::from([start]);
+    while let Some(cell) = queue.pop_front() {
+        for (nx, ny) in neighbors(map, cell, moves, &passable) {
+            let landing = match map.portal_exit(nx, ny) {
+                Some((ex, ey)) if passable(ex, ey) => (ex, ey),
+                _ => (nx, ny),
+            };
+            // The agent passes over a working portal without stopping, so only its exit
+            // is searched from
+            if landing != (nx, ny) {
+                reached.insert((nx, ny));
+            }
+            if reached.insert(landing) {
+                queue.push_back(landing);
+            }
+        }
+    }
+    reached
+}
+
+/// Check that a map's designated goal can be reached from its designated spawn
+///
+/// Maps without both a spawn and a goal pass trivially. Errors describe what is wrong so
+/// they can be logged as-is.
+pub fn check_solvable(map: &GridMap, passable: impl Fn(usize, usize) -> bool) -> Result<(), String> {
+    let (Some(spawn), Some(goal)) = (map.spawn_point(), map.goal_point()) else {
+        return Ok(());
+    };
+    if !passable(spawn.0, spawn.1) {
+        return Err(format!("spawn ({}, {}) is on a blocked cell", spawn.0, spawn.1));
+    }
+    if !passable(goal.0, goal.1) {
+        return Err(format!("goal ({}, {}) is on a blocked cell", goal.0, goal.1));
+    }
+    match shortest_path(map, spawn, goal, Direction::for_topology(map.topology()), passable) {
+        Some(_) => Ok(()),
+        None => Err(format!(
+            "goal ({}, {}) is unreachable from spawn ({}, {})",
+            goal.0, goal.1, spawn.0, spawn.1
+        )),
+    }
+}
+
+/// Find the cheapest path from `start` to `goal` using Dijkstra's algorithm
+///
+/// `cost(x, y)` is the price of entering a cell (e.g. `Agent::movement_cost`), plus the
+/// map's `climb_cost` for steps uphill; `moves` and portal landings work like in
+/// `shortest_path`. Returns the path (start and goal inclusive) and its total cost, or
+/// None if the goal can't be reached.
+pub fn cheapest_path(
+    map: &GridMap,
+    start: (usize, usize),
+    goal: (usize, usize),
+    moves: &[Direction],
+    passable: impl Fn(usize, usize) -> bool,
+    cost: impl Fn(usize, usize) -> u32,
+) -> Option<(Vec<(usize, usize)>, u32)> {
+    best_first(
+        map,
+        start,
+        goal,
+        moves,
+        passable,
+        cost,
+        |_, _| 0,
+        &mut Vec::new(),
+        &mut Vec::new(),
+    )
+}
+
+/// Dijkstra / A* search, recording each cell as it is expanded in `expanded` and every
+/// equally cheap second way into a cell in `ties`
+///
+/// `heuristic(x, y)` must never overestimate the remaining cost; returning 0 gives Dijkstra.
+#[allow(clippy::too_many_arguments)]
+fn best_first(
+    map: &GridMap,
+    start: (usize, usize),
+    goal: (usize, usize),
+    moves: &[Direction],
+    passable: impl Fn(usize, usize) -> bool,
+    cost: impl Fn(usize, usize) -> u32,
+    heuristic: impl Fn(usize, usize) -> u32,
+    expanded: &mut Vec<(usize, usize)>,
+    ties: &mut Vec<TieBreak>,
+) -> Option<(Vec<(usize, usize)>, u32)> {
+    if !map.in_bounds(start.0, start.1) {
+        return None;
+    }
+
+    let width = map.width();
+    let index = |(x, y): (usize, usize)| y * width + x;
+    let mut previous: Vec<Option<(usize, usize)>> = vec![None; width * map.height()];
+    let mut best = vec![u32::MAX; width * map.height()];
+    let mut heap = BinaryHeap::new();
+
+    best[index(start)] = 0;
+    heap.push(Reverse((heuristic(start.0, start.1), start.1, start.0)));
+
+    while let Some(Reverse((estimate, y, x))) = heap.pop() {
+        let current = (x, y);
+        let spent = best[index(current)];
+        if estimate > spent.saturating_add(heuristic(x, y)) {
+            continue; // Stale entry, a cheaper route was found since
+        }
+        expanded.push(current);
+        if current == goal {
+            return Some((walk_back(&previous, width, goal), spent));
+        }
+
+        for (nx, ny) in neighbors(map, current, moves, &passable) {
+            let landing = match map.portal_exit(nx, ny) {
+                Some((ex, ey)) if passable(ex, ey) => (ex, ey),
+                _ => (nx, ny),
+            };
+            let total = spent
+                .saturating_add(cost(nx, ny))
+                .saturating_add(map.climb_cost(current, (nx, ny)));
+            if total < best[index(landing)] {
+                best[index(landing)] = total;
+                previous[index(landing)] = Some(current);
+                let estimate = total.saturating_add(heuristic(landing.0, landing.1));
+                heap.push(Reverse((estimate, landing.1, landing.0)));
+            } else if total == best[index(landing)]
+                && let Some(kept) = previous[index(landing)]
+                && kept != current
+            {
+                ties.push(TieBreak {
+                    cell: landing,
+                    kept,
+                    rejected: current,
+                });
+            }
+        }
+    }
+
+    None
+}
+
+/// Rebuild the path ending at `goal` from the predecessor table
+fn walk_back(
+    previous: &[Option<(usize, usize)>],
+    width: usize,
+    goal: (usize, usize),
+) -> Vec<(usize, usize)> {
+    let mut path = vec![goal];
+    let mut cursor = goal;
+    while let Some(prev) = previous[cursor.1 * width + cursor.0] {
+        path.push(prev);
+        cursor = prev;
+    }
+    path.reverse();
+    path
+}
+
+/// Search strategies that can be traced for visualization
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SearchAlgorithm {
+    Bfs,
+    Dijkstra,
+    AStar,
+}
+
+impl SearchAlgorithm {
+    pub const ALL: [SearchAlgorithm; 3] = [Self::Bfs, Self::Dijkstra, Self::AStar];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Bfs => "BFS",
+            Self::Dijkstra => "Dijkstra",
+            Self::AStar => "A*",
+        }
+    }
+}
+
+/// A cell two neighbors reached equally well; the search kept the one expanded first
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TieBreak {
+    pub cell: (usize, usize),
+    pub kept: (usize, usize),
+    pub rejected: (usize, usize),
+}
+
+/// Recorded run of a search: the order cells were expanded in, the tie-breaks it made
+/// and the path found
+#[derive(Clone, Debug)]
+pub struct SearchTrace {
+    pub algorithm: SearchAlgorithm,
+    pub topology: Topology,
+    pub expanded: Vec<(usize, usize)>,
+    pub ties: Vec<TieBreak>,
+    pub path: Option<Vec<(usize, usize)>>,
+}
+
+/// How a single move from `from` lands on `to`
+fn heading(topology: Topology, from: (usize, usize), to: (usize, usize)) -> String {
+    let delta = (to.0 as i64 - from.0 as i64, to.1 as i64 - from.1 as i64);
+    Direction::available(topology, true)
+        .iter()
+        .find(|dir| dir.offset(topology, from.1 as i64) == delta)
+        .map_or_else(
+            || "through a portal".to_string(),
+            |dir| format!("heading {}", dir.compass()),
+        )
+}
+
+impl SearchTrace {
+    /// The tie-breaks that shaped the found path, one sentence each
+    pub fn path_tie_breaks(&self) -> Vec<String> {
+        let Some(path) = &self.path else {
+            return Vec::new();
+        };
+        path.windows(2)
+            .filter_map(|pair| {
+                let tie = self
+                    .ties
+                    .iter()
+                    .find(|tie| tie.cell == pair[1] && tie.kept == pair[0])?;
+                Some(format!(
+                    "At ({}, {}) preferred coming from ({}, {}) {} over ({}, {}) {}",
+                    tie.cell.0,
+                    tie.cell.1,
+                    tie.kept.0,
+                    tie.kept.1,
+                    heading(self.topology, tie.kept, tie.cell),
+                    tie.rejected.0,
+                    tie.rejected.1,
+                    heading(self.topology, tie.rejected, tie.cell)
+                ))
+            })
+            .collect()
+    }
+
+    /// Why the search picked this path over equally good ones, for showing to users
+    pub fn explanation(&self) -> String {
+        if self.path.is_none() {
+            return format!("{} found no path", self.algorithm.name());
+        }
+        let ties = self.path_tie_breaks();
+        if ties.is_empty() {
+            return format!(
+                "{} made no tie-breaks along this path; it was the only best route it found",
+                self.algorithm.name()
+            );
+        }
+        let rule = match self.algorithm {
+            SearchAlgorithm::Bfs => {
+                "BFS keeps the first way it finds into a cell, and it looks at neighbors in \
+                 a fixed order (up, down, left, right on square grids)"
+            }
+            SearchAlgorithm::Dijkstra | SearchAlgorithm::AStar => {
+                "equally good cells are expanded lowest y first, then lowest x, and the first \
+                 way into a cell is kept"
+            }
+        };
+        format!(
+            "{}. Both options were equally good; {}",
+            ties.join(". "),
+            rule
+        )
+    }
+}
+
+/// Run `algorithm` from `start` to `goal`, keeping the expansion order
+///
+/// BFS ignores `cost`. The A* heuristic is the `move_distance` to the goal or to the
+/// nearest paired portal, whichever is closer, so it stays admissible when portals
+/// shortcut the map (every move costs at least 1).
+pub fn trace_search(
+    map: &GridMap,
+    start: (usize, usize),
+    goal: (usize, usize),
+    algorithm: SearchAlgorithm,
+    moves: &[Direction],
+    passable: impl Fn(usize, usize) -> bool,
+    cost: impl Fn(usize, usize) -> u32,
+) -> SearchTrace {
+    let mut expanded = Vec::new();
+    let mut ties = Vec::new();
+    let path = match algorithm {
+        SearchAlgorithm::Bfs => bfs(map, start, goal, moves, passable, &mut expanded, &mut ties),
+        SearchAlgorithm::Dijkstra => best_first(
+            map,
+            start,
+            goal,
+            moves,
+            passable,
+            cost,
+            |_, _| 0,
+            &mut expanded,
+            &mut ties,
+        )
+        .map(|(path, _)| path),
+        SearchAlgorithm::AStar => {
+            let portals: Vec<(usize, usize)> = map
+                .portals()
+                .filter(|&(_, x, y)| map.portal_exit(x, y).is_some())
+                .map(|(_, x, y)| (x, y))
+                .collect();
+            let topology = map.topology();
+            let heuristic = |x: usize, y: usize| {
+                let to = |target: (usize, usize)| move_distance(topology, moves, (x, y), target);
+                portals.iter().map(|&p| to(p)).fold(to(goal), u32::min)
+            };
+            best_first(
+                map,
+                start,
+                goal,
+                moves,
+                passable,
+                cost,
+                heuristic,
+                &mut expanded,
+                &mut ties,
+            )
+            .map(|(path, _)| path)
+        }
+    };
+    SearchTrace {
+        algorithm,
+        topology: map.topology(),
+        expanded,
+        ties,
+        path,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::{MapMetadata, TileKind};
+
+    fn open_square(width: usize, height: usize) -> GridMap {
+        GridMap::new(width, height, TileKind::Empty)
+    }
+
+    /// `trace_search` from the top-left corner with the map's own moves and unit costs
+    fn trace_from_corner(
+        map: &GridMap,
+        goal: (usize, usize),
+        algorithm: SearchAlgorithm,
+        passable: impl Fn(usize, usize) -> bool,
+    ) -> SearchTrace {
+        let moves = Direction::for_topology(map.topology());
+        trace_search(map, (0, 0), goal, algorithm, moves, passable, |_, _| 1)
+    }
+
+    #[test]
+    fn traced_dijkstra_records_expansions_and_the_tie_it_broke() {
+        let map = open_square(2, 2);
+        let trace = trace_from_corner(&map, (1, 1), SearchAlgorithm::Dijkstra, |_, _| true);
+
+        assert_eq!(trace.expanded, [(0, 0), (1, 0), (0, 1), (1, 1)]);
+        assert_eq!(trace.path, Some(vec![(0, 0), (1, 0), (1, 1)]));
+        assert_eq!(
+            trace.ties,
+            [TieBreak {
+                cell: (1, 1),
+                kept: (1, 0),
+                rejected: (0, 1),
+            }]
+        );
+        assert_eq!(
+            trace.explanation(),
+            "At (1, 1) preferred coming from (1, 0) heading south over (0, 1) heading east. \
+             Both options were equally good; equally good cells are expanded lowest y first, \
+             then lowest x, and the first way into a cell is kept"
+        );
+    }
+
+    #[test]
+    fn traced_bfs_stops_at_the_goal() {
+        let map = open_square(2, 2);
+        let trace = trace_from_corner(&map, (1, 1), SearchAlgorithm::Bfs, |_, _| true);
+
+        // Down is searched before right, and the goal ends the search as soon as it is seen
+        assert_eq!(trace.expanded, [(0, 0), (0, 1), (1, 1)]);
+        assert_eq!(trace.path, Some(vec![(0, 0), (0, 1), (1, 1)]));
+        assert_eq!(
+            trace.explanation(),
+            "BFS made no tie-breaks along this path; it was the only best route it found"
+        );
+
+        let walled = trace_from_corner(&map, (1, 1), SearchAlgorithm::Bfs, |x, y| (x, y) == (0, 0));
+        assert_eq!(walled.path, None);
+        assert_eq!(walled.explanation(), "BFS found no path");
+    }
+
+    #[test]
+    fn cheapest_path_detours_around_expensive_cells() {
+        let mut map = open_square(3, 2);
+        map.set(1, 0, TileKind::Sand);
+        let moves = Direction::for_topology(Topology::Square);
+        let cost = |x, y| if map.get(x, y) == Some(&TileKind::Sand) { 5 } else { 1 };
+
+        let shortest = shortest_path(&map, (0, 0), (2, 0), moves, |_, _| true);
+        assert_eq!(shortest, Some(vec![(0, 0), (1, 0), (2, 0)]));
+        let (path, total) = cheapest_path(&map, (0, 0), (2, 0), moves, |_, _| true, cost).unwrap();
+        assert_eq!(path, [(0, 0), (0, 1), (1, 1), (2, 1), (2, 0)]);
+        assert_eq!(total, 4);
+    }
+
+    #[test]
+    fn hex_distance_matches_known_cases_on_odd_and_even_rows() {
+        let hex = |a, b| grid_distance(Topology::Hex, a, b);
+        // Even row 0: (0, 1) is down-right, (1, 1) is one further
+        assert_eq!(hex((0, 0), (0, 1)), 1);
+        assert_eq!(hex((0, 0), (1, 1)), 2);
+        assert_eq!(hex((0, 0), (2, 2)), 3);
+        // Odd row 1: (1, 2) is down-left and (2, 2) down-right
+        assert_eq!(hex((1, 1), (1, 2)), 1);
+        assert_eq!(hex((1, 1), (2, 2)), 1);
+        assert_eq!(hex((1, 1), (0, 2)), 2);
+        assert_eq!(hex((1, 1), (1, 3)), 2);
+        assert_eq!(hex((3, 1), (0, 1)), 3);
+    }
+
+    #[test]
+    fn hex_distance_agrees_with_searching_the_hex_neighbors() {
+        let mut map = open_square(5, 5);
+        map.set_topology(Topology::Hex);
+        let moves = Direction::for_topology(Topology::Hex);
+        let cells: Vec<(usize, usize)> = (0..5).flat_map(|y| (0..5).map(move |x| (x, y))).collect();
+        for &from in &cells {
+            for &to in &cells {
+                let path = shortest_path(&map, from, to, moves, |_, _| true).unwrap();
+                assert_eq!(grid_distance(Topology::Hex, from, to), path.len() as u32 - 1);
+            }
+        }
+    }
+
+    /// 5x3 map walled down column 2, with a 3-cell pocket top right and a lone cell at
+    /// (4, 2), spawn at (0, 0) and goal at (4, 0)
+    fn pocket_map() -> GridMap {
+        let mut map = open_square(5, 3);
+        for (x, y) in [(2, 0), (2, 1), (2, 2), (4, 1), (3, 2)] {
+            map.set(x, y, TileKind::Wall);
+        }
+        map.metadata = Some(MapMetadata {
+            name: "Pockets".to_string(),
+            description: String::new(),
+            author: None,
+            difficulty: None,
+            spawn: Some((0, 0)),
+            goal: Some((4, 0)),
+        });
+        map
+    }
+
+    #[test]
+    fn walled_off_pockets_are_separate_components_and_unreachable() {
+        let map = pocket_map();
+        let open = |x, y| map.is_traversable(x, y);
+
+        let sizes: Vec<usize> = connected_components(&map, open).iter().map(Vec::len).collect();
+        assert_eq!(sizes, [6, 3, 1]);
+        let reached = reachable_cells(&map, (0, 0), open);
+        assert_eq!(reached.len(), 6);
+        assert!(reached.iter().all(|&(x, _)| x < 2));
+        assert_eq!(
+            check_solvable(&map, open),
+            Err("goal (4, 0) is unreachable from spawn (0, 0)".to_string())
+        );
+
+        // A portal pair into the pocket joins it up, but not the lone cell
+        let mut linked = pocket_map();
+        linked.set(1, 2, TileKind::Portal(1));
+        linked.set(3, 1, TileKind::Portal(1));
+        let open = |x, y| linked.is_traversable(x, y);
+        assert_eq!(connected_components(&linked, open).len(), 3);
+        let reached = reachable_cells(&linked, (0, 0), open);
+        assert_eq!(reached.len(), 9);
+        assert!(!reached.contains(&(4, 2)));
+        assert_eq!(check_solvable(&linked, open), Ok(()));
+
+        let mut walled_goal = pocket_map();
+        walled_goal.set(4, 0, TileKind::Wall);
+        let open = |x, y| walled_goal.is_traversable(x, y);
+        let blocked = Err("goal (4, 0) is on a blocked cell".to_string());
+        assert_eq!(check_solvable(&walled_goal, open), blocked);
+    }
+}
@@ -0,0 +1,81 @@
+use crate::agent::Direction;
+use crate::map::GridMap;
+use crate::pathfinding::shortest_path;
+
+/// Scripted mover that walks a fixed route back and forth, blocking the cell it stands on
+///
+/// Patrollers never use the LLM; they exist to make the map dynamic so the agent has to
+/// replan around moving obstacles.
+#[derive(Clone, Debug)]
+pub struct Patroller {
+    path: Vec<(usize, usize)>,
+    index: usize,
+    /// Walking toward the end of `path` (true) or back toward its start
+    forward: bool,
+}
+
+impl Patroller {
+    /// Patroller starting at the first cell of `path`; None for an empty path
+    pub fn new(path: Vec<(usize, usize)>) -> Option<Self> {
+        if path.is_empty() {
+            return None;
+        }
+        Some(Self {
+            path,
+            index: 0,
+            forward: true,
+        })
+    }
+
+    /// Build a contiguous route visiting `waypoints` in order, then walking back
+    ///
+    /// Consecutive waypoints are joined by shortest paths of orthogonal moves over
+    /// `passable` cells. Returns None if any leg is unreachable.
+    pub fn through_waypoints(
+        map: &GridMap,
+        waypoints: &[(usize, usize)],
+        passable: impl Fn(usize, usize) -> bool,
+    ) -> Option<Self> {
+        let moves = Direction::for_topology(map.topology());
+        let mut path = vec![*waypoints.first()?];
+        for leg in waypoints.windows(2) {
+            let segment = shortest_path(map, leg[0], leg[1], moves, &passable)?;
+            path.extend_from_slice(&segment[1..]);
+        }
+        Self::new(path)
+    }
+
+    pub fn pos(&self) -> (usize, usize) {
+        self.path[self.index]
+    }
+
+    pub fn path(&self) -> &[(usize, usize)] {
+        &self.path
+    }
+
+    /// Take one step along the route, turning around at either end
+    ///
+    /// Waits in place when `blocked` says the next cell is occupied (e.g. by the agent).
+    pub fn advance(&mut self, blocked: impl Fn(usize, usize) -> bool) {
+        if self.path.len() < 2 {
+            return;
+        }
+        let at_end = if self.forward {
+            self.index + 1 == self.path.len()
+        } else {
+            self.index == 0
+        };
+        if at_end {
+            self.forward = !self.forward;
+        }
+        let next = if self.forward {
+            self.index + 1
+        } else {
+            self.index - 1
+        };
+        let (x, y) = self.path[next];
+        if !blocked(x, y) {
+            self.index = next;
+        }
+    }
+}
@@ -2,6 +2,11 @@ mod sprites;
 mod tiles;
 mod ui;
 
-pub use sprites::{draw_tree_sprite, generate_tree_sprite};
-pub use tiles::{draw_grass_tile, draw_sand_tile, draw_wall_tile, draw_water_tile};
+pub use sprites::{
+    draw_agent_sprite, draw_tree_sprite, generate_agent_sprite, generate_tree_sprite,
+};
+pub use tiles::{
+    draw_fuel_tile, draw_grass_tile, draw_portal_tile, draw_sand_tile, draw_wall_tile,
+    draw_water_tile, tile_base_color, TilePalette,
+};
 pub use ui::draw_log_entry;
@@ -83,6 +83,37 @@ pub fn generate_tree_sprite(size: usize) -> egui::ColorImage {
     img
 }
 
+/// Small robot, drawn in light grays so it can be tinted with the agent color
+pub fn generate_agent_sprite(size: usize) -> egui::ColorImage {
+    let mut img = egui::ColorImage::new([size, size], egui::Color32::TRANSPARENT);
+    let s = size as f32;
+    let body = egui::Color32::from_gray(235);
+    let shade = egui::Color32::from_gray(150);
+    let eye = egui::Color32::from_gray(40);
+
+    let mut fill = |x0: f32, y0: f32, x1: f32, y1: f32, col: egui::Color32| {
+        for y in (y0 * s) as usize..((y1 * s) as usize).min(size) {
+            for x in (x0 * s) as usize..((x1 * s) as usize).min(size) {
+                img[(x, y)] = col;
+            }
+        }
+    };
+
+    // Antenna, head, body and feet
+    fill(0.47, 0.04, 0.53, 0.16, shade);
+    fill(0.25, 0.16, 0.75, 0.45, body);
+    fill(0.18, 0.48, 0.82, 0.84, body);
+    fill(0.24, 0.84, 0.4, 0.96, shade);
+    fill(0.6, 0.84, 0.76, 0.96, shade);
+    // Eyes and chest panel
+    fill(0.34, 0.25, 0.44, 0.35, eye);
+    fill(0.56, 0.25, 0.66, 0.35, eye);
+    fill(0.36, 0.58, 0.64, 0.68, shade);
+    blit_disc(&mut img, s * 0.5, s * 0.05, s * 0.06, shade);
+
+    img
+}
+
 fn blit_disc(img: &mut egui::ColorImage, cx: f32, cy: f32, r: f32, col: egui::Color32) {
     let w = img.size[0] as i32;
     let h = img.size[1] as i32;
@@ -100,6 +131,19 @@ fn blit_disc(img: &mut egui::ColorImage, cx: f32, cy: f32, r: f32, col: egui::Co
     }
 }
 
+/// Draw the generated agent sprite centered at `center`, tinted with `color`
+pub fn draw_agent_sprite(
+    painter: &Painter,
+    center: egui::Pos2,
+    side: f32,
+    tex: &egui::TextureHandle,
+    color: egui::Color32,
+) {
+    let dst = Rect::from_center_size(center, egui::vec2(side, side));
+    let uv = Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+    painter.image(tex.id(), dst, uv, color);
+}
+
 pub fn draw_tree_sprite(painter: &Painter, rect: Rect, tex: &egui::TextureHandle) {
     let r = rect.shrink(2.0);
     let scale = 1.1;
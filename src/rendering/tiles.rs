@@ -1,7 +1,68 @@
+use crate::map::TileKind;
 use eframe::egui::{Painter, Rect};
+use serde::{Deserialize, Serialize};
 
-pub fn draw_grass_tile(painter: &Painter, rect: Rect) {
-    let base = egui::Color32::from_rgb(88, 160, 78);
+/// Set of tile colors the board is drawn with
+///
+/// `ColorBlind` uses the Okabe-Ito hues, which stay distinct under the common forms of
+/// color vision deficiency, and adds patterns so tiles differ by more than color alone.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TilePalette {
+    #[default]
+    Standard,
+    ColorBlind,
+}
+
+impl TilePalette {
+    pub const ALL: [TilePalette; 2] = [Self::Standard, Self::ColorBlind];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Standard => "Standard",
+            Self::ColorBlind => "Color-blind friendly",
+        }
+    }
+}
+
+/// Dominant color of a tile kind, for flat scaled-down rendering (e.g. the minimap)
+pub fn tile_base_color(kind: &TileKind, palette: TilePalette) -> egui::Color32 {
+    if palette == TilePalette::ColorBlind {
+        match kind {
+            TileKind::Grass => return egui::Color32::from_rgb(0, 158, 115),
+            TileKind::Water => return egui::Color32::from_rgb(0, 114, 178),
+            TileKind::Sand => return egui::Color32::from_rgb(230, 159, 0),
+            TileKind::Wall => return egui::Color32::from_rgb(35, 35, 35),
+            TileKind::Tree => return egui::Color32::from_rgb(0, 90, 65),
+            TileKind::Trail => return egui::Color32::from_rgb(240, 228, 66),
+            TileKind::Portal(_) => return egui::Color32::from_rgb(204, 121, 167),
+            TileKind::Fuel => return egui::Color32::from_rgb(213, 94, 0),
+            TileKind::Empty | TileKind::Custom(_) => {}
+        }
+    }
+    match kind {
+        TileKind::Empty => egui::Color32::from_rgb(240, 240, 240),
+        TileKind::Grass => egui::Color32::from_rgb(88, 160, 78),
+        TileKind::Water => egui::Color32::from_rgb(46, 105, 205),
+        TileKind::Sand => egui::Color32::from_rgb(220, 190, 150),
+        TileKind::Wall => egui::Color32::from_rgb(90, 90, 95),
+        TileKind::Tree => egui::Color32::from_rgb(45, 110, 50),
+        TileKind::Trail => egui::Color32::from_rgb(245, 215, 110),
+        TileKind::Portal(_) => egui::Color32::from_rgb(150, 80, 220),
+        TileKind::Fuel => egui::Color32::from_rgb(225, 85, 45),
+        TileKind::Custom(code) => egui::Color32::from_rgb(
+            ((code >> 16) & 0xFF) as u8,
+            ((code >> 8) & 0xFF) as u8,
+            (code & 0xFF) as u8,
+        ),
+    }
+}
+
+pub fn draw_grass_tile(painter: &Painter, rect: Rect, palette: TilePalette) {
+    let base = tile_base_color(&TileKind::Grass, palette);
+    let blade = match palette {
+        TilePalette::Standard => egui::Color32::from_rgb(120, 200, 110),
+        TilePalette::ColorBlind => egui::Color32::from_rgb(150, 230, 200),
+    };
     painter.rect_filled(rect.shrink(2.0), 2.0, base);
     let mut x = rect.left() + 3.0;
     while x < rect.right() - 3.0 {
@@ -13,23 +74,48 @@ pub fn draw_grass_tile(painter: &Painter, rect: Rect) {
             ],
             egui::Stroke {
                 width: 1.0,
-                color: egui::Color32::from_rgb(120, 200, 110),
+                color: blade,
             },
         );
         x += 3.5;
     }
 }
 
-pub fn draw_water_tile(painter: &Painter, rect: Rect) {
-    // Solid water color
-    let base = egui::Color32::from_rgb(46, 105, 205);
+pub fn draw_water_tile(painter: &Painter, rect: Rect, frame: u64, palette: TilePalette) {
+    let base = tile_base_color(&TileKind::Water, palette);
     painter.rect_filled(rect.shrink(2.0), 2.0, base);
+    if palette == TilePalette::ColorBlind {
+        draw_wave_pattern(painter, rect);
+    }
+
+    // Two short shimmer lines drifting sideways; phase is offset per tile so the lake
+    // doesn't pulse in lockstep
+    if rect.width() < 8.0 {
+        return;
+    }
+    let shimmer = egui::Color32::from_rgba_unmultiplied(170, 205, 255, 110);
+    let tile_phase = (rect.left() * 0.37 + rect.top() * 0.61).sin();
+    for (i, row) in [0.35, 0.7].into_iter().enumerate() {
+        let phase = frame as f32 * 0.25 + tile_phase * 3.0 + i as f32 * 2.0;
+        let offset = phase.sin() * rect.width() * 0.15;
+        let y = rect.top() + rect.height() * row;
+        let x = rect.center().x + offset;
+        let half = rect.width() * 0.18;
+        painter.line_segment(
+            [egui::pos2(x - half, y), egui::pos2(x + half, y)],
+            egui::Stroke::new(1.0, shimmer),
+        );
+    }
 }
 
-pub fn draw_sand_tile(painter: &Painter, rect: Rect) {
+pub fn draw_sand_tile(painter: &Painter, rect: Rect, palette: TilePalette) {
     // Simple, performant sand rendering - just a solid color with subtle variation
-    let base = egui::Color32::from_rgb(220, 190, 150);
+    let base = tile_base_color(&TileKind::Sand, palette);
     painter.rect_filled(rect.shrink(2.0), 2.0, base);
+    if palette == TilePalette::ColorBlind {
+        draw_dot_pattern(painter, rect);
+        return;
+    }
 
     // Add just a few subtle dots for texture (much fewer than before)
     let center = rect.center();
@@ -51,13 +137,16 @@ pub fn draw_sand_tile(painter: &Painter, rect: Rect) {
     }
 }
 
-pub fn draw_wall_tile(painter: &Painter, rect: Rect) {
-    let base = egui::Color32::from_rgb(90, 90, 95);
+pub fn draw_wall_tile(painter: &Painter, rect: Rect, palette: TilePalette) {
+    let base = tile_base_color(&TileKind::Wall, palette);
     painter.rect_filled(rect.shrink(1.0), 0.0, base);
     let r = rect.shrink(2.0);
     let bw = (r.width() / 3.0).max(2.0);
     let bh = (r.height() / 3.0).max(2.0);
-    let mortar = egui::Color32::from_rgb(140, 140, 140);
+    let mortar = match palette {
+        TilePalette::Standard => egui::Color32::from_rgb(140, 140, 140),
+        TilePalette::ColorBlind => egui::Color32::from_rgb(220, 220, 220),
+    };
     let stroke = egui::Stroke {
         width: 1.0,
         color: mortar,
@@ -77,3 +166,84 @@ pub fn draw_wall_tile(painter: &Painter, rect: Rect) {
         }
     }
 }
+
+pub fn draw_portal_tile(painter: &Painter, rect: Rect, id: u8, palette: TilePalette) {
+    let center = rect.center();
+    let radius = rect.width().min(rect.height()) * 0.38;
+    painter.rect_filled(rect.shrink(2.0), 2.0, egui::Color32::from_rgb(40, 20, 60));
+    painter.circle_filled(center, radius, tile_base_color(&TileKind::Portal(id), palette));
+    painter.circle_stroke(
+        center,
+        radius * 0.65,
+        egui::Stroke::new(1.5, egui::Color32::from_rgb(220, 190, 255)),
+    );
+    painter.text(
+        center,
+        egui::Align2::CENTER_CENTER,
+        id.to_string(),
+        egui::FontId::proportional((radius * 0.9).max(8.0)),
+        egui::Color32::WHITE,
+    );
+}
+
+/// Fuel can on a pale pad; the can's shape marks it in either palette
+pub fn draw_fuel_tile(painter: &Painter, rect: Rect, palette: TilePalette) {
+    painter.rect_filled(rect.shrink(2.0), 2.0, egui::Color32::from_rgb(250, 235, 215));
+    let can = Rect::from_center_size(
+        rect.center() + egui::vec2(0.0, rect.height() * 0.05),
+        egui::vec2(rect.width() * 0.45, rect.height() * 0.55),
+    );
+    painter.rect_filled(can, 2.0, tile_base_color(&TileKind::Fuel, palette));
+    // Spout on the top-right corner
+    let spout = Rect::from_min_size(
+        can.right_top() - egui::vec2(can.width() * 0.35, can.height() * 0.22),
+        egui::vec2(can.width() * 0.25, can.height() * 0.22),
+    );
+    painter.rect_filled(spout, 1.0, egui::Color32::from_rgb(90, 40, 20));
+    if rect.width() >= 12.0 {
+        painter.line_segment(
+            [
+                can.left_top() + egui::vec2(can.width() * 0.2, can.height() * 0.25),
+                can.right_bottom() - egui::vec2(can.width() * 0.2, can.height() * 0.25),
+            ],
+            egui::Stroke::new(1.0, egui::Color32::WHITE),
+        );
+    }
+}
+
+/// Static zig-zag waves, so water reads as water without relying on its hue
+fn draw_wave_pattern(painter: &Painter, rect: Rect) {
+    if rect.width() < 8.0 {
+        return;
+    }
+    let stroke = egui::Stroke::new(1.0, egui::Color32::from_rgb(200, 230, 255));
+    let r = rect.shrink(rect.width() * 0.18);
+    for row in [0.3, 0.75] {
+        let y = r.top() + r.height() * row;
+        let amp = r.height() * 0.08;
+        let step = r.width() / 4.0;
+        let points: Vec<_> = (0..=4)
+            .map(|i| {
+                let dy = if i % 2 == 0 { -amp } else { amp };
+                egui::pos2(r.left() + i as f32 * step, y + dy)
+            })
+            .collect();
+        painter.add(egui::Shape::line(points, stroke));
+    }
+}
+
+/// Even 3x3 grid of dark dots marking sand in the color-blind palette
+fn draw_dot_pattern(painter: &Painter, rect: Rect) {
+    let dot = egui::Color32::from_rgb(120, 75, 0);
+    let r = rect.shrink(rect.width() * 0.22);
+    let radius = (rect.width() * 0.05).clamp(0.6, 1.5);
+    for row in 0..3 {
+        for col in 0..3 {
+            let pos = egui::pos2(
+                r.left() + r.width() * col as f32 / 2.0,
+                r.top() + r.height() * row as f32 / 2.0,
+            );
+            painter.circle_filled(pos, radius, dot);
+        }
+    }
+}
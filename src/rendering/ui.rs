@@ -1,4 +1,4 @@
-use crate::agent::LogEntry;
+use crate::agent::{ErrorKind, LogEntry};
 use eframe::egui;
 
 /// Draw a rich log entry with color coding and formatting
@@ -73,9 +73,13 @@ pub fn draw_log_entry(ui: &mut egui::Ui, entry: &LogEntry) {
             ui.add_space(4.0);
         }
         LogEntry::ToolProposal { name, data } => {
+            // Per-entry "</>" toggle swaps the custom card for the generic raw JSON view
+            let raw_id = ui.id().with("show_raw_json");
+            let show_raw = ui.ctx().data(|d| d.get_temp::<bool>(raw_id)).unwrap_or(false);
+
             // Rich tool execution proposals with custom UI per tool
             match name.as_str() {
-                "think" => {
+                "think" if !show_raw => {
                     // Thinking tool - show as collapsible card with the agent's thoughts
                     let thoughts = data
                         .get("thoughts")
@@ -93,6 +97,7 @@ pub fn draw_log_entry(ui: &mut egui::Ui, entry: &LogEntry) {
                                         .size(13.0)
                                         .color(egui::Color32::from_rgb(130, 90, 180)),
                                 );
+                                draw_raw_json_toggle(ui, raw_id, show_raw);
                             });
 
                             ui.add_space(4.0);
@@ -116,7 +121,7 @@ pub fn draw_log_entry(ui: &mut egui::Ui, entry: &LogEntry) {
                         });
                     ui.add_space(4.0);
                 }
-                "get_map_state" => {
+                "get_map_state" if !show_raw => {
                     // Map state tool - show parameters and indicate what area is being viewed
                     frame
                         .fill(egui::Color32::from_rgb(240, 248, 255))
@@ -127,6 +132,7 @@ pub fn draw_log_entry(ui: &mut egui::Ui, entry: &LogEntry) {
                                     egui::RichText::new("Viewing map")
                                         .color(egui::Color32::from_rgb(60, 100, 140)),
                                 );
+                                draw_raw_json_toggle(ui, raw_id, show_raw);
                             });
 
                             // Show what area is being viewed
@@ -153,7 +159,7 @@ pub fn draw_log_entry(ui: &mut egui::Ui, entry: &LogEntry) {
                     ui.add_space(4.0);
                 }
                 _ => {
-                    // Fallback for unknown rich tools - show as generic tool call
+                    // Fallback for unknown rich tools (or raw view) - show as generic tool call
                     frame
                         .fill(egui::Color32::from_rgb(255, 250, 230))
                         .show(ui, |ui| {
@@ -169,6 +175,9 @@ pub fn draw_log_entry(ui: &mut egui::Ui, entry: &LogEntry) {
                                         .strong()
                                         .color(egui::Color32::from_rgb(200, 100, 0)),
                                 );
+                                if has_rich_view(name) {
+                                    draw_raw_json_toggle(ui, raw_id, show_raw);
+                                }
                             });
                             ui.label(
                                 egui::RichText::new(
@@ -282,16 +291,39 @@ pub fn draw_log_entry(ui: &mut egui::Ui, entry: &LogEntry) {
                 });
             ui.add_space(4.0);
         }
-        LogEntry::Error(text) => {
+        LogEntry::Teleport { portal, from, to } => {
+            frame
+                .fill(egui::Color32::from_rgb(245, 235, 255))
+                .show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(egui::RichText::new("O").size(14.0).strong());
+                        ui.label(
+                            egui::RichText::new(format!("Portal {}:", portal))
+                                .strong()
+                                .color(egui::Color32::from_rgb(130, 70, 200)),
+                        );
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "({}, {}) → ({}, {})",
+                                from.0, from.1, to.0, to.1
+                            ))
+                            .color(egui::Color32::from_rgb(110, 60, 170)),
+                        );
+                    });
+                });
+            ui.add_space(4.0);
+        }
+        LogEntry::Error(kind, text) => {
+            let (bg_color, icon, label_color) = error_style(*kind);
             frame
-                .fill(egui::Color32::from_rgb(255, 220, 220))
+                .fill(bg_color)
                 .show(ui, |ui| {
                     ui.horizontal(|ui| {
-                        ui.label(egui::RichText::new("WARN").size(16.0).strong());
+                        ui.label(egui::RichText::new(icon).size(16.0).strong());
                         ui.label(
-                            egui::RichText::new("Error:")
+                            egui::RichText::new(format!("{} error:", kind.label()))
                                 .strong()
-                                .color(egui::Color32::from_rgb(200, 50, 50)),
+                                .color(label_color),
                         );
                     });
 
@@ -300,14 +332,12 @@ pub fn draw_log_entry(ui: &mut egui::Ui, entry: &LogEntry) {
                     for (i, line) in lines.iter().enumerate() {
                         if i == 0 {
                             ui.label(
-                                egui::RichText::new(*line)
-                                    .color(egui::Color32::from_rgb(180, 40, 40))
-                                    .strong(),
+                                egui::RichText::new(*line).color(label_color).strong(),
                             );
                         } else {
                             ui.label(
                                 egui::RichText::new(*line)
-                                    .color(egui::Color32::from_rgb(160, 60, 60))
+                                    .color(label_color.gamma_multiply(0.85))
                                     .small(),
                             );
                         }
@@ -326,5 +356,69 @@ pub fn draw_log_entry(ui: &mut egui::Ui, entry: &LogEntry) {
                 });
             ui.add_space(4.0);
         }
+        LogEntry::Usage { prompt_tokens, completion_tokens, cost } => {
+            let mut text = format!(
+                "{} prompt + {} completion tokens",
+                prompt_tokens, completion_tokens
+            );
+            if let Some(cost) = cost {
+                text.push_str(&format!(" (${:.4})", cost));
+            }
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("TOKENS").small().strong());
+                ui.label(egui::RichText::new(text).small().color(egui::Color32::from_gray(120)));
+            });
+            ui.add_space(2.0);
+        }
+    }
+}
+
+/// Background, icon and label color for each error category
+fn error_style(kind: ErrorKind) -> (egui::Color32, &'static str, egui::Color32) {
+    match kind {
+        ErrorKind::Movement => (
+            egui::Color32::from_rgb(255, 235, 215),
+            "BLOCK",
+            egui::Color32::from_rgb(190, 100, 20),
+        ),
+        ErrorKind::Network => (
+            egui::Color32::from_rgb(225, 230, 250),
+            "NET",
+            egui::Color32::from_rgb(60, 70, 170),
+        ),
+        ErrorKind::Validation => (
+            egui::Color32::from_rgb(255, 245, 205),
+            "CHECK",
+            egui::Color32::from_rgb(150, 110, 0),
+        ),
+        ErrorKind::Tool => (
+            egui::Color32::from_rgb(255, 220, 220),
+            "WARN",
+            egui::Color32::from_rgb(200, 50, 50),
+        ),
+        ErrorKind::Internal => (
+            egui::Color32::from_rgb(235, 225, 235),
+            "BUG",
+            egui::Color32::from_rgb(120, 40, 90),
+        ),
     }
 }
+
+/// Tool proposals that have a custom card instead of the raw JSON view
+fn has_rich_view(name: &str) -> bool {
+    matches!(name, "think" | "get_map_state")
+}
+
+/// Small right-aligned "</>" button that flips the raw JSON view stored under `id`
+fn draw_raw_json_toggle(ui: &mut egui::Ui, id: egui::Id, show_raw: bool) {
+    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+        let hover = if show_raw { "Show formatted view" } else { "Show raw JSON" };
+        if ui
+            .selectable_label(show_raw, egui::RichText::new("</>").small().monospace())
+            .on_hover_text(hover)
+            .clicked()
+        {
+            ui.ctx().data_mut(|d| d.insert_temp(id, !show_raw));
+        }
+    });
+}
@@ -0,0 +1,256 @@
+use crate::agent::Agent;
+use crate::events::Event;
+use crate::map::GridMap;
+use std::collections::HashSet;
+
+/// An event executed while recording, with what it needs to run again identically
+#[derive(Clone, Debug)]
+struct RecordedStep {
+    /// Ticks since the recording started
+    tick: u64,
+    event: Event,
+    /// Cells held by patrollers when it ran, which block the agent like walls
+    obstacles: HashSet<(usize, usize)>,
+    /// `GridMap::content_hash` of the live map just before it ran
+    map_hash: u64,
+}
+
+/// A recorded session: the map and agent it started from and every event executed since
+///
+/// Events are replayed in order rather than by wall-clock time, so rebuilding any point
+/// of the session is deterministic. Changes made outside the event queue (editor
+/// painting, tiles placed by tool calls) are not captured, but each step remembers the
+/// live map's hash so a rebuild can tell where it stopped matching.
+#[derive(Clone, Debug)]
+pub struct Recording {
+    start_map: GridMap,
+    start_agent: Agent,
+    steps: Vec<RecordedStep>,
+    ticks: u64,
+}
+
+/// Map and agent as they were after the first `step` recorded events
+#[derive(Clone, Debug)]
+pub struct ReplayView {
+    pub step: usize,
+    pub map: GridMap,
+    pub agent: Agent,
+    /// First step (1-based) whose rebuilt map didn't match the live map it ran on
+    pub diverged_at: Option<usize>,
+}
+
+impl Recording {
+    /// Snapshot the starting state
+    pub fn start(map: &GridMap, agent: &Agent) -> Self {
+        Self {
+            start_map: map.clone(),
+            start_agent: agent.clone(),
+            steps: Vec::new(),
+            ticks: 0,
+        }
+    }
+
+    /// Number of recorded events
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Ticks the recording has been running
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Tick at which the `step`th event (1-based) ran; 0 for the start
+    pub fn tick_at(&self, step: usize) -> u64 {
+        step.checked_sub(1)
+            .and_then(|index| self.steps.get(index))
+            .map_or(0, |recorded| recorded.tick)
+    }
+
+    /// Short description of the `step`th event (1-based), e.g. "move right"
+    pub fn describe(&self, step: usize) -> String {
+        let Some(recorded) = step.checked_sub(1).and_then(|index| self.steps.get(index)) else {
+            return "start".to_string();
+        };
+        match &recorded.event {
+            Event::AgentMove { direction, .. } => format!("move {}", direction.as_str()),
+            Event::Delay { ticks } => format!("wait {} ticks", ticks),
+            Event::Custom(custom) => custom.name.clone(),
+        }
+    }
+
+    /// State after `step` events, continuing from `view` when it hasn't passed `step`
+    /// and replaying from the start snapshot otherwise
+    pub fn seek(&self, view: Option<ReplayView>, step: usize) -> ReplayView {
+        let step = step.min(self.steps.len());
+        let mut view = match view {
+            Some(view) if view.step <= step => view,
+            _ => ReplayView {
+                step: 0,
+                map: self.start_map.clone(),
+                agent: self.start_agent.clone(),
+                diverged_at: None,
+            },
+        };
+        for (index, recorded) in self.steps.iter().enumerate().take(step).skip(view.step) {
+            if view.diverged_at.is_none() && view.map.content_hash() != recorded.map_hash {
+                view.diverged_at = Some(index + 1);
+            }
+            view.agent.set_dynamic_obstacles(recorded.obstacles.iter().copied());
+            // Failed steps failed the same way live; their effect (none) is what we want
+            let _ = recorded.event.clone().apply(&mut view.agent, &mut view.map);
+        }
+        view.step = step;
+        view
+    }
+}
+
+/// Session recorder plus the scrubber position while a recording is being viewed
+#[derive(Default)]
+pub struct Replay {
+    recording: Option<Recording>,
+    active: bool,
+    view: Option<ReplayView>,
+}
+
+impl Replay {
+    /// Start a new recording from the current state, dropping the previous one
+    pub fn start(&mut self, map: &GridMap, agent: &Agent) {
+        self.recording = Some(Recording::start(map, agent));
+        self.active = true;
+        self.view = None;
+    }
+
+    pub fn stop(&mut self) {
+        self.active = false;
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.active
+    }
+
+    pub fn recording(&self) -> Option<&Recording> {
+        self.recording.as_ref()
+    }
+
+    /// Note an event the tick loop is about to run on `map`
+    pub fn record(&mut self, event: &Event, map: &GridMap, obstacles: &HashSet<(usize, usize)>) {
+        if self.active
+            && let Some(recording) = &mut self.recording
+        {
+            recording.steps.push(RecordedStep {
+                tick: recording.ticks,
+                event: event.clone(),
+                obstacles: obstacles.clone(),
+                map_hash: map.content_hash(),
+            });
+        }
+    }
+
+    /// Count a tick of the loop toward the recording
+    pub fn tick(&mut self) {
+        if self.active
+            && let Some(recording) = &mut self.recording
+        {
+            recording.ticks += 1;
+        }
+    }
+
+    /// Show the recording after `step` events instead of the live state
+    pub fn scrub_to(&mut self, step: usize) {
+        if let Some(recording) = &self.recording {
+            self.view = Some(recording.seek(self.view.take(), step));
+        }
+    }
+
+    /// Return to the live state
+    pub fn stop_scrubbing(&mut self) {
+        self.view = None;
+    }
+
+    /// The state being scrubbed to, if any
+    pub fn view(&self) -> Option<&ReplayView> {
+        self.view.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::Direction;
+    use crate::map::TileKind;
+
+    /// Record and run a move the way the tick loop does
+    fn step(replay: &mut Replay, agent: &mut Agent, map: &mut GridMap, direction: Direction) {
+        let event = Event::AgentMove { agent_id: agent.id, direction };
+        replay.record(&event, map, agent.dynamic_obstacles());
+        event.apply(agent, map).unwrap();
+    }
+
+    fn obstacles(cells: &[(usize, usize)]) -> HashSet<(usize, usize)> {
+        cells.iter().copied().collect()
+    }
+
+    #[test]
+    fn seek_rebuilds_positions_and_obstacles_both_ways() {
+        let mut map = GridMap::new(5, 3, TileKind::Grass);
+        let mut agent = Agent::new(1, "a", 0, 0);
+        let mut replay = Replay::default();
+        replay.start(&map, &agent);
+        agent.set_dynamic_obstacles([(4, 0)]);
+        step(&mut replay, &mut agent, &mut map, Direction::Right);
+        agent.set_dynamic_obstacles([(3, 1)]);
+        step(&mut replay, &mut agent, &mut map, Direction::Right);
+        agent.set_dynamic_obstacles([]);
+        step(&mut replay, &mut agent, &mut map, Direction::Down);
+        replay.stop();
+
+        let recording = replay.recording().unwrap();
+        assert_eq!(recording.len(), 3);
+        let view = recording.seek(None, 2);
+        assert_eq!(view.agent.pos(), (2, 0));
+        assert_eq!(view.agent.dynamic_obstacles(), &obstacles(&[(3, 1)]));
+
+        // Back to step 1 rebuilds from the start snapshot
+        let view = recording.seek(Some(view), 1);
+        assert_eq!(view.agent.pos(), (1, 0));
+        assert_eq!(view.agent.dynamic_obstacles(), &obstacles(&[(4, 0)]));
+
+        // Forward again continues from the view, and past the end clamps
+        let view = recording.seek(Some(view), 10);
+        assert_eq!(view.step, 3);
+        assert_eq!(view.agent.pos(), agent.pos());
+        assert!(view.agent.dynamic_obstacles().is_empty());
+        assert_eq!(view.map.content_hash(), map.content_hash());
+        assert_eq!(view.diverged_at, None);
+
+        let view = recording.seek(Some(view), 0);
+        assert_eq!(view.agent.pos(), (0, 0));
+        assert_eq!(recording.describe(0), "start");
+        assert_eq!(recording.describe(3), "move down");
+    }
+
+    #[test]
+    fn seek_reports_where_the_map_was_changed_outside_the_recording() {
+        let mut map = GridMap::new(4, 2, TileKind::Grass);
+        let mut agent = Agent::new(1, "a", 0, 0);
+        let mut replay = Replay::default();
+        replay.start(&map, &agent);
+        step(&mut replay, &mut agent, &mut map, Direction::Right);
+        // Painted in the editor, so the recording never sees it
+        map.set(3, 1, TileKind::Wall);
+        step(&mut replay, &mut agent, &mut map, Direction::Right);
+        step(&mut replay, &mut agent, &mut map, Direction::Down);
+
+        let recording = replay.recording().unwrap();
+        assert_eq!(recording.seek(None, 1).diverged_at, None);
+        let view = recording.seek(None, 3);
+        assert_eq!(view.diverged_at, Some(2));
+        assert_eq!(view.agent.pos(), (2, 1));
+        assert_ne!(view.map.content_hash(), map.content_hash());
+    }
+}
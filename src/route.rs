@@ -0,0 +1,230 @@
+use crate::agent::Direction;
+use crate::map::{GridMap, Topology};
+use crate::pathfinding::{grid_distance, shortest_path};
+use serde_json::{Value, json};
+
+/// Cell one step from (x, y) in `dir`, or None past the map edge
+fn step(map: &GridMap, (x, y): (usize, usize), dir: &Direction) -> Option<(usize, usize)> {
+    let (dx, dy) = dir.offset(map.topology(), y as i64);
+    let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+    (nx >= 0 && ny >= 0 && map.in_bounds(nx as usize, ny as usize))
+        .then_some((nx as usize, ny as usize))
+}
+
+/// Turn-by-turn summary of walking `directions` from `start`, e.g.
+/// "go east 4 until the wall at (7, 2), then go south 3, arriving at (6, 5)"
+///
+/// Consecutive moves in the same direction are merged. When the route turns because the
+/// cell straight ahead is one `passable` rejects, that obstacle is named. Stepping onto a
+/// paired portal follows it, just like the agent does.
+pub fn explain_route(
+    map: &GridMap,
+    start: (usize, usize),
+    directions: &[Direction],
+    passable: impl Fn(usize, usize) -> bool,
+) -> String {
+    if directions.is_empty() {
+        return format!("Already at ({}, {}); no moves needed", start.0, start.1);
+    }
+
+    let mut legs = Vec::new();
+    let mut pos = start;
+    let mut i = 0;
+    while i < directions.len() {
+        let dir = &directions[i];
+        let mut count = 0;
+        let mut portal = None;
+        while i < directions.len() && directions[i] == *dir && portal.is_none() {
+            let Some(next) = step(map, pos, dir) else {
+                break;
+            };
+            count += 1;
+            i += 1;
+            pos = match map.portal_exit(next.0, next.1) {
+                Some(exit) => {
+                    portal = Some((next, exit));
+                    exit
+                }
+                None => next,
+            };
+        }
+        if count == 0 {
+            // A move off the map edge goes nowhere, so the rest of the route can't be followed
+            legs.push(format!("stop at the map edge going {}", dir.compass()));
+            break;
+        }
+
+        let mut leg = format!("go {} {}", dir.compass(), count);
+        if let Some((entry, exit)) = portal {
+            leg.push_str(&format!(
+                " into the portal at ({}, {}) and come out at ({}, {})",
+                entry.0, entry.1, exit.0, exit.1
+            ));
+        } else if i < directions.len() {
+            match step(map, pos, dir) {
+                None => leg.push_str(" to the map edge"),
+                Some((ax, ay)) if !passable(ax, ay) => {
+                    let what = map.get(ax, ay).map_or("obstacle", |tile| tile.name());
+                    leg.push_str(&format!(" until the {} at ({}, {})", what, ax, ay));
+                }
+                Some(_) => {}
+            }
+        }
+        legs.push(leg);
+    }
+
+    format!("{}, arriving at ({}, {})", legs.join(", then "), pos.0, pos.1)
+}
+
+/// Straight-line and walking distances between two cells, for comparing candidate targets
+///
+/// Only the map is consulted: the walking distance is the shortest route over traversable
+/// tiles (portals included), whoever walks it. When there is no route it is null, with
+/// the reason alongside. Hex maps report their grid distance as "hex" instead of
+/// Manhattan and Chebyshev, and measure Euclidean distance between the staggered cell
+/// centers.
+pub fn measure_distances(
+    map: &GridMap,
+    from: (usize, usize),
+    to: (usize, usize),
+) -> Result<Value, String> {
+    for (name, (x, y)) in [("from", from), ("to", to)] {
+        if !map.in_bounds(x, y) {
+            return Err(format!(
+                "{} ({}, {}) is outside the {}x{} map",
+                name,
+                x,
+                y,
+                map.width(),
+                map.height()
+            ));
+        }
+    }
+
+    let mut result = json!({
+        "from": {"x": from.0, "y": from.1},
+        "to": {"x": to.0, "y": to.1},
+    });
+    let (dx, dy) = (from.0.abs_diff(to.0), from.1.abs_diff(to.1));
+    let euclidean = match map.topology() {
+        Topology::Square => {
+            result["manhattan"] = json!(dx + dy);
+            result["chebyshev"] = json!(dx.max(dy));
+            ((dx * dx + dy * dy) as f64).sqrt()
+        }
+        Topology::Hex => {
+            result["hex"] = json!(grid_distance(Topology::Hex, from, to));
+            // Odd rows sit half a cell to the right, and rows are sqrt(3)/2 apart
+            let center = |(x, y): (usize, usize)| {
+                (x as f64 + 0.5 * (y % 2) as f64, y as f64 * 3f64.sqrt() / 2.0)
+            };
+            let ((x1, y1), (x2, y2)) = (center(from), center(to));
+            (x1 - x2).hypot(y1 - y2)
+        }
+    };
+    result["euclidean"] = json!((euclidean * 100.0).round() / 100.0);
+
+    let blocked = [from, to]
+        .into_iter()
+        .find(|&(x, y)| !map.is_traversable(x, y));
+    let moves = Direction::for_topology(map.topology());
+    let path = blocked
+        .is_none()
+        .then(|| shortest_path(map, from, to, moves, |x, y| map.is_traversable(x, y)))
+        .flatten();
+    match (blocked, path) {
+        (None, Some(path)) => result["path"] = json!(path.len() - 1),
+        (Some((x, y)), _) => {
+            let tile = map.get(x, y).map_or("obstacle", |tile| tile.name());
+            result["path"] = Value::Null;
+            result["path_reason"] = json!(format!("({}, {}) is a {} tile", x, y, tile));
+        }
+        (None, None) => {
+            result["path"] = Value::Null;
+            result["path_reason"] = json!("no route between them over traversable tiles");
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::TileKind;
+
+    fn explain(map: &GridMap, start: (usize, usize), directions: &[Direction]) -> String {
+        explain_route(map, start, directions, |x, y| map.is_traversable(x, y))
+    }
+
+    #[test]
+    fn explains_merged_legs_and_the_obstacle_at_a_turn() {
+        let mut map = GridMap::new(6, 4, TileKind::Grass);
+        map.set(4, 0, TileKind::Wall);
+        let route = [Direction::Right, Direction::Right, Direction::Right, Direction::Down];
+        assert_eq!(
+            explain(&map, (0, 0), &route),
+            "go east 3 until the wall at (4, 0), then go south 1, arriving at (3, 1)"
+        );
+        // A turn with nothing in the way just changes direction
+        assert_eq!(
+            explain(&map, (0, 1), &[Direction::Right, Direction::Right, Direction::Up]),
+            "go east 2, then go north 1, arriving at (2, 0)"
+        );
+        assert_eq!(explain(&map, (2, 2), &[]), "Already at (2, 2); no moves needed");
+    }
+
+    #[test]
+    fn explains_portal_legs_and_stops_at_the_map_edge() {
+        let mut map = GridMap::new(6, 4, TileKind::Grass);
+        map.set(1, 0, TileKind::Portal(1));
+        map.set(3, 3, TileKind::Portal(1));
+        assert_eq!(
+            explain(&map, (0, 0), &[Direction::Right, Direction::Left]),
+            "go east 1 into the portal at (1, 0) and come out at (3, 3), then go west 1, \
+             arriving at (2, 3)"
+        );
+
+        assert_eq!(
+            explain(&map, (1, 1), &[Direction::Left, Direction::Left, Direction::Down]),
+            "go west 1 to the map edge, then stop at the map edge going west, arriving at (0, 1)"
+        );
+    }
+
+    #[test]
+    fn measures_straight_line_and_walking_distances() {
+        let map = GridMap::new(5, 5, TileKind::Grass);
+        let result = measure_distances(&map, (0, 0), (3, 4)).unwrap();
+        assert_eq!(result["manhattan"], 7);
+        assert_eq!(result["chebyshev"], 4);
+        assert_eq!(result["euclidean"], 5.0);
+        assert_eq!(result["path"], 7);
+
+        let mut hex = map.clone();
+        hex.set_topology(Topology::Hex);
+        let result = measure_distances(&hex, (0, 0), (2, 2)).unwrap();
+        assert_eq!(result["hex"], 3);
+        assert_eq!(result["path"], 3);
+        assert!(result.get("manhattan").is_none());
+        assert_eq!(result["euclidean"], 2.65);
+    }
+
+    #[test]
+    fn off_map_blocked_and_unreachable_points() {
+        let mut map = GridMap::new(5, 3, TileKind::Grass);
+        let error = measure_distances(&map, (0, 0), (5, 1)).unwrap_err();
+        assert_eq!(error, "to (5, 1) is outside the 5x3 map");
+
+        map.set(4, 1, TileKind::Water);
+        let result = measure_distances(&map, (0, 0), (4, 1)).unwrap();
+        assert_eq!(result["path"], Value::Null);
+        assert_eq!(result["path_reason"], "(4, 1) is a water tile");
+
+        for y in 0..3 {
+            map.set(2, y, TileKind::Wall);
+        }
+        let result = measure_distances(&map, (0, 0), (4, 0)).unwrap();
+        assert_eq!(result["manhattan"], 4);
+        assert_eq!(result["path"], Value::Null);
+        assert_eq!(result["path_reason"], "no route between them over traversable tiles");
+    }
+}
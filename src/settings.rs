@@ -0,0 +1,165 @@
+use crate::animation::IdleRepaint;
+use crate::board::{GridLineStyle, OutOfBoundsStyle};
+use crate::keyboard::MovementKeyScheme;
+use crate::map::Topology;
+use crate::rendering::TilePalette;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use web_time::Duration;
+
+// localStorage key the settings are saved under
+const STORAGE_KEY: &str = "settings";
+
+// Default time an LLM stream may go without any event before it is abandoned
+const DEFAULT_LLM_TIMEOUT: Duration = Duration::from_secs(60);
+
+// Default number of times a request failing with a network error, 429 or 5xx is resent
+const DEFAULT_LLM_RETRIES: u32 = 3;
+
+/// App-wide preferences edited in the Settings window and saved to localStorage
+///
+/// Per-agent configuration (rules, costs, sampling) stays on `Agent`; this only holds
+/// what `MyApp` itself reads. Fields missing from a saved copy fall back to defaults,
+/// so adding a setting doesn't discard the rest.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub movement_keys: MovementKeyScheme,
+    pub grid_lines: GridLineStyle,
+    pub show_trail: bool,
+    pub tile_palette: TilePalette,
+    pub out_of_bounds: OutOfBoundsStyle,
+    pub idle_repaint: IdleRepaint,
+    /// Grid shape applied to every loaded map
+    pub topology: Topology,
+    /// Stream silence after which the LLM turn is abandoned
+    pub llm_timeout: Duration,
+    /// Resends of an LLM request after a transient failure, with exponential backoff
+    pub llm_retries: u32,
+    /// Stream LLM responses; off sends each turn as one request and waits for the reply
+    pub streaming: bool,
+    /// Record the full request and response of every LLM turn for debugging
+    pub decision_log: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            movement_keys: MovementKeyScheme::default(),
+            grid_lines: GridLineStyle::default(),
+            show_trail: true,
+            tile_palette: TilePalette::default(),
+            out_of_bounds: OutOfBoundsStyle::default(),
+            idle_repaint: IdleRepaint::default(),
+            topology: Topology::default(),
+            llm_timeout: DEFAULT_LLM_TIMEOUT,
+            llm_retries: DEFAULT_LLM_RETRIES,
+            streaming: true,
+            decision_log: false,
+        }
+    }
+}
+
+/// Result of applying imported settings: field names that took effect, and skipped ones
+/// with the reason
+#[derive(Debug, Default)]
+pub struct SettingsImport {
+    pub applied: Vec<String>,
+    pub ignored: Vec<String>,
+}
+
+impl Settings {
+    /// Apply the fields of an exported settings object one at a time
+    ///
+    /// Unknown fields and values that don't parse are skipped and listed in `ignored`, so
+    /// one bad entry doesn't throw away the rest.
+    pub fn apply_json(&mut self, value: &Value) -> Result<SettingsImport, String> {
+        let Value::Object(fields) = value else {
+            return Err("Settings must be a JSON object".to_string());
+        };
+        let mut current = serde_json::to_value(*self).map_err(|e| e.to_string())?;
+        let mut import = SettingsImport::default();
+        for (key, field) in fields {
+            if current.get(key).is_none() {
+                import.ignored.push(format!("{} (unknown setting)", key));
+                continue;
+            }
+            let mut candidate = current.clone();
+            candidate[key] = field.clone();
+            match serde_json::from_value::<Settings>(candidate.clone()) {
+                Ok(settings) => {
+                    *self = settings;
+                    current = candidate;
+                    import.applied.push(key.clone());
+                }
+                Err(e) => import.ignored.push(format!("{} ({})", key, e)),
+            }
+        }
+        Ok(import)
+    }
+
+    /// Load saved settings from localStorage; None if nothing (valid) was saved
+    pub fn load() -> Option<Self> {
+        let json = web_sys::window()
+            .and_then(|window| window.local_storage().ok())
+            .flatten()
+            .and_then(|storage| storage.get_item(STORAGE_KEY).ok())
+            .flatten()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    /// Save settings to localStorage
+    pub fn save(&self) {
+        if let Some(window) = web_sys::window()
+            && let Ok(Some(storage)) = window.local_storage()
+            && let Ok(json) = serde_json::to_string(self)
+        {
+            let _ = storage.set_item(STORAGE_KEY, &json);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn apply_json_keeps_valid_fields_and_lists_the_rest() {
+        let mut settings = Settings::default();
+        let import = settings
+            .apply_json(&json!({
+                "show_trail": false,
+                "llm_retries": 5,
+                "streaming": "sometimes",
+                "llm_timeout": {"secs": 5, "nanos": 0},
+                "topology": "Triangle",
+                "volume": 11
+            }))
+            .unwrap();
+
+        let mut applied = import.applied.clone();
+        applied.sort();
+        assert_eq!(applied, vec!["llm_retries", "llm_timeout", "show_trail"]);
+        let mut ignored = import.ignored.clone();
+        ignored.sort();
+        assert_eq!(ignored.len(), 3);
+        assert!(ignored[0].starts_with("streaming ("));
+        assert!(ignored[1].starts_with("topology ("));
+        assert_eq!(ignored[2], "volume (unknown setting)");
+
+        assert!(!settings.show_trail);
+        assert_eq!(settings.llm_retries, 5);
+        assert_eq!(settings.llm_timeout, Duration::from_secs(5));
+        // Rejected fields keep their previous values
+        assert!(settings.streaming);
+        assert_eq!(settings.topology, Topology::default());
+    }
+
+    #[test]
+    fn apply_json_rejects_anything_but_an_object() {
+        let mut settings = Settings::default();
+        assert!(settings.apply_json(&json!([1, 2])).is_err());
+        assert_eq!(settings, Settings::default());
+    }
+}
@@ -0,0 +1,255 @@
+use crate::agent::{Agent, Direction};
+use crate::clock::{MockClock, SharedClock};
+use crate::events::{Event, EventQueue};
+use crate::map::GridMap;
+use crate::pathfinding::shortest_path;
+use crate::rng::SeededRng;
+use std::sync::Arc;
+use web_time::{Duration, Instant};
+
+/// Aggregate results of a stress test
+#[derive(Clone, Debug, Default)]
+pub struct StressStats {
+    pub trials: usize,
+    /// Trials where the agent ended on the goal
+    pub reached: usize,
+    /// Pairs with no route between them (not counted as failures)
+    pub unreachable: usize,
+    /// Trials with a route where the agent still didn't end on the goal
+    pub failed: usize,
+    /// Submitted events that never completed, or completed more than once
+    pub lost_events: usize,
+    /// Trials whose events were still pending when the tick limit ran out
+    pub stalled: usize,
+    pub moves: usize,
+    pub plan_time: Duration,
+    pub run_time: Duration,
+    pub slowest: Duration,
+}
+
+impl StressStats {
+    /// Share of routable trials that reached the goal, as a percentage
+    pub fn success_rate(&self) -> f32 {
+        let routable = self.trials - self.unreachable;
+        if routable == 0 {
+            return 0.0;
+        }
+        self.reached as f32 * 100.0 / routable as f32
+    }
+
+    /// Average time per trial spent planning and executing, over trials that had a route
+    pub fn average_times(&self) -> (Duration, Duration) {
+        let routable = (self.trials - self.unreachable).max(1) as u32;
+        (self.plan_time / routable, self.run_time / routable)
+    }
+}
+
+/// Repeated navigations between random cells on a copy of the current map
+///
+/// Each trial plans a route with the BFS pathfinder (no LLM), submits it as move events to
+/// a private event queue on a `MockClock`, and ticks the queue until it drains, so the
+/// same event, movement and pathfinding code as a real run is exercised end to end.
+/// The live map and agent are never touched.
+pub struct StressTest {
+    map: GridMap,
+    agent: Agent,
+    cells: Vec<(usize, usize)>,
+    rng: SeededRng,
+    step_delay: Duration,
+    remaining: usize,
+    stats: StressStats,
+}
+
+impl StressTest {
+    pub fn new(
+        map: &GridMap,
+        agent: &Agent,
+        trials: usize,
+        seed: u64,
+        step_delay: Duration,
+    ) -> Self {
+        let mut agent = agent.clone();
+        // Trials run on the static map; patrollers and chat state don't take part
+        agent.set_dynamic_obstacles([]);
+        agent.clear_logs();
+        agent.clear_chat_history();
+        let cells = (0..map.height())
+            .flat_map(|y| (0..map.width()).map(move |x| (x, y)))
+            .filter(|&(x, y)| agent.can_enter(map, x, y))
+            .collect();
+        Self {
+            map: map.clone(),
+            agent,
+            cells,
+            rng: SeededRng::new(seed),
+            step_delay,
+            remaining: trials,
+            stats: StressStats::default(),
+        }
+    }
+
+    pub fn stats(&self) -> &StressStats {
+        &self.stats
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.remaining == 0 || self.cells.is_empty()
+    }
+
+    /// Trials still to run
+    pub fn remaining(&self) -> usize {
+        if self.cells.is_empty() { 0 } else { self.remaining }
+    }
+
+    /// Skip the remaining trials, keeping the results so far
+    pub fn stop(&mut self) {
+        self.remaining = 0;
+    }
+
+    /// Run up to `count` more trials
+    pub fn run_batch(&mut self, count: usize) {
+        for _ in 0..count {
+            if self.is_finished() {
+                return;
+            }
+            self.remaining -= 1;
+            self.run_trial();
+        }
+    }
+
+    fn random_cell(&mut self) -> (usize, usize) {
+        self.cells[self.rng.below(self.cells.len() as u64) as usize]
+    }
+
+    fn run_trial(&mut self) {
+        let start = self.random_cell();
+        let mut goal = self.random_cell();
+        if self.cells.len() > 1 {
+            while goal == start {
+                goal = self.random_cell();
+            }
+        }
+        self.stats.trials += 1;
+
+        let mut map = self.map.clone();
+        let agent = &mut self.agent;
+        // Each trial walks a fresh copy of the map, so trail and target from the last one
+        // would describe cells that no longer exist
+        agent.forget_trail_tiles();
+        agent.clear_target();
+        agent.set_pos(start.0, start.1);
+        agent.refill_energy();
+
+        let started = Instant::now();
+        let moves = agent.directions(&map);
+        let path = shortest_path(&map, start, goal, moves, |x, y| agent.can_enter(&map, x, y));
+        let Some(directions) = path.and_then(|path| Agent::directions_along(&map, &path)) else {
+            self.stats.unreachable += 1;
+            return;
+        };
+        self.stats.plan_time += started.elapsed();
+
+        let running = Instant::now();
+        let moves = directions.len();
+        let outcome = Self::execute(agent, &mut map, directions, self.step_delay);
+        self.stats.run_time += running.elapsed();
+        self.stats.slowest = self.stats.slowest.max(started.elapsed());
+        self.stats.moves += moves;
+        if let Err(problem) = outcome {
+            match problem {
+                TrialProblem::Stalled => self.stats.stalled += 1,
+                TrialProblem::LostEvents(count) => self.stats.lost_events += count,
+            }
+        }
+        if agent.pos() == goal {
+            self.stats.reached += 1;
+        } else {
+            self.stats.failed += 1;
+        }
+
+        // Keep the working agent from growing across thousands of trials
+        agent.clear_logs();
+        agent.clear_movement_history();
+    }
+
+    /// Submit `directions` as move events and tick a mock clock until the queue drains
+    fn execute(
+        agent: &mut Agent,
+        map: &mut GridMap,
+        directions: Vec<Direction>,
+        step_delay: Duration,
+    ) -> Result<(), TrialProblem> {
+        let clock = Arc::new(MockClock::new());
+        let queue = EventQueue::with_clock(clock.clone() as SharedClock);
+        let events: Vec<Event> = directions
+            .into_iter()
+            .map(|direction| Event::AgentMove {
+                agent_id: agent.id,
+                direction,
+            })
+            .collect();
+        let submitted = queue.submit_sequence(events, step_delay).len();
+
+        // Generous limit: one tick per event plus slack, so only a real stall trips it
+        let mut ticks_left = submitted * 2 + 10;
+        let mut aborted = false;
+        while queue.pending_count() > 0 {
+            if ticks_left == 0 {
+                return Err(TrialProblem::Stalled);
+            }
+            ticks_left -= 1;
+            while let Some(scheduled) = queue.pop_ready() {
+                let result = scheduled.event.apply(agent, map);
+                if let Err(message) = &result
+                    && message.starts_with("ABORT:")
+                {
+                    queue.cancel_agent_events(agent.id);
+                    aborted = true;
+                }
+                queue.complete(scheduled.id, result);
+            }
+            clock.advance(step_delay);
+        }
+
+        // An aborted sequence legitimately completes fewer events than were submitted
+        let completed = queue.recent_completed(usize::MAX).len();
+        if completed > submitted || (!aborted && completed < submitted) {
+            return Err(TrialProblem::LostEvents(completed.abs_diff(submitted)));
+        }
+        Ok(())
+    }
+}
+
+/// What went wrong with the event queue during a trial
+enum TrialProblem {
+    Stalled,
+    LostEvents(usize),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::TileKind;
+
+    #[test]
+    fn every_trial_on_an_open_map_reaches_its_goal() {
+        let mut map = GridMap::new(6, 6, TileKind::Grass);
+        let mut agent = Agent::new(1, "a", 0, 0);
+        agent.set_leave_trail_tiles(true, &mut map);
+        let mut test = StressTest::new(&map, &agent, 25, 7, Duration::from_millis(50));
+
+        test.run_batch(10);
+        assert_eq!(test.remaining(), 15);
+        test.run_batch(100);
+        assert!(test.is_finished());
+
+        let stats = test.stats();
+        assert_eq!(stats.trials, 25);
+        assert_eq!(stats.reached, stats.trials);
+        assert_eq!(stats.unreachable, 0);
+        assert_eq!(stats.lost_events, 0);
+        assert_eq!(stats.stalled, 0);
+        assert!(stats.moves > 0);
+        assert_eq!(stats.success_rate(), 100.0);
+    }
+}
@@ -0,0 +1,174 @@
+use crate::agent::Agent;
+use crate::map::GridMap;
+use crate::pathfinding::{trace_search, SearchAlgorithm, SearchTrace};
+use web_time::{Duration, Instant};
+
+/// Step-by-step playback of a recorded pathfinder search, for teaching how searches spread
+pub struct TeachMode {
+    pub algorithm: SearchAlgorithm,
+    /// Time between revealed expansions while playing
+    pub step_interval: Duration,
+    trace: Option<SearchTrace>,
+    /// Number of expansions revealed so far
+    shown: usize,
+    playing: bool,
+    last_step: Instant,
+}
+
+impl TeachMode {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            algorithm: SearchAlgorithm::Bfs,
+            step_interval: Duration::from_millis(80),
+            trace: None,
+            shown: 0,
+            playing: false,
+            last_step: now,
+        }
+    }
+
+    /// Record a search from the agent to `goal` and start playing it from the beginning
+    pub fn start(&mut self, map: &GridMap, agent: &Agent, goal: (usize, usize), now: Instant) {
+        self.trace = Some(trace_search(
+            map,
+            agent.pos(),
+            goal,
+            self.algorithm,
+            agent.directions(map),
+            |x, y| agent.can_enter(map, x, y),
+            |x, y| agent.movement_cost(map, x, y),
+        ));
+        self.shown = 0;
+        self.playing = true;
+        self.last_step = now;
+    }
+
+    /// Drop the recorded search and its overlay
+    pub fn clear(&mut self) {
+        self.trace = None;
+        self.shown = 0;
+        self.playing = false;
+    }
+
+    /// Reveal expansions that are due; returns true while playback is running
+    pub fn update(&mut self, now: Instant) -> bool {
+        if !self.playing {
+            return false;
+        }
+        while now.duration_since(self.last_step) >= self.step_interval {
+            self.last_step += self.step_interval;
+            if !self.step() {
+                break;
+            }
+        }
+        self.playing
+    }
+
+    /// Reveal one more expansion; returns false (and pauses) when the search is fully shown
+    pub fn step(&mut self) -> bool {
+        let total = self.total_steps();
+        if self.shown < total {
+            self.shown += 1;
+        }
+        if self.shown >= total {
+            self.playing = false;
+            return false;
+        }
+        true
+    }
+
+    pub fn toggle_playing(&mut self, now: Instant) {
+        if self.trace.is_none() {
+            return;
+        }
+        if self.is_finished() {
+            self.shown = 0;
+        }
+        self.playing = !self.playing;
+        self.last_step = now;
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn has_trace(&self) -> bool {
+        self.trace.is_some()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.trace.is_some() && self.shown >= self.total_steps()
+    }
+
+    pub fn total_steps(&self) -> usize {
+        self.trace.as_ref().map_or(0, |trace| trace.expanded.len())
+    }
+
+    pub fn shown_steps(&self) -> usize {
+        self.shown
+    }
+
+    /// Cells expanded so far, in order (the last one is the current node)
+    pub fn expanded(&self) -> &[(usize, usize)] {
+        self.trace
+            .as_ref()
+            .map_or(&[], |trace| &trace.expanded[..self.shown])
+    }
+
+    /// The found path, only once the whole search has been revealed
+    pub fn revealed_path(&self) -> Option<&[(usize, usize)]> {
+        if !self.is_finished() {
+            return None;
+        }
+        self.trace.as_ref()?.path.as_deref()
+    }
+
+    /// How the search broke ties along its path, once the whole search has been revealed
+    pub fn path_explanation(&self) -> Option<String> {
+        if !self.is_finished() {
+            return None;
+        }
+        self.trace.as_ref().map(SearchTrace::explanation)
+    }
+
+    /// One-line description of what the search is doing at the current step
+    pub fn narration(&self) -> String {
+        let Some(trace) = &self.trace else {
+            return "Pick an algorithm and press Run to watch it search".to_string();
+        };
+        let name = trace.algorithm.name();
+        if self.shown == 0 {
+            return format!("{} starts at the agent's position", name);
+        }
+        if self.is_finished() {
+            return match &trace.path {
+                Some(path) => format!(
+                    "{} reached the goal after expanding {} cells; path is {} moves",
+                    name,
+                    trace.expanded.len(),
+                    path.len() - 1
+                ),
+                None => format!(
+                    "{} exhausted {} reachable cells without finding the goal",
+                    name,
+                    trace.expanded.len()
+                ),
+            };
+        }
+        let (x, y) = trace.expanded[self.shown - 1];
+        let how = match trace.algorithm {
+            SearchAlgorithm::Bfs => "the oldest cell in the queue",
+            SearchAlgorithm::Dijkstra => "the cheapest cell found so far",
+            SearchAlgorithm::AStar => "the cell with the lowest cost + distance estimate",
+        };
+        format!(
+            "Step {}/{}: {} expands ({}, {}), {}",
+            self.shown,
+            trace.expanded.len(),
+            name,
+            x,
+            y,
+            how
+        )
+    }
+}
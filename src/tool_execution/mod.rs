@@ -1,3 +1,5 @@
+mod schema_validation;
 mod tool_execution_manager;
 
-pub use tool_execution_manager::ToolExecutionManager;
+pub use schema_validation::validate_args;
+pub use tool_execution_manager::{PendingApproval, ToolExecutionManager};
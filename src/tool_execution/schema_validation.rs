@@ -0,0 +1,138 @@
+use serde_json::Value;
+
+/// Validate tool-call arguments against the tool's declared JSON schema
+///
+/// Covers the subset of JSON Schema our tool definitions use: `type`, `required`,
+/// `properties`, `items`, `enum`, `minimum`/`maximum` and `minItems`/`maxItems`.
+/// Unknown keywords are ignored. Errors name the offending path (e.g. `steps[0]`) so the
+/// LLM can correct its call.
+pub fn validate_args(schema: &Value, args: &Value) -> Result<(), String> {
+    // Missing/empty arguments arrive as null; treat them as an empty object
+    let empty = Value::Object(Default::default());
+    let args = if args.is_null() { &empty } else { args };
+    validate_value(schema, args, "arguments")
+}
+
+fn validate_value(schema: &Value, value: &Value, path: &str) -> Result<(), String> {
+    if let Some(expected) = schema.get("type").and_then(|t| t.as_str())
+        && !matches_type(expected, value)
+    {
+        return Err(format!(
+            "{}: expected {}, got {}",
+            path,
+            expected,
+            type_name(value)
+        ));
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(|e| e.as_array())
+        && !allowed.contains(value)
+    {
+        let options: Vec<String> = allowed.iter().map(|v| v.to_string()).collect();
+        return Err(format!(
+            "{}: {} is not one of [{}]",
+            path,
+            value,
+            options.join(", ")
+        ));
+    }
+
+    if let Some(number) = value.as_f64() {
+        if let Some(min) = schema.get("minimum").and_then(|m| m.as_f64())
+            && number < min
+        {
+            return Err(format!("{}: {} is below the minimum of {}", path, value, min));
+        }
+        if let Some(max) = schema.get("maximum").and_then(|m| m.as_f64())
+            && number > max
+        {
+            return Err(format!("{}: {} is above the maximum of {}", path, value, max));
+        }
+    }
+
+    match value {
+        Value::Object(fields) => {
+            if let Some(required) = schema.get("required").and_then(|r| r.as_array()) {
+                for name in required.iter().filter_map(|r| r.as_str()) {
+                    if !fields.contains_key(name) {
+                        return Err(format!("{}: missing required field '{}'", path, name));
+                    }
+                }
+            }
+            if let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) {
+                for (name, field) in fields {
+                    if let Some(field_schema) = properties.get(name) {
+                        validate_value(field_schema, field, &format!("{}.{}", path, name))?;
+                    }
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(min) = schema.get("minItems").and_then(|m| m.as_u64())
+                && (items.len() as u64) < min
+            {
+                return Err(format!("{}: needs at least {} items, got {}", path, min, items.len()));
+            }
+            if let Some(max) = schema.get("maxItems").and_then(|m| m.as_u64())
+                && (items.len() as u64) > max
+            {
+                return Err(format!("{}: allows at most {} items, got {}", path, max, items.len()));
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    validate_value(item_schema, item, &format!("{}[{}]", path, i))?;
+                }
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn matches_type(expected: &str, value: &Value) -> bool {
+    match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::Agent;
+    use serde_json::json;
+
+    fn move_agent_schema() -> Value {
+        let agent = Agent::new(1, "a", 0, 0);
+        let tool = agent.get_all_tools().iter().find(|tool| tool.function.name == "move_agent");
+        tool.unwrap().function.parameters.clone()
+    }
+
+    #[test]
+    fn move_agent_rejects_a_number_in_steps() {
+        let schema = move_agent_schema();
+        assert!(validate_args(&schema, &json!({"agent_id": 1, "steps": ["up", "left"]})).is_ok());
+
+        let error = validate_args(&schema, &json!({"agent_id": 1, "steps": ["up", 2]}));
+        assert_eq!(error, Err("arguments.steps[1]: expected string, got integer".to_string()));
+    }
+}
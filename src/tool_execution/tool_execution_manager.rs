@@ -1,10 +1,21 @@
-use crate::agent::{Agent, LogEntry};
-use crate::events::{Event, EventQueue, PendingToolExecution};
+use crate::agent::{Agent, Direction, ErrorKind, LogEntry, REPLAN_PREFIX};
+use crate::events::{Event, EventId, EventQueue, PendingToolExecution};
 use crate::map::GridMap;
+use crate::tool_execution::validate_args;
 use serde_json::Value;
 use std::sync::{Arc, Mutex};
 use web_time::Duration;
 
+/// Tools that change the map, which need the user's approval when approval is required
+pub const MAP_MUTATING_TOOLS: [&str; 1] = ["set_tile"];
+
+/// A map-mutating tool call held back until the user approves or rejects it
+pub struct PendingApproval {
+    pub tool_call_id: String,
+    pub name: String,
+    pub args: Value,
+}
+
 /// Manages tool execution, callbacks, and event coordination
 pub struct ToolExecutionManager {
     /// Queue for tool callbacks from async operations
@@ -15,6 +26,12 @@ pub struct ToolExecutionManager {
 
     /// Tick rate for event scheduling
     tick_rate: Duration,
+
+    /// Pause map-mutating tool calls until the user approves them
+    pub require_approval: bool,
+
+    /// Held-back tool calls, oldest first
+    awaiting_approval: Vec<PendingApproval>,
 }
 
 impl ToolExecutionManager {
@@ -23,6 +40,8 @@ impl ToolExecutionManager {
             tool_callbacks: Arc::new(Mutex::new(Vec::new())),
             pending_tool_executions: Vec::new(),
             tick_rate,
+            require_approval: false,
+            awaiting_approval: Vec::new(),
         }
     }
 
@@ -72,63 +91,205 @@ impl ToolExecutionManager {
                 let args_str = serde_json::to_string(&args).unwrap_or_default();
                 agent.add_assistant_tool_call(tool_call_id.clone(), name.clone(), args_str);
 
-                match agent.handle_tool_call(&name, args, map) {
-                    Ok(result_msg) => {
-                        // Check if tool generated any pending moves (events to submit)
-                        let moves = agent.take_pending_moves();
-
-                        if !moves.is_empty() {
-                            // Tool needs to wait for events to complete
-                            let events: Vec<Event> = moves
-                                .into_iter()
-                                .map(|direction| Event::AgentMove {
-                                    agent_id: agent.id,
-                                    direction,
-                                })
-                                .collect();
-
-                            // Submit with 2-tick delay between moves
-                            let event_ids =
-                                event_queue.submit_sequence(events, self.tick_rate * 2);
-
-                            // Create pending tool execution to track this
-                            let initial_result = if result_msg.is_empty() {
-                                String::new()
-                            } else {
-                                result_msg
-                            };
-                            let pending = PendingToolExecution::new(
-                                tool_call_id,
-                                name.clone(),
-                                initial_result,
-                                event_ids,
-                            );
-                            self.pending_tool_executions.push(pending);
-                        } else {
-                            // No events, tool completes immediately
-                            if !result_msg.is_empty() {
-                                agent.add_tool_result(tool_call_id, name.clone(), result_msg);
-                            }
-                            should_continue = true;
-                        }
-                    }
-                    Err(e) => {
-                        // Add error as tool result immediately
-                        agent.add_tool_result(
-                            tool_call_id,
-                            name.clone(),
-                            format!("Error: {}", e),
-                        );
-                        agent.log(LogEntry::Error(format!("Tool execution failed: {}", e)));
-                        should_continue = true;
-                    }
+                // Reject malformed arguments up front so the LLM gets a precise error back
+                let schema = agent
+                    .get_all_tools()
+                    .iter()
+                    .find(|tool| tool.function.name == name)
+                    .map(|tool| tool.function.parameters.clone());
+                if let Some(schema) = schema
+                    && let Err(e) = validate_args(&schema, &args)
+                {
+                    let message = format!("invalid arguments for '{}': {}", name, e);
+                    agent.log(LogEntry::ToolResult {
+                        name: name.clone(),
+                        success: false,
+                        message: message.clone(),
+                    });
+                    agent.add_tool_result(tool_call_id, name.clone(), format!("Error: {}", message));
+                    should_continue = true;
+                    continue;
                 }
+
+                if self.require_approval && MAP_MUTATING_TOOLS.contains(&name.as_str()) {
+                    agent.log_info(format!("TOOL: '{}' is waiting for your approval", name));
+                    self.awaiting_approval.push(PendingApproval {
+                        tool_call_id,
+                        name,
+                        args,
+                    });
+                    continue;
+                }
+
+                should_continue |=
+                    self.execute_tool(agent, map, event_queue, tool_call_id, name, args);
             }
         }
 
         should_continue
     }
 
+    /// Run a tool call and record its result, or queue its moves as events
+    /// Returns true if execution should continue (tool result was added)
+    fn execute_tool(
+        &mut self,
+        agent: &mut Agent,
+        map: &mut GridMap,
+        event_queue: &EventQueue,
+        tool_call_id: String,
+        name: String,
+        args: Value,
+    ) -> bool {
+        let queued = event_queue.queued_moves(agent.id);
+        match agent.handle_tool_call(&name, args, map, &queued) {
+            Ok(result_msg) => {
+                // Check if tool generated any pending moves (events to submit)
+                let moves = agent.take_pending_moves();
+                if moves.is_empty() {
+                    // No events, tool completes immediately
+                    if !result_msg.is_empty() {
+                        agent.add_tool_result(tool_call_id, name, result_msg);
+                    }
+                    return true;
+                }
+
+                // Tool needs to wait for events to complete
+                let event_ids = self.submit_moves(agent, map, event_queue, moves);
+
+                // Create pending tool execution to track this; the result is only added
+                // to history once the moves finish, even if the tool already returned a
+                // (non-empty) initial message
+                let pending = PendingToolExecution::new(
+                    tool_call_id,
+                    name,
+                    result_msg,
+                    event_ids,
+                    event_queue.now(),
+                );
+                self.pending_tool_executions.push(pending);
+                false
+            }
+            Err(e) => {
+                // Add error as tool result immediately
+                agent.add_tool_result(tool_call_id, name, format!("Error: {}", e));
+                agent.log_error(ErrorKind::Tool, format!("Tool execution failed: {}", e));
+                true
+            }
+        }
+    }
+
+    /// Queue `moves` as the agent's move events and return their ids
+    ///
+    /// Each move takes 2 ticks per cost point of the tile it enters, so slow terrain also
+    /// plays out slower.
+    fn submit_moves(
+        &self,
+        agent: &Agent,
+        map: &GridMap,
+        event_queue: &EventQueue,
+        moves: Vec<Direction>,
+    ) -> Vec<EventId> {
+        let delays: Vec<Duration> = agent
+            .step_costs(map, &moves)
+            .into_iter()
+            .map(|cost| self.tick_rate * 2 * cost)
+            .collect();
+        let events: Vec<Event> = moves
+            .into_iter()
+            .map(|direction| Event::AgentMove {
+                agent_id: agent.id,
+                direction,
+            })
+            .collect();
+        event_queue.submit_timed_sequence(events, &delays)
+    }
+
+    /// Settle a move event that failed with `REPLAN_PREFIX`, returning its final result
+    ///
+    /// If a tool execution is waiting on the `blocked` event, the agent's remaining move
+    /// events are swapped for a fresh route to its target, which the execution then waits
+    /// on too. Without a waiting tool or a route, the movement error policy applies.
+    pub fn replan_blocked_moves(
+        &mut self,
+        agent: &mut Agent,
+        map: &GridMap,
+        event_queue: &EventQueue,
+        blocked: EventId,
+        error: &str,
+    ) -> Result<(), String> {
+        let reason = error.trim_start_matches(REPLAN_PREFIX).to_string();
+        let waiting = self
+            .pending_tool_executions
+            .iter()
+            .position(|pending| pending.event_ids.contains(&blocked));
+        let remaining = event_queue.queued_moves(agent.id).len() + 1;
+        let moves = waiting.and_then(|_| agent.replan_remaining_moves(map, remaining));
+        let (Some(index), Some(moves)) = (waiting, moves) else {
+            agent.log_error(ErrorKind::Movement, reason.clone());
+            return Err(agent.blocked_step_error(reason));
+        };
+
+        event_queue.cancel_agent_events(agent.id);
+        let event_ids = self.submit_moves(agent, map, event_queue, moves);
+        self.pending_tool_executions[index].event_ids.extend(event_ids);
+        Err(format!("Replanned after: {}", reason))
+    }
+
+    /// The held-back tool call the user is asked about, if any
+    pub fn awaiting_approval(&self) -> Option<&PendingApproval> {
+        self.awaiting_approval.first()
+    }
+
+    /// Approve (run) or reject the oldest held-back tool call
+    /// Returns true if execution should continue (tool result was added)
+    pub fn resolve_approval(
+        &mut self,
+        approved: bool,
+        agent: &mut Agent,
+        map: &mut GridMap,
+        event_queue: &EventQueue,
+    ) -> bool {
+        if self.awaiting_approval.is_empty() {
+            return false;
+        }
+        let call = self.awaiting_approval.remove(0);
+        if approved {
+            agent.log_info(format!("TOOL: '{}' approved", call.name));
+            return self.execute_tool(
+                agent,
+                map,
+                event_queue,
+                call.tool_call_id,
+                call.name,
+                call.args,
+            );
+        }
+        Self::reject(agent, call, "the user rejected it");
+        true
+    }
+
+    /// Reject every held-back tool call, e.g. when the run is cancelled
+    pub fn reject_all_approvals(&mut self, agent: &mut Agent, reason: &str) {
+        for call in std::mem::take(&mut self.awaiting_approval) {
+            Self::reject(agent, call, reason);
+        }
+    }
+
+    /// Answer a held-back call with an error so the model knows the map wasn't changed
+    fn reject(agent: &mut Agent, call: PendingApproval, reason: &str) {
+        let message = format!(
+            "'{}' was not run because {}. The map was not changed; \
+            continue without this change or try a different approach.",
+            call.name, reason
+        );
+        agent.log(LogEntry::ToolResult {
+            name: call.name.clone(),
+            success: false,
+            message: message.clone(),
+        });
+        agent.add_tool_result(call.tool_call_id, call.name, format!("Error: {}", message));
+    }
+
     /// Process pending tool executions and complete those whose events are done
     /// Returns true if any executions completed and should continue
     pub fn process_pending_executions(
@@ -155,9 +316,9 @@ impl ToolExecutionManager {
             // Check logs for errors/cancellations
             let recent_logs: Vec<&LogEntry> = agent.get_logs().iter().rev().take(20).collect();
 
-            let had_errors = recent_logs.iter().any(|entry| {
-                matches!(entry, LogEntry::Error(msg) if msg.contains("Movement blocked") || msg.contains("ABORT"))
-            });
+            let had_errors = recent_logs
+                .iter()
+                .any(|entry| matches!(entry, LogEntry::Error(ErrorKind::Movement, _)));
 
             let cancelled = recent_logs.iter().any(|entry| {
                 matches!(entry, LogEntry::Info(msg) if msg.contains("events cancelled"))
@@ -183,7 +344,7 @@ impl ToolExecutionManager {
                 let log_errors: Vec<String> = recent_logs
                     .iter()
                     .filter_map(|entry| match entry {
-                        LogEntry::Error(msg) => Some(msg.clone()),
+                        LogEntry::Error(_, msg) => Some(msg.clone()),
                         LogEntry::Info(msg) if msg.contains("cancelled") => Some(msg.clone()),
                         _ => None,
                     })
@@ -193,13 +354,22 @@ impl ToolExecutionManager {
                     error_parts.push(format!("Details: {}", log_errors.join("; ")));
                 }
 
+                // Skipped steps don't stop the sequence, so only cancellation means "stopped"
+                let outcome = if cancelled { "Stopped" } else { "Finished" };
                 format!(
-                    "{} (Errors: {})",
-                    pending.initial_result,
+                    "{} at ({}, {}). Errors: {}",
+                    outcome,
+                    agent.x,
+                    agent.y,
                     error_parts.join(" | ")
                 )
             } else {
-                format!("{} (Completed successfully)", pending.initial_result)
+                format!("Completed successfully, now at ({}, {}).", agent.x, agent.y)
+            };
+            let result_msg = if pending.initial_result.is_empty() {
+                result_msg
+            } else {
+                format!("{} {}", pending.initial_result, result_msg)
             };
 
             agent.add_tool_result(pending.tool_call_id, pending.tool_name, result_msg);
@@ -209,9 +379,17 @@ impl ToolExecutionManager {
         should_continue
     }
 
-    /// Check if there are any pending tool executions
+    /// Check if any tool callbacks are waiting to be processed
+    pub fn has_pending_callbacks(&self) -> bool {
+        self.tool_callbacks
+            .lock()
+            .map(|callbacks| !callbacks.is_empty())
+            .unwrap_or(false)
+    }
+
+    /// Check if there are any pending tool executions (including ones awaiting approval)
     pub fn has_pending_executions(&self) -> bool {
-        !self.pending_tool_executions.is_empty()
+        !self.pending_tool_executions.is_empty() || !self.awaiting_approval.is_empty()
     }
 
     /// Get the number of pending tool executions
@@ -226,3 +404,78 @@ impl ToolExecutionManager {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{MockClock, SharedClock};
+    use crate::map::TileKind;
+    use serde_json::json;
+
+    #[test]
+    fn current_plan_reads_the_queued_move_events() {
+        let mut map = GridMap::new(4, 3, TileKind::Grass);
+        let mut agent = Agent::new(1, "a", 0, 0);
+        let queue = EventQueue::with_clock(Arc::new(MockClock::new()) as SharedClock);
+        let mut manager = ToolExecutionManager::new(Duration::ZERO);
+        let plan = |manager: &mut ToolExecutionManager, agent: &mut Agent, map: &mut GridMap| {
+            let (name, args) = ("get_current_plan".to_string(), json!({"agent_id": 1}));
+            assert!(manager.execute_tool(agent, map, &queue, "plan".into(), name, args));
+            let result = agent.get_chat_history().last().and_then(|m| m.content.clone());
+            serde_json::from_str::<Value>(&result.unwrap()).unwrap()
+        };
+        assert_eq!(plan(&mut manager, &mut agent, &mut map)["queued"], 0);
+
+        let steps = ["right", "right", "down"];
+        let args = json!({"agent_id": 1, "steps": steps});
+        let name = "move_agent".to_string();
+        assert!(!manager.execute_tool(&mut agent, &mut map, &queue, "move".into(), name, args));
+        let current = plan(&mut manager, &mut agent, &mut map);
+        assert_eq!(current["moves"], json!(steps));
+        assert_eq!(current["endpoint"], json!({"x": 2, "y": 1}));
+
+        let first = queue.pop_ready().unwrap();
+        let result = first.event.clone().apply(&mut agent, &mut map);
+        queue.complete(first.id, result);
+        let current = plan(&mut manager, &mut agent, &mut map);
+        assert_eq!(current["moves"], json!(["right", "down"]));
+        assert_eq!(current["from"], json!({"x": 1, "y": 0}));
+        assert_eq!(current["endpoint"], json!({"x": 2, "y": 1}));
+    }
+
+    #[test]
+    fn blocked_move_events_are_replanned_around_the_block() {
+        let mut map = GridMap::new(4, 3, TileKind::Grass);
+        let mut agent = Agent::new(1, "a", 0, 1);
+        let clock = Arc::new(MockClock::new());
+        let queue = EventQueue::with_clock(clock.clone() as SharedClock);
+        let mut manager = ToolExecutionManager::new(Duration::from_millis(10));
+        let steps = ["right", "right", "right"];
+        let args = json!({"agent_id": 1, "steps": steps, "target": {"x": 3, "y": 1}});
+        let name = "move_agent".to_string();
+        assert!(!manager.execute_tool(&mut agent, &mut map, &queue, "call".into(), name, args));
+        // A wall appears on the route after it was queued
+        map.set(2, 1, TileKind::Wall);
+
+        for _ in 0..100 {
+            while let Some(scheduled) = queue.pop_ready() {
+                let result = match scheduled.event.apply(&mut agent, &mut map) {
+                    Err(error) if error.starts_with(REPLAN_PREFIX) => manager
+                        .replan_blocked_moves(&mut agent, &map, &queue, scheduled.id, &error),
+                    result => result,
+                };
+                queue.complete(scheduled.id, result);
+            }
+            clock.advance(Duration::from_millis(100));
+        }
+
+        assert_eq!(queue.pending_count(), 0);
+        assert!(manager.process_pending_executions(&mut agent, &queue));
+        // Two moves were left when the wall blocked the second one, so the detour stops short
+        let moves = agent.get_logs().iter().filter(|e| matches!(e, LogEntry::Movement { .. }));
+        assert_eq!(moves.count(), 3);
+        assert!(matches!(agent.pos(), (2, 0) | (2, 2)));
+        let result = agent.get_chat_history().last().and_then(|m| m.content.clone());
+        assert!(result.unwrap().contains("Replanned after: Movement blocked by wall"));
+    }
+}
@@ -1,5 +1,6 @@
-use crate::agent::Agent;
+use crate::agent::{Agent, RunStats};
 use crate::animation::AnimationController;
+use crate::editor::EditorOperations;
 use crate::events::EventQueue;
 use crate::rendering::draw_log_entry;
 use crate::tool_execution::ToolExecutionManager;
@@ -16,8 +17,9 @@ impl AgentPanel {
             .stick_to_bottom(true);
 
         scroll_area.show(ui, |ui| {
-            for log_entry in agent.get_logs() {
-                draw_log_entry(ui, log_entry);
+            for (index, log_entry) in agent.get_logs().iter().enumerate() {
+                // Scope per entry so per-entry toggles (like raw JSON) get stable ids
+                ui.push_id(index, |ui| draw_log_entry(ui, log_entry));
             }
         });
     }
@@ -49,8 +51,68 @@ impl AgentPanel {
         ui.add_space(4.0);
     }
 
+    /// Running token total across turns, with a button to start counting again
+    pub fn draw_token_usage(ui: &mut egui::Ui, agent: &mut Agent) {
+        let usage = agent.token_usage();
+        if usage.turns == 0 {
+            return;
+        }
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("Tokens:").strong());
+            ui.label(egui::RichText::new(usage.summary()).color(egui::Color32::from_gray(90)));
+            if ui.small_button("Reset").on_hover_text("Start counting from zero").clicked() {
+                agent.reset_token_usage();
+            }
+        });
+    }
+
+    /// Draw stats for the current or last run
+    pub fn draw_run_stats(ui: &mut egui::Ui, stats: &RunStats) {
+        if stats.started_at.is_none() {
+            return;
+        }
+        let label = if stats.is_active() { "Run:" } else { "Last run:" };
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(label).strong());
+            ui.label(
+                egui::RichText::new(stats.summary())
+                    .color(egui::Color32::from_gray(90)),
+            );
+        });
+        if let Some(route) = &stats.route {
+            ui.horizontal(|ui| {
+                let color = match route.efficiency() {
+                    Some(e) if e >= 0.999 => egui::Color32::from_rgb(50, 150, 50),
+                    Some(e) if e >= 0.7 => egui::Color32::from_rgb(200, 140, 0),
+                    Some(_) => egui::Color32::from_rgb(200, 60, 60),
+                    None => egui::Color32::from_gray(120),
+                };
+                ui.label(egui::RichText::new(route.summary()).color(color));
+                ui.label(
+                    egui::RichText::new(route.cost_summary()).color(egui::Color32::from_gray(90)),
+                );
+                if ui
+                    .small_button("Copy path")
+                    .on_hover_text("Copy the walked path as JSON [[x, y], ...]")
+                    .clicked()
+                {
+                    let path: Vec<[usize; 2]> = stats.path.iter().map(|&(x, y)| [x, y]).collect();
+                    EditorOperations::copy_to_clipboard(
+                        &serde_json::to_string(&path).unwrap_or_default(),
+                    );
+                }
+            });
+        }
+        ui.add_space(4.0);
+    }
+
     /// Draw LLM thinking status indicator
-    pub fn draw_thinking_status(ui: &mut egui::Ui, animation_controller: &AnimationController) {
+    /// Animated "Thinking..." indicator, followed by the response text streamed so far
+    pub fn draw_thinking_status(
+        ui: &mut egui::Ui,
+        animation_controller: &AnimationController,
+        streaming: &str,
+    ) {
         ui.horizontal(|ui| {
             ui.label(egui::RichText::new("AI").size(16.0).strong());
             ui.label(
@@ -59,6 +121,24 @@ impl AgentPanel {
                     .strong(),
             );
         });
+        if !streaming.is_empty() {
+            egui::Frame::none()
+                .fill(egui::Color32::from_rgb(245, 240, 255))
+                .rounding(4.0)
+                .inner_margin(6.0)
+                .show(ui, |ui| {
+                    egui::ScrollArea::vertical()
+                        .id_source("streaming_thinking")
+                        .max_height(120.0)
+                        .stick_to_bottom(true)
+                        .show(ui, |ui| {
+                            ui.label(
+                                egui::RichText::new(streaming)
+                                    .color(egui::Color32::from_gray(60)),
+                            );
+                        });
+                });
+        }
         ui.add_space(4.0);
     }
 }
@@ -0,0 +1,142 @@
+use eframe::egui;
+
+/// One palette entry: a label to filter on and the action it runs on `T`
+pub struct Command<T> {
+    pub label: String,
+    /// Disabled commands are listed greyed out and can't be run
+    pub enabled: bool,
+    pub run: Box<dyn FnOnce(&mut T)>,
+}
+
+impl<T> Command<T> {
+    pub fn new(label: impl Into<String>, run: impl FnOnce(&mut T) + 'static) -> Self {
+        Self {
+            label: label.into(),
+            enabled: true,
+            run: Box::new(run),
+        }
+    }
+
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+}
+
+/// Ctrl+P popup that filters a list of commands as you type
+///
+/// The palette holds the keyboard while open; Enter runs the highlighted command, and
+/// Escape or running a command closes it and gives focus back.
+pub struct CommandPalette {
+    open: bool,
+    query: String,
+    selected: usize,
+}
+
+impl CommandPalette {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            query: String::new(),
+            selected: 0,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Open with an empty query
+    pub fn open(&mut self) {
+        self.open = true;
+        self.query.clear();
+        self.selected = 0;
+    }
+
+    /// Whether `label` contains every word of the query, ignoring case
+    fn matches(&self, label: &str) -> bool {
+        let label = label.to_lowercase();
+        self.query
+            .to_lowercase()
+            .split_whitespace()
+            .all(|word| label.contains(word))
+    }
+
+    /// Draw the palette if open; returns the index in `commands` of the one to run
+    pub fn draw<T>(&mut self, ctx: &egui::Context, commands: &[Command<T>]) -> Option<usize> {
+        if !self.open {
+            return None;
+        }
+        // Consumed here so Escape closes the palette without also cancelling a run
+        let (escape, enter, up, down) = ctx.input_mut(|i| {
+            (
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+            )
+        });
+        let matching: Vec<usize> = (0..commands.len())
+            .filter(|&index| self.matches(&commands[index].label))
+            .collect();
+        if down {
+            self.selected += 1;
+        }
+        if up {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        self.selected = self.selected.min(matching.len().saturating_sub(1));
+
+        let mut chosen = None;
+        egui::Window::new("Command Palette")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .fixed_size([360.0, 0.0])
+            .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 48.0))
+            .show(ctx, |ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.query)
+                        .hint_text("Type a command...")
+                        .desired_width(f32::INFINITY),
+                );
+                if response.changed() {
+                    self.selected = 0;
+                }
+                response.request_focus();
+
+                egui::ScrollArea::vertical().max_height(280.0).show(ui, |ui| {
+                    if matching.is_empty() {
+                        ui.weak("No matching commands");
+                    }
+                    for (row, &index) in matching.iter().enumerate() {
+                        let command = &commands[index];
+                        let item = ui.add_enabled(
+                            command.enabled,
+                            egui::SelectableLabel::new(row == self.selected, &command.label),
+                        );
+                        if row == self.selected && (up || down) {
+                            item.scroll_to_me(None);
+                        }
+                        if item.clicked() {
+                            chosen = Some(index);
+                        }
+                    }
+                });
+                if enter
+                    && let Some(&index) = matching.get(self.selected)
+                    && commands[index].enabled
+                {
+                    chosen = Some(index);
+                }
+                if chosen.is_some() || escape {
+                    response.surrender_focus();
+                }
+            });
+
+        if chosen.is_some() || escape {
+            self.open = false;
+        }
+        chosen
+    }
+}
@@ -0,0 +1,80 @@
+use eframe::egui;
+
+/// Compass showing the bearing and distance from the agent to the goal
+pub struct Compass;
+
+impl Compass {
+    /// Bearing in degrees from `from` to `to`: 0 = north (up), 90 = east, clockwise
+    pub fn bearing_degrees(from: (usize, usize), to: (usize, usize)) -> f32 {
+        let dx = to.0 as f32 - from.0 as f32;
+        let dy = to.1 as f32 - from.1 as f32;
+        // Screen y grows downward, so north is -y
+        let degrees = dx.atan2(-dy).to_degrees();
+        (degrees + 360.0) % 360.0
+    }
+
+    pub fn draw(ui: &mut egui::Ui, from: (usize, usize), to: (usize, usize)) {
+        let size = 36.0;
+        ui.horizontal(|ui| {
+            let (rect, _) = ui.allocate_exact_size(egui::vec2(size, size), egui::Sense::hover());
+            let painter = ui.painter();
+            let center = rect.center();
+            let radius = size * 0.45;
+
+            painter.circle_filled(center, radius, egui::Color32::from_rgb(245, 245, 250));
+            painter.circle_stroke(
+                center,
+                radius,
+                egui::Stroke::new(1.0, egui::Color32::from_gray(150)),
+            );
+            painter.text(
+                egui::pos2(center.x, rect.top() + 2.0),
+                egui::Align2::CENTER_TOP,
+                "N",
+                egui::FontId::proportional(8.0),
+                egui::Color32::from_gray(120),
+            );
+
+            let manhattan = from.0.abs_diff(to.0) + from.1.abs_diff(to.1);
+            if manhattan == 0 {
+                painter.circle_filled(center, 3.0, egui::Color32::from_rgb(50, 150, 50));
+                ui.label(
+                    egui::RichText::new("At goal")
+                        .strong()
+                        .color(egui::Color32::from_rgb(50, 150, 50)),
+                );
+                return;
+            }
+
+            let bearing = Self::bearing_degrees(from, to);
+            let angle = bearing.to_radians();
+            let dir = egui::vec2(angle.sin(), -angle.cos());
+            let tip = center + dir * radius * 0.85;
+            painter.arrow(
+                center - dir * radius * 0.5,
+                tip - (center - dir * radius * 0.5),
+                egui::Stroke::new(2.0, egui::Color32::from_rgb(200, 60, 60)),
+            );
+
+            let dx = to.0 as f32 - from.0 as f32;
+            let dy = to.1 as f32 - from.1 as f32;
+            ui.vertical(|ui| {
+                ui.label(
+                    egui::RichText::new(format!("Goal ({}, {})", to.0, to.1))
+                        .strong()
+                        .color(egui::Color32::from_rgb(50, 80, 120)),
+                );
+                ui.label(
+                    egui::RichText::new(format!(
+                        "{:.0}° · {} steps (straight line {:.1})",
+                        bearing,
+                        manhattan,
+                        (dx * dx + dy * dy).sqrt()
+                    ))
+                    .small()
+                    .color(egui::Color32::from_gray(100)),
+                );
+            });
+        });
+    }
+}
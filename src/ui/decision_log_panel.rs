@@ -0,0 +1,82 @@
+use crate::agent::Agent;
+use crate::editor::EditorOperations;
+use eframe::egui;
+
+/// Recorded LLM turns with their full request and response, for prompt debugging
+pub struct DecisionLogPanel;
+
+impl DecisionLogPanel {
+    pub fn draw(ui: &mut egui::Ui, agent: &mut Agent) {
+        let records = agent.decision_log();
+        egui::CollapsingHeader::new(format!("Decision Log ({})", records.len()))
+            .id_source("decision_log")
+            .default_open(false)
+            .show(ui, |ui| {
+                let has_records = !records.is_empty();
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(has_records, egui::Button::new("Copy JSON").small())
+                        .on_hover_text("Copy every recorded turn as a JSON array")
+                        .clicked()
+                    {
+                        EditorOperations::copy_to_clipboard(&agent.decision_log_json());
+                        agent.log_info("Decision log JSON copied to clipboard!");
+                    }
+                    if ui
+                        .add_enabled(has_records, egui::Button::new("Clear").small())
+                        .clicked()
+                    {
+                        agent.clear_decision_log();
+                    }
+                });
+                if !has_records {
+                    ui.label(
+                        egui::RichText::new("Turns appear here once the agent calls the LLM")
+                            .small()
+                            .color(egui::Color32::from_gray(120)),
+                    );
+                }
+
+                // Newest first
+                for record in records.iter().rev() {
+                    let outcome = match (&record.tool_name, record.finished) {
+                        (_, false) => "streaming...".to_string(),
+                        (Some(name), true) => format!("called {}", name),
+                        (None, true) => "no tool call".to_string(),
+                    };
+                    egui::CollapsingHeader::new(format!("Turn {}: {}", record.turn, outcome))
+                        .id_source(("decision_record", record.turn))
+                        .show(ui, |ui| {
+                            let request = serde_json::to_string_pretty(&record.request)
+                                .unwrap_or_default();
+                            Self::section(ui, "Request", &request);
+                            Self::section(ui, "Raw output", &record.raw_output);
+                            if record.tool_name.is_some() {
+                                Self::section(ui, "Raw arguments", &record.raw_arguments);
+                            }
+                            if let Some(parsed) = &record.parsed_arguments {
+                                let parsed =
+                                    serde_json::to_string_pretty(parsed).unwrap_or_default();
+                                Self::section(ui, "Parsed arguments", &parsed);
+                            }
+                            for error in &record.errors {
+                                ui.colored_label(egui::Color32::from_rgb(200, 60, 60), error);
+                            }
+                        });
+                }
+            });
+    }
+
+    /// Collapsible monospace block, since requests run to thousands of lines
+    fn section(ui: &mut egui::Ui, title: &str, text: &str) {
+        egui::CollapsingHeader::new(title).show(ui, |ui| {
+            if text.is_empty() {
+                ui.weak("(empty)");
+                return;
+            }
+            egui::ScrollArea::vertical().max_height(240.0).show(ui, |ui| {
+                ui.label(egui::RichText::new(text).font(egui::FontId::monospace(11.0)));
+            });
+        });
+    }
+}
@@ -0,0 +1,98 @@
+use crate::events::{Event, EventQueue, EventStatus, ScheduledEvent};
+use eframe::egui;
+use web_time::Instant;
+
+/// Number of completed results shown under the queue
+const RECENT_RESULTS: usize = 10;
+
+/// Debug view of the event queue: what is scheduled, when it runs, and how it ended
+pub struct EventInspector;
+
+impl EventInspector {
+    pub fn draw(ui: &mut egui::Ui, event_queue: &EventQueue) {
+        let events = event_queue.snapshot();
+
+        egui::CollapsingHeader::new(format!("Event Queue ({})", events.len()))
+            .id_source("event_inspector")
+            .default_open(false)
+            .show(ui, |ui| {
+                if events.is_empty() {
+                    ui.label(
+                        egui::RichText::new("No queued events")
+                            .small()
+                            .color(egui::Color32::from_gray(120)),
+                    );
+                } else {
+                    let now = event_queue.now();
+                    egui::Grid::new("event_queue_grid")
+                        .striped(true)
+                        .num_columns(4)
+                        .show(ui, |ui| {
+                            ui.label(egui::RichText::new("id").small().strong());
+                            ui.label(egui::RichText::new("event").small().strong());
+                            ui.label(egui::RichText::new("status").small().strong());
+                            ui.label(egui::RichText::new("runs in").small().strong());
+                            ui.end_row();
+
+                            for scheduled in &events {
+                                Self::draw_event_row(ui, scheduled, now);
+                            }
+                        });
+                }
+
+                let recent = event_queue.recent_completed(RECENT_RESULTS);
+                if !recent.is_empty() {
+                    ui.add_space(4.0);
+                    ui.label(egui::RichText::new("Recent results").small().strong());
+                    for (id, result) in recent {
+                        let (text, color) = match result {
+                            Ok(()) => ("ok".to_string(), egui::Color32::from_rgb(50, 150, 50)),
+                            Err(e) => (e, egui::Color32::from_rgb(200, 50, 50)),
+                        };
+                        ui.label(
+                            egui::RichText::new(format!("#{} {}", id.value(), text))
+                                .small()
+                                .color(color),
+                        );
+                    }
+                }
+            });
+    }
+
+    fn draw_event_row(ui: &mut egui::Ui, scheduled: &ScheduledEvent, now: Instant) {
+        let kind = match &scheduled.event {
+            Event::AgentMove {
+                agent_id,
+                direction,
+            } => format!("move {} (agent {})", direction.as_str(), agent_id),
+            Event::Delay { ticks } => format!("delay {} ticks", ticks),
+            Event::Custom(custom) => match custom.agent_id {
+                Some(agent_id) => format!("{} (agent {})", custom.name, agent_id),
+                None => custom.name.clone(),
+            },
+        };
+        let (status, color) = match &scheduled.status {
+            EventStatus::Pending => ("pending".to_string(), egui::Color32::from_gray(90)),
+            EventStatus::Processing => {
+                ("processing".to_string(), egui::Color32::from_rgb(200, 100, 0))
+            }
+            EventStatus::Completed => {
+                ("completed".to_string(), egui::Color32::from_rgb(50, 150, 50))
+            }
+            EventStatus::Failed(e) => {
+                (format!("failed: {}", e), egui::Color32::from_rgb(200, 50, 50))
+            }
+        };
+        let runs_in = if scheduled.execute_at > now {
+            format!("{:.1}s", (scheduled.execute_at - now).as_secs_f32())
+        } else {
+            "ready".to_string()
+        };
+
+        ui.label(egui::RichText::new(format!("#{}", scheduled.id.value())).small());
+        ui.label(egui::RichText::new(kind).small());
+        ui.label(egui::RichText::new(status).small().color(color));
+        ui.label(egui::RichText::new(runs_in).small());
+        ui.end_row();
+    }
+}
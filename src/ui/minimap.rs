@@ -0,0 +1,71 @@
+use crate::agent::Agent;
+use crate::board::{BoardRenderer, Viewport};
+use crate::map::GridMap;
+use crate::rendering::{tile_base_color, TilePalette};
+use eframe::egui;
+
+/// Scaled-down overview of the whole map with the visible part of the board outlined
+///
+/// Tiles are flat colored squares so it stays cheap enough to draw every frame.
+/// Clicking or dragging on it pans the main view.
+pub struct Minimap;
+
+impl Minimap {
+    /// Returns true when the user panned the view this frame
+    pub fn draw(
+        ui: &mut egui::Ui,
+        map: &GridMap,
+        agent: &Agent,
+        viewport: &mut Viewport,
+        side: f32,
+        palette: TilePalette,
+    ) -> bool {
+        let (rect, response) =
+            ui.allocate_exact_size(egui::vec2(side, side), egui::Sense::click_and_drag());
+        let painter = ui.painter_at(rect);
+        // Same aspect-fitted layout as the board, so fractions line up with the viewport
+        let board = BoardRenderer::fit_map_rect(rect, map);
+        let cell = board.height() / map.height().max(1) as f32;
+
+        painter.rect_filled(rect, 2.0, egui::Color32::from_gray(225));
+        for y in 0..map.height() {
+            for x in 0..map.width() {
+                if let Some(kind) = map.get(x, y) {
+                    painter.rect_filled(
+                        BoardRenderer::cell_rect(board, cell, map, x, y),
+                        0.0,
+                        tile_base_color(kind, palette),
+                    );
+                }
+            }
+        }
+
+        let (w, h) = agent.size();
+        let agent_rect = egui::Rect::from_min_size(
+            BoardRenderer::cell_rect(board, cell, map, agent.x, agent.y).min,
+            egui::vec2(cell * w as f32, cell * h as f32),
+        );
+        painter.circle_filled(agent_rect.center(), (cell * 0.6).max(2.0), agent.color);
+
+        let visible = viewport.visible_fraction();
+        let view_rect = egui::Rect::from_min_max(
+            board.min + visible.min.to_vec2() * board.size(),
+            board.min + visible.max.to_vec2() * board.size(),
+        );
+        painter.rect_stroke(
+            view_rect,
+            0.0,
+            egui::Stroke::new(1.5, egui::Color32::from_rgb(255, 140, 0)),
+        );
+        painter.rect_stroke(rect, 2.0, egui::Stroke::new(1.0, egui::Color32::from_gray(150)));
+
+        if (response.clicked() || response.dragged())
+            && let Some(pos) = response.interact_pointer_pos()
+        {
+            let fraction = (pos - board.min) / board.size();
+            viewport.center_on(egui::pos2(fraction.x, fraction.y));
+            return true;
+        }
+        false
+    }
+}
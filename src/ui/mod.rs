@@ -1,5 +1,37 @@
 mod agent_panel;
+mod command_palette;
+mod compass;
+mod decision_log_panel;
+mod event_inspector;
+mod minimap;
+mod model_picker;
+mod patrol_panel;
+mod replay_panel;
+mod run_history_panel;
+mod settings_window;
+mod shortcut_help;
+mod stress_test_panel;
+mod teach_panel;
+mod template_panel;
 mod tile_info_panel;
+mod tile_legend;
+mod tool_approval_card;
 
 pub use agent_panel::AgentPanel;
+pub use command_palette::{Command, CommandPalette};
+pub use compass::Compass;
+pub use decision_log_panel::DecisionLogPanel;
+pub use event_inspector::EventInspector;
+pub use minimap::Minimap;
+pub use model_picker::ModelPicker;
+pub use patrol_panel::PatrolPanel;
+pub use replay_panel::ReplayPanel;
+pub use run_history_panel::RunHistoryPanel;
+pub use settings_window::SettingsWindow;
+pub use shortcut_help::ShortcutHelp;
+pub use stress_test_panel::StressTestPanel;
+pub use teach_panel::TeachPanel;
+pub use template_panel::TemplatePanel;
 pub use tile_info_panel::TileInfoPanel;
+pub use tile_legend::TileLegend;
+pub use tool_approval_card::ToolApprovalCard;
@@ -0,0 +1,135 @@
+use crate::model_presets::MODEL_PRESETS;
+use crate::openrouter::{ModelInfo, fetch_models};
+use eframe::egui;
+use std::sync::{Arc, Mutex};
+
+/// Result of the one-off model list fetch (None while not yet loaded)
+type FetchedModels = Option<Result<Vec<ModelInfo>, String>>;
+
+/// Searchable model selector backed by OpenRouter's `/models` list
+///
+/// The list is fetched once and cached. Without an API key, or if the fetch fails,
+/// the picker falls back to the built-in presets.
+pub struct ModelPicker {
+    search: String,
+    fetch_started: bool,
+    fetched: Arc<Mutex<FetchedModels>>,
+    /// Whether the dropdown was open last frame, to notice it being reopened
+    was_open: bool,
+}
+
+impl ModelPicker {
+    pub fn new() -> Self {
+        Self {
+            search: String::new(),
+            fetch_started: false,
+            fetched: Arc::new(Mutex::new(None)),
+            was_open: false,
+        }
+    }
+
+    /// Start fetching the model list if it hasn't been loaded yet
+    /// A failed fetch keeps its error until the picker is opened again (`just_opened`)
+    fn ensure_fetch(&mut self, ctx: &egui::Context, api_key: &str, just_opened: bool) {
+        if just_opened && matches!(*self.fetched.lock().unwrap(), Some(Err(_))) {
+            self.fetch_started = false;
+        }
+        if self.fetch_started || api_key.is_empty() {
+            return;
+        }
+        self.fetch_started = true;
+        *self.fetched.lock().unwrap() = None;
+
+        let api_key = api_key.to_string();
+        let fetched = self.fetched.clone();
+        let ctx = ctx.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            let result = fetch_models(api_key).await.map_err(|e| e.to_string());
+            if let Ok(mut g) = fetched.lock() {
+                *g = Some(result);
+            }
+            ctx.request_repaint();
+        });
+    }
+
+    /// Models to offer as (id, label): fetched list if available, otherwise the presets
+    fn models(&self) -> (Vec<(String, String)>, Option<String>) {
+        let fetched = self.fetched.lock().unwrap();
+        match fetched.as_ref() {
+            Some(Ok(models)) if !models.is_empty() => (
+                models
+                    .iter()
+                    .map(|m| {
+                        let label = if m.name.is_empty() { m.id.clone() } else { m.name.clone() };
+                        (m.id.clone(), label)
+                    })
+                    .collect(),
+                None,
+            ),
+            other => {
+                let status = match other {
+                    Some(Err(e)) => Some(format!("Couldn't load models ({}), showing defaults", e)),
+                    None if self.fetch_started => Some("Loading models...".to_string()),
+                    _ => Some("Set an API key to load all models".to_string()),
+                };
+                (
+                    MODEL_PRESETS
+                        .iter()
+                        .map(|p| (p.id.to_string(), p.label.to_string()))
+                        .collect(),
+                    status,
+                )
+            }
+        }
+    }
+
+    /// Draw the picker. Returns true if the selection changed.
+    pub fn draw(&mut self, ui: &mut egui::Ui, selected_model: &mut String, api_key: &str) -> bool {
+        let (models, status) = self.models();
+        let previous = selected_model.clone();
+
+        let mut open = false;
+        egui::ComboBox::from_id_source("model_selector")
+            .selected_text(selected_model.as_str())
+            .width(ui.available_width().min(260.0))
+            .show_ui(ui, |ui| {
+                // Only hit the network once the user actually opens the picker
+                open = true;
+                self.ensure_fetch(ui.ctx(), api_key, !self.was_open);
+
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.search)
+                        .hint_text("Search models...")
+                        .desired_width(f32::INFINITY),
+                );
+                ui.separator();
+
+                let query = self.search.to_lowercase();
+                for (id, label) in models.iter().filter(|(id, label)| {
+                    query.is_empty()
+                        || id.to_lowercase().contains(&query)
+                        || label.to_lowercase().contains(&query)
+                }) {
+                    ui.selectable_value(selected_model, id.clone(), label.as_str())
+                        .on_hover_text(id.as_str());
+                }
+            });
+        self.was_open = open;
+
+        if let Some(status) = status {
+            ui.label(
+                egui::RichText::new(status)
+                    .small()
+                    .color(egui::Color32::from_gray(120)),
+            );
+        }
+
+        *selected_model != previous
+    }
+}
+
+impl Default for ModelPicker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,81 @@
+use crate::agent::Agent;
+use crate::map::GridMap;
+use crate::patrol::Patroller;
+use eframe::egui;
+
+/// Controls for scripted patrollers: record a route by clicking the board, then add it
+pub struct PatrolPanel;
+
+impl PatrolPanel {
+    /// Returns true when the patroller list changed
+    pub fn draw(
+        ui: &mut egui::Ui,
+        patrollers: &mut Vec<Patroller>,
+        recording: &mut Option<Vec<(usize, usize)>>,
+        map: &GridMap,
+        agent: &mut Agent,
+    ) -> bool {
+        let mut changed = false;
+        egui::CollapsingHeader::new("Patrollers")
+            .id_source("patrollers")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.label(
+                    egui::RichText::new(
+                        "Moving obstacles that walk a route back and forth, one step per tick",
+                    )
+                    .small()
+                    .color(egui::Color32::from_gray(120)),
+                );
+                match recording {
+                    None => {
+                        ui.horizontal(|ui| {
+                            if ui.button("Record route").clicked() {
+                                *recording = Some(Vec::new());
+                            }
+                            if ui
+                                .add_enabled(!patrollers.is_empty(), egui::Button::new("Clear all"))
+                                .clicked()
+                            {
+                                patrollers.clear();
+                                changed = true;
+                            }
+                        });
+                    }
+                    Some(waypoints) => {
+                        ui.label(format!(
+                            "Click the board to add waypoints ({} so far)",
+                            waypoints.len()
+                        ));
+                        let (mut add, mut cancel) = (false, false);
+                        ui.horizontal(|ui| {
+                            add = ui
+                                .add_enabled(waypoints.len() >= 2, egui::Button::new("Add patroller"))
+                                .clicked();
+                            cancel = ui.button("Cancel").clicked();
+                        });
+                        if add {
+                            // Routes only avoid terrain; other movers are ignored
+                            let terrain = |x, y| agent.traversability().is_cell_passable(map, x, y);
+                            match Patroller::through_waypoints(map, waypoints, terrain) {
+                                Some(patroller) => {
+                                    patrollers.push(patroller);
+                                    changed = true;
+                                }
+                                None => agent.log_info(
+                                    "WARN: Patrol route not added: a waypoint is unreachable",
+                                ),
+                            }
+                        }
+                        if add || cancel {
+                            *recording = None;
+                        }
+                    }
+                }
+                if !patrollers.is_empty() {
+                    ui.label(egui::RichText::new(format!("{} active", patrollers.len())).small());
+                }
+            });
+        changed
+    }
+}
@@ -0,0 +1,83 @@
+use crate::agent::Agent;
+use crate::map::GridMap;
+use crate::replay::Replay;
+use eframe::egui;
+
+/// Record a session, then drag through it to see the board at any step
+pub struct ReplayPanel;
+
+impl ReplayPanel {
+    pub fn draw(ui: &mut egui::Ui, replay: &mut Replay, map: &GridMap, agent: &Agent) {
+        egui::CollapsingHeader::new("Replay")
+            .id_source("replay")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    if replay.is_recording() {
+                        if ui.button("Stop recording").clicked() {
+                            replay.stop();
+                        }
+                    } else if ui
+                        .button("Record")
+                        .on_hover_text("Snapshot the board now and record every event from here")
+                        .clicked()
+                    {
+                        replay.start(map, agent);
+                    }
+                    if let Some(recording) = replay.recording() {
+                        ui.label(format!(
+                            "{} events over {} ticks",
+                            recording.len(),
+                            recording.ticks()
+                        ));
+                    }
+                });
+
+                let Some(recording) = replay.recording().filter(|r| !r.is_empty()) else {
+                    return;
+                };
+                let total = recording.len();
+                let mut step = replay.view().map_or(total, |view| view.step);
+                let label = format!(
+                    "tick {}: {}",
+                    recording.tick_at(step),
+                    recording.describe(step)
+                );
+                let scrubbed = ui
+                    .add(egui::Slider::new(&mut step, 0..=total).text("step"))
+                    .on_hover_text("Drag to rebuild the board as it was after this many events")
+                    .changed();
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new(label).small());
+                    if ui
+                        .add_enabled(replay.view().is_some(), egui::Button::new("Live").small())
+                        .on_hover_text("Stop viewing the recording and show the current board")
+                        .clicked()
+                    {
+                        replay.stop_scrubbing();
+                    }
+                });
+                if scrubbed {
+                    replay.scrub_to(step);
+                }
+                if let Some(view) = replay.view() {
+                    ui.label(
+                        egui::RichText::new("The board shows the recording, not the live state")
+                            .small()
+                            .color(egui::Color32::from_rgb(180, 100, 20)),
+                    );
+                    if let Some(diverged) = view.diverged_at {
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "The map was changed outside the recording before step {}; \
+                                 later steps may not match what happened",
+                                diverged
+                            ))
+                            .small()
+                            .color(egui::Color32::from_rgb(200, 60, 60)),
+                        );
+                    }
+                }
+            });
+    }
+}
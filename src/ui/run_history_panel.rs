@@ -0,0 +1,74 @@
+use crate::agent::Agent;
+use crate::editor::EditorOperations;
+use eframe::egui;
+
+/// Finished runs side by side, so attempts at the same instruction can be compared
+pub struct RunHistoryPanel;
+
+impl RunHistoryPanel {
+    pub fn draw(ui: &mut egui::Ui, agent: &mut Agent) {
+        egui::CollapsingHeader::new(format!("Run History ({})", agent.run_history().len()))
+            .id_source("run_history")
+            .default_open(false)
+            .show(ui, |ui| {
+                let has_runs = !agent.run_history().is_empty();
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(has_runs, egui::Button::new("Copy JSON").small())
+                        .on_hover_text("Copy every run as a JSON array")
+                        .clicked()
+                    {
+                        EditorOperations::copy_to_clipboard(&agent.run_history_json());
+                        agent.log_info("Run history JSON copied to clipboard!");
+                    }
+                    if ui
+                        .add_enabled(has_runs, egui::Button::new("Clear").small())
+                        .clicked()
+                    {
+                        agent.clear_run_history();
+                    }
+                });
+                if !has_runs {
+                    ui.label(
+                        egui::RichText::new("Runs appear here once they finish")
+                            .small()
+                            .color(egui::Color32::from_gray(120)),
+                    );
+                }
+
+                // Newest first
+                for (index, run) in agent.run_history().iter().enumerate().rev() {
+                    ui.separator();
+                    let (icon, color) = if run.success {
+                        ("✔", egui::Color32::from_rgb(50, 150, 50))
+                    } else {
+                        ("✖", egui::Color32::from_rgb(200, 60, 60))
+                    };
+                    ui.horizontal(|ui| {
+                        ui.label(egui::RichText::new(icon).color(color));
+                        ui.label(egui::RichText::new(format!("#{}", index + 1)).strong());
+                        ui.label(&run.instruction);
+                    });
+                    let optimal = run
+                        .optimal_moves
+                        .map_or(String::new(), |optimal| format!(" ({} optimal)", optimal));
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "{:.1}s, {} moves{}, {} tool calls, {} LLM turns, {} errors, \
+                            ended at ({}, {})",
+                            run.duration_secs,
+                            run.moves,
+                            optimal,
+                            run.tool_calls,
+                            run.llm_turns,
+                            run.errors,
+                            run.final_position.0,
+                            run.final_position.1
+                        ))
+                        .small()
+                        .color(egui::Color32::from_gray(90)),
+                    );
+                }
+            });
+    }
+}
@@ -0,0 +1,128 @@
+use crate::animation::IdleRepaint;
+use crate::board::OutOfBoundsStyle;
+use crate::keyboard::MovementKeyScheme;
+use crate::map::Topology;
+use crate::rendering::TilePalette;
+use crate::settings::Settings;
+use eframe::egui;
+use web_time::Duration;
+
+/// Sections of the Settings window that edit `Settings`
+///
+/// The window itself lives on `MyApp`, which adds the per-agent sections between these.
+pub struct SettingsWindow;
+
+impl SettingsWindow {
+    /// Board appearance: grid lines, the trail, tile colors, grid shape, the off-map area
+    /// and idle animation
+    pub fn draw_display(ui: &mut egui::Ui, settings: &mut Settings) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut settings.grid_lines.visible, "Grid Lines");
+            ui.add_enabled_ui(settings.grid_lines.visible, |ui| {
+                ui.color_edit_button_srgba(&mut settings.grid_lines.color);
+                ui.add(
+                    egui::Slider::new(&mut settings.grid_lines.width, 0.5..=4.0)
+                        .text("thickness"),
+                );
+            });
+        });
+        ui.checkbox(&mut settings.show_trail, "Trail")
+            .on_hover_text("Draw the agent's recent positions in yellow (trail tiles are separate)");
+        egui::Grid::new("display_settings").num_columns(2).show(ui, |ui| {
+            ui.label("Tiles:");
+            egui::ComboBox::from_id_source("tile_palette")
+                .selected_text(settings.tile_palette.label())
+                .show_ui(ui, |ui| {
+                    for palette in TilePalette::ALL {
+                        ui.selectable_value(&mut settings.tile_palette, palette, palette.label());
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Grid shape:")
+                .on_hover_text("Hex grids offset odd rows and give each cell six neighbors");
+            egui::ComboBox::from_id_source("topology")
+                .selected_text(settings.topology.label())
+                .show_ui(ui, |ui| {
+                    for topology in Topology::ALL {
+                        ui.selectable_value(&mut settings.topology, topology, topology.label());
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Off-map:")
+                .on_hover_text("How the board area around the map's edges is drawn");
+            egui::ComboBox::from_id_source("out_of_bounds_style")
+                .selected_text(settings.out_of_bounds.label())
+                .show_ui(ui, |ui| {
+                    for style in OutOfBoundsStyle::ALL {
+                        ui.selectable_value(&mut settings.out_of_bounds, style, style.label());
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Idle animation:")
+                .on_hover_text("How often water animates while nothing else is happening");
+            egui::ComboBox::from_id_source("idle_repaint")
+                .selected_text(settings.idle_repaint.label())
+                .show_ui(ui, |ui| {
+                    for rate in IdleRepaint::ALL {
+                        ui.selectable_value(&mut settings.idle_repaint, rate, rate.label());
+                    }
+                });
+            ui.end_row();
+        });
+    }
+
+    /// Keyboard controls
+    pub fn draw_controls(ui: &mut egui::Ui, settings: &mut Settings) {
+        ui.horizontal(|ui| {
+            ui.label("Move keys:");
+            egui::ComboBox::from_id_source("movement_keys")
+                .selected_text(settings.movement_keys.label())
+                .show_ui(ui, |ui| {
+                    for scheme in MovementKeyScheme::all() {
+                        ui.selectable_value(&mut settings.movement_keys, scheme, scheme.label());
+                    }
+                });
+        });
+    }
+
+    /// LLM stream timeout
+    pub fn draw_llm_timeout(ui: &mut egui::Ui, settings: &mut Settings) {
+        ui.label("LLM Timeout");
+        let mut secs = settings.llm_timeout.as_secs();
+        if ui
+            .add(egui::Slider::new(&mut secs, 10..=300).text("seconds").logarithmic(true))
+            .on_hover_text("Abandon the LLM turn if the response stream is silent this long")
+            .changed()
+        {
+            settings.llm_timeout = Duration::from_secs(secs);
+        }
+    }
+
+    /// Retries of LLM requests that fail transiently
+    pub fn draw_llm_retries(ui: &mut egui::Ui, settings: &mut Settings) {
+        ui.add(egui::Slider::new(&mut settings.llm_retries, 0..=5).text("retries"))
+            .on_hover_text(
+                "Resend a request that hits a network error, rate limit (429) or server \
+                error (5xx), waiting 0.5s, 1s, 2s... in between. Auth errors fail at once",
+            );
+    }
+
+    /// Streamed vs single-response LLM requests
+    pub fn draw_streaming(ui: &mut egui::Ui, settings: &mut Settings) {
+        ui.checkbox(&mut settings.streaming, "Stream responses").on_hover_text(
+            "Turn off for models or providers whose streamed replies come back garbled; \
+            each turn then waits for the complete response",
+        );
+    }
+
+    /// Opt-in recording of each LLM turn's full I/O
+    pub fn draw_decision_log(ui: &mut egui::Ui, settings: &mut Settings) {
+        ui.checkbox(&mut settings.decision_log, "Decision Log").on_hover_text(
+            "Record every request sent, the raw model output and the parsed tool call \
+            (API key redacted), viewable under the agent panel",
+        );
+    }
+}
@@ -0,0 +1,27 @@
+use crate::keyboard::{MovementKeyScheme, Shortcut};
+use eframe::egui;
+
+/// Window listing every shortcut, built from `Shortcut::ALL`
+pub struct ShortcutHelp;
+
+impl ShortcutHelp {
+    pub fn draw(ctx: &egui::Context, open: &mut bool, movement_keys: MovementKeyScheme) {
+        egui::Window::new("Keyboard Shortcuts")
+            .open(open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                egui::Grid::new("shortcut_help_grid")
+                    .striped(true)
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        for shortcut in Shortcut::ALL {
+                            ui.label(egui::RichText::new(shortcut.keys(movement_keys)).monospace());
+                            ui.label(shortcut.description());
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+}
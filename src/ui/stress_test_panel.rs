@@ -0,0 +1,87 @@
+use crate::agent::Agent;
+use crate::map::GridMap;
+use crate::stress_test::StressTest;
+use eframe::egui;
+use web_time::Duration;
+
+/// Controls and results for the automated navigation stress test
+pub struct StressTestPanel;
+
+impl StressTestPanel {
+    /// `trials` and `seed` configure the next run; `step_delay` is the tick spacing used
+    /// for the simulated move events
+    pub fn draw(
+        ui: &mut egui::Ui,
+        test: &mut Option<StressTest>,
+        (trials, seed): (&mut usize, &mut u64),
+        map: &GridMap,
+        agent: &Agent,
+        step_delay: Duration,
+    ) {
+        egui::CollapsingHeader::new("Stress Test")
+            .id_source("stress_test")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.label(
+                    egui::RichText::new(
+                        "Navigate between random cells with the built-in pathfinder (no LLM) \
+                        on a copy of this map",
+                    )
+                    .small()
+                    .color(egui::Color32::from_gray(120)),
+                );
+                let running = test.as_ref().is_some_and(|test| !test.is_finished());
+                ui.horizontal(|ui| {
+                    ui.add_enabled(
+                        !running,
+                        egui::DragValue::new(trials).range(1..=5000).suffix(" trials"),
+                    );
+                    ui.add_enabled(!running, egui::DragValue::new(seed).prefix("seed "));
+                    if running {
+                        if ui.button("Stop").clicked()
+                            && let Some(test) = test
+                        {
+                            test.stop();
+                        }
+                    } else if ui.button("Run").clicked() {
+                        *test = Some(StressTest::new(map, agent, *trials, *seed, step_delay));
+                    }
+                });
+
+                let Some(test) = test else {
+                    return;
+                };
+                let stats = test.stats();
+                if running {
+                    let done = stats.trials as f32 / (stats.trials + test.remaining()) as f32;
+                    ui.add(egui::ProgressBar::new(done).show_percentage());
+                }
+                if stats.trials == 0 {
+                    return;
+                }
+                let (plan, run) = stats.average_times();
+                egui::Grid::new("stress_test_results").num_columns(2).show(ui, |ui| {
+                    let rows = [
+                        ("Trials", stats.trials.to_string()),
+                        (
+                            "Reached goal",
+                            format!("{} ({:.1}%)", stats.reached, stats.success_rate()),
+                        ),
+                        ("Failed", stats.failed.to_string()),
+                        ("No route", stats.unreachable.to_string()),
+                        ("Stalled", stats.stalled.to_string()),
+                        ("Lost events", stats.lost_events.to_string()),
+                        ("Moves", stats.moves.to_string()),
+                        ("Avg plan", format!("{:.2} ms", plan.as_secs_f64() * 1000.0)),
+                        ("Avg run", format!("{:.2} ms", run.as_secs_f64() * 1000.0)),
+                        ("Slowest", format!("{:.2} ms", stats.slowest.as_secs_f64() * 1000.0)),
+                    ];
+                    for (name, value) in rows {
+                        ui.label(name);
+                        ui.label(value);
+                        ui.end_row();
+                    }
+                });
+            });
+    }
+}
@@ -0,0 +1,118 @@
+use crate::agent::Agent;
+use crate::map::GridMap;
+use crate::pathfinding::SearchAlgorithm;
+use crate::teach::TeachMode;
+use eframe::egui;
+use web_time::{Duration, Instant};
+
+/// Controls for teach mode: pick a pathfinder and play its search on the board
+pub struct TeachPanel;
+
+impl TeachPanel {
+    pub fn draw(
+        ui: &mut egui::Ui,
+        teach: &mut TeachMode,
+        map: &GridMap,
+        agent: &Agent,
+        now: Instant,
+    ) {
+        egui::CollapsingHeader::new("Teach Mode")
+            .id_source("teach_mode")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_source("teach_algorithm")
+                        .selected_text(teach.algorithm.name())
+                        .show_ui(ui, |ui| {
+                            for algorithm in SearchAlgorithm::ALL {
+                                ui.selectable_value(
+                                    &mut teach.algorithm,
+                                    algorithm,
+                                    algorithm.name(),
+                                );
+                            }
+                        });
+
+                    let goal = agent.goal();
+                    let run = ui
+                        .add_enabled(goal.is_some(), egui::Button::new("Run"))
+                        .on_disabled_hover_text("Right-click the board to set a goal first");
+                    if run.clicked()
+                        && let Some(goal) = goal
+                    {
+                        teach.start(map, agent, goal, now);
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    let has_trace = teach.has_trace();
+                    let play_label = if teach.is_playing() { "Pause" } else { "Play" };
+                    if ui
+                        .add_enabled(has_trace, egui::Button::new(play_label).small())
+                        .clicked()
+                    {
+                        teach.toggle_playing(now);
+                    }
+                    if ui
+                        .add_enabled(
+                            has_trace && !teach.is_playing() && !teach.is_finished(),
+                            egui::Button::new("Step").small(),
+                        )
+                        .clicked()
+                    {
+                        teach.step();
+                    }
+                    if ui
+                        .add_enabled(has_trace, egui::Button::new("Clear").small())
+                        .clicked()
+                    {
+                        teach.clear();
+                    }
+                });
+
+                let mut millis = teach.step_interval.as_millis() as u64;
+                if ui
+                    .add(
+                        egui::Slider::new(&mut millis, 10..=500)
+                            .text("ms / step")
+                            .logarithmic(true),
+                    )
+                    .changed()
+                {
+                    teach.step_interval = Duration::from_millis(millis);
+                }
+
+                if teach.has_trace() {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "{} / {} expanded",
+                            teach.shown_steps(),
+                            teach.total_steps()
+                        ))
+                        .small(),
+                    );
+                }
+                ui.label(
+                    egui::RichText::new(teach.narration())
+                        .small()
+                        .color(egui::Color32::from_gray(90)),
+                );
+                if let Some(explanation) = teach.path_explanation() {
+                    ui.label(
+                        egui::RichText::new(explanation)
+                            .small()
+                            .color(egui::Color32::from_gray(90)),
+                    );
+                }
+                if let Some(explanation) = agent.last_path_explanation() {
+                    ui.add_space(4.0);
+                    ui.label(egui::RichText::new("Agent's last planned route").small().strong());
+                    ui.label(
+                        egui::RichText::new(explanation)
+                            .small()
+                            .color(egui::Color32::from_gray(90)),
+                    );
+                }
+            });
+    }
+}
@@ -0,0 +1,59 @@
+use crate::map::{MAX_MAP_DIMENSION, MIN_MAP_DIMENSION};
+use crate::map_type::{MapTemplate, TemplateParams};
+use eframe::egui;
+
+/// Parameters for the selected generated map template
+pub struct TemplatePanel;
+
+impl TemplatePanel {
+    /// Returns true when the map should be regenerated with the edited `params`
+    pub fn draw(ui: &mut egui::Ui, template: MapTemplate, params: &mut TemplateParams) -> bool {
+        let mut generate = false;
+        ui.horizontal_wrapped(|ui| {
+            ui.label(egui::RichText::new(template.label()).strong());
+            let dimensions = MIN_MAP_DIMENSION..=MAX_MAP_DIMENSION;
+            ui.add(egui::DragValue::new(&mut params.width).range(dimensions.clone()).prefix("w "));
+            ui.add(egui::DragValue::new(&mut params.height).range(dimensions).prefix("h "));
+            ui.add(egui::DragValue::new(&mut params.seed).prefix("seed "));
+            ui.separator();
+
+            match template {
+                MapTemplate::Maze => {
+                    ui.add(
+                        egui::DragValue::new(&mut params.corridor_width)
+                            .range(1..=5)
+                            .prefix("corridor "),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut params.complexity, 0.0..=1.0).text("complexity"),
+                    );
+                }
+                MapTemplate::PerfectMaze => {
+                    ui.weak("size rounds down to odd");
+                }
+                MapTemplate::Rooms => {
+                    ui.add(
+                        egui::DragValue::new(&mut params.room_count)
+                            .range(1..=30)
+                            .suffix(" rooms"),
+                    );
+                    let (min, max) = &mut params.room_size;
+                    ui.add(egui::DragValue::new(min).range(1..=20).prefix("size "));
+                    ui.add(egui::DragValue::new(max).range(*min..=20).prefix("to "));
+                }
+                MapTemplate::Cave => {
+                    ui.add(egui::Slider::new(&mut params.fill_prob, 0.3..=0.7).text("fill"));
+                    ui.add(egui::Slider::new(&mut params.iterations, 0..=10).text("passes"));
+                }
+            }
+            ui.separator();
+
+            generate = ui.button("Generate").clicked();
+            if ui.button("New seed").on_hover_text("Generate with a different seed").clicked() {
+                params.seed = params.seed.wrapping_add(1);
+                generate = true;
+            }
+        });
+        generate
+    }
+}
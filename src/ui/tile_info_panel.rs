@@ -1,4 +1,5 @@
 use crate::agent::Agent;
+use crate::editor::EditorOperations;
 use crate::map::GridMap;
 use eframe::egui;
 
@@ -21,11 +22,29 @@ impl TileInfoPanel {
                 .inner_margin(egui::Margin::same(8.0))
                 .rounding(4.0)
                 .show(ui, |ui| {
-                    ui.label(
-                        egui::RichText::new(format!("Position: ({}, {})", tile_x, tile_y))
-                            .strong()
-                            .color(egui::Color32::from_rgb(50, 80, 120)),
-                    );
+                    ui.horizontal(|ui| {
+                        ui.label(
+                            egui::RichText::new(format!("Position: ({}, {})", tile_x, tile_y))
+                                .strong()
+                                .color(egui::Color32::from_rgb(50, 80, 120)),
+                        );
+                        if ui
+                            .small_button("Copy")
+                            .on_hover_text("Copy as \"x, y\" to paste into an instruction")
+                            .clicked()
+                        {
+                            EditorOperations::copy_to_clipboard(&format!("{}, {}", tile_x, tile_y));
+                        }
+                        if ui
+                            .small_button("Copy JSON")
+                            .on_hover_text("Copy as {\"x\":..,\"y\":..}, the form tools expect")
+                            .clicked()
+                        {
+                            EditorOperations::copy_to_clipboard(
+                                &serde_json::json!({ "x": tile_x, "y": tile_y }).to_string(),
+                            );
+                        }
+                    });
 
                     if let Some(tile_kind) = map.get(tile_x, tile_y) {
                         ui.horizontal(|ui| {
@@ -38,7 +57,7 @@ impl TileInfoPanel {
 
                         ui.horizontal(|ui| {
                             ui.label("Traversable:");
-                            let (icon, color) = if tile_kind.is_traversable() {
+                            let (icon, color) = if agent.traversability().is_passable(tile_kind) {
                                 ("YES", egui::Color32::from_rgb(50, 150, 50))
                             } else {
                                 ("NO", egui::Color32::from_rgb(150, 50, 50))
@@ -46,6 +65,13 @@ impl TileInfoPanel {
                             ui.label(egui::RichText::new(icon).color(color).strong());
                         });
 
+                        if map.has_elevation() {
+                            ui.horizontal(|ui| {
+                                ui.label("Elevation:");
+                                ui.label(map.elevation(tile_x, tile_y).to_string());
+                            });
+                        }
+
                         // Show if agent is on this tile
                         if tile_x == agent.x && tile_y == agent.y {
                             ui.add_space(4.0);
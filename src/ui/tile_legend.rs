@@ -0,0 +1,49 @@
+use crate::map::TileKind;
+use crate::rendering::{
+    draw_fuel_tile, draw_grass_tile, draw_portal_tile, draw_sand_tile, draw_tree_sprite,
+    draw_wall_tile, draw_water_tile, TilePalette,
+};
+use eframe::egui;
+
+/// Swatch-and-name key for the tiles on the board, drawn in the active palette
+pub struct TileLegend;
+
+impl TileLegend {
+    const ENTRIES: [(TileKind, &'static str); 7] = [
+        (TileKind::Grass, "Grass"),
+        (TileKind::Sand, "Sand"),
+        (TileKind::Water, "Water"),
+        (TileKind::Wall, "Wall"),
+        (TileKind::Tree, "Tree"),
+        (TileKind::Portal(1), "Portal"),
+        (TileKind::Fuel, "Fuel"),
+    ];
+
+    pub fn draw(
+        ui: &mut egui::Ui,
+        palette: TilePalette,
+        tree_tex: Option<&egui::TextureHandle>,
+    ) {
+        for (kind, label) in Self::ENTRIES {
+            ui.horizontal(|ui| {
+                let (rect, _) =
+                    ui.allocate_exact_size(egui::vec2(18.0, 18.0), egui::Sense::hover());
+                let painter = ui.painter_at(rect);
+                match kind {
+                    TileKind::Grass => draw_grass_tile(&painter, rect, palette),
+                    TileKind::Sand => draw_sand_tile(&painter, rect, palette),
+                    TileKind::Water => draw_water_tile(&painter, rect, 0, palette),
+                    TileKind::Wall => draw_wall_tile(&painter, rect, palette),
+                    TileKind::Tree => match tree_tex {
+                        Some(tex) => draw_tree_sprite(&painter, rect, tex),
+                        None => draw_grass_tile(&painter, rect, palette),
+                    },
+                    TileKind::Portal(id) => draw_portal_tile(&painter, rect, id, palette),
+                    TileKind::Fuel => draw_fuel_tile(&painter, rect, palette),
+                    _ => {}
+                }
+                ui.label(egui::RichText::new(label).small());
+            });
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use crate::tool_execution::PendingApproval;
+use eframe::egui;
+
+/// Card asking the user to approve or reject a held-back map-mutating tool call
+pub struct ToolApprovalCard;
+
+impl ToolApprovalCard {
+    /// Returns Some(true) on Approve, Some(false) on Reject
+    pub fn draw(ui: &mut egui::Ui, call: &PendingApproval) -> Option<bool> {
+        let mut decision = None;
+        egui::Frame::default()
+            .fill(egui::Color32::from_rgb(255, 243, 220))
+            .inner_margin(egui::Margin::same(8.0))
+            .rounding(4.0)
+            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(220, 160, 60)))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new("APPROVE?").strong());
+                    ui.label(
+                        egui::RichText::new(&call.name)
+                            .strong()
+                            .color(egui::Color32::from_rgb(200, 100, 0)),
+                    );
+                });
+                ui.label(
+                    egui::RichText::new(serde_json::to_string_pretty(&call.args).unwrap_or_default())
+                        .small()
+                        .color(egui::Color32::from_rgb(100, 80, 40))
+                        .font(egui::FontId::monospace(10.0)),
+                );
+                ui.horizontal(|ui| {
+                    if ui.button("Approve").clicked() {
+                        decision = Some(true);
+                    }
+                    if ui.button("Reject").clicked() {
+                        decision = Some(false);
+                    }
+                });
+            });
+        ui.add_space(4.0);
+        decision
+    }
+}